@@ -0,0 +1,106 @@
+//! JSON lines decoding benchmarks.
+//!
+//! Compares decoding of JSON-RPC messages using the codec against
+//! the previous decoding path: splitting a `String` line with [`LinesCodec`]
+//! and deserializing it with `serde_json::from_str` into a message
+//! with an `untagged` identifier and boxed raw value parameters.
+//!
+//! Run with `cargo bench -p net3_codec_json_lines`.
+//!
+//! [`LinesCodec`]: https://docs.rs/tokio-util/0.3.1/tokio_util/codec/struct.LinesCodec.html
+
+use bytes::BytesMut;
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use serde_derive::Deserialize;
+use serde_json::value::RawValue;
+use tokio_util::codec::{Decoder, LinesCodec};
+
+use net3_codec_json_lines::Codec;
+use net3_proto_jsonrpc::{Error, Message, Version};
+
+/// Number of messages decoded in a single iteration.
+const MESSAGES: usize = 100;
+
+/// Stratum share submission request.
+const SUBMIT: &str = r#"{"id":4,"method":"mining.submit","params":["worker.1","bf","00000001","504e86ed","b2957c02"]}"#;
+
+/// Previous message identifier.
+#[derive(Deserialize)]
+#[serde(untagged)]
+#[allow(dead_code)]
+enum PreviousId {
+    Null,
+    Str(String),
+    Num(u64),
+}
+
+/// Previous message layout.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+#[allow(dead_code)]
+struct PreviousMessage {
+    #[serde(default)]
+    jsonrpc: Option<Version>,
+    #[serde(default)]
+    id: Option<PreviousId>,
+    method: Option<String>,
+    params: Option<Box<RawValue>>,
+    result: Option<Box<RawValue>>,
+    error: Option<Error>,
+}
+
+/// Creates a job notification with `size` bytes of parameters.
+fn notify(size: usize) -> String {
+    format!(
+        r#"{{"method":"mining.notify","params":["{}"]}}"#,
+        "f".repeat(size)
+    )
+}
+
+/// Creates a read buffer containing `MESSAGES` lines.
+fn buffer(line: &str) -> BytesMut {
+    let mut buf = BytesMut::with_capacity((line.len() + 1) * MESSAGES);
+    for _ in 0..MESSAGES {
+        buf.extend_from_slice(line.as_bytes());
+        buf.extend_from_slice(b"\n");
+    }
+    buf
+}
+
+/// Benchmarks both decoding paths of `MESSAGES` lines in a group.
+fn bench_lines(c: &mut Criterion, name: &str, line: &str) {
+    let buf = buffer(line);
+    let mut group = c.benchmark_group(name);
+    group.throughput(Throughput::Bytes(buf.len() as u64));
+    group.bench_function("previous", |b| {
+        b.iter(|| {
+            let mut src = buf.clone();
+            let mut codec = LinesCodec::new();
+            while let Some(line) = codec.decode(&mut src).unwrap() {
+                let message: PreviousMessage = serde_json::from_str(&line).unwrap();
+                black_box(message);
+            }
+        })
+    });
+    group.bench_function("codec", |b| {
+        b.iter(|| {
+            let mut src = buf.clone();
+            let mut codec = Codec::<Message>::default();
+            while let Some(message) = codec.decode(&mut src).unwrap() {
+                black_box(message);
+            }
+        })
+    });
+    group.finish();
+}
+
+fn submit(c: &mut Criterion) {
+    bench_lines(c, "submit", SUBMIT);
+}
+
+fn notify_4k(c: &mut Criterion) {
+    bench_lines(c, "notify_4k", &notify(4096));
+}
+
+criterion_group!(benches, submit, notify_4k);
+criterion_main!(benches);
@@ -3,16 +3,41 @@
 //! Serialization and deserialization is done with [`serde_json`].
 //! Messages are decoded and encoded with a new line separator.
 //!
+//! Incoming lines are checked against codec [`Limits`] before deserialization.
+//!
+//! Codec of [`Batch`] messages decodes array lines as batches of messages
+//! and encodes batches as array lines.
+//!
+//! Lines are deserialized straight from the read buffer without copying them.
+//! Deserialization runs inside of a [`frame::scope`] so message payloads
+//! can reference the shared frame buffer.
+//!
+//! Codec options implementing [`Scope`] are in effect while messages
+//! are deserialized and serialized, see [`Codec::with_options`].
+//!
 //! [`serde_json`]: https://docs.rs/serde_json/1/serde_json/
+//! [`Limits`]: ../net3_codec_limits/struct.Limits.html
+//! [`Batch`]: ../net3_msg/batch/enum.Batch.html
+//! [`frame::scope`]: ../net3_msg/frame/fn.scope.html
+//! [`Scope`]: ../net3_msg/scope/trait.Scope.html
+//! [`Codec::with_options`]: struct.Codec.html#method.with_options
 
 use std::io::{Error, ErrorKind};
 
 use bytes::{
     buf::{ext::BufMutExt, BufMut},
-    BytesMut,
+    Buf, Bytes, BytesMut,
 };
 use serde::{de::DeserializeOwned, Serialize};
-use tokio_util::codec::{Decoder, Encoder, LinesCodec};
+use serde_json::error::Category;
+use tokio_util::codec::{Decoder, Encoder};
+
+use net3_msg::{frame, scope::Scope};
+
+pub use net3_codec_limits::{InvalidFrame, LimitError, LimitedCodec, Limits};
+
+#[cfg(test)]
+mod tests;
 
 /// JSON lines channel message codec.
 ///
@@ -23,53 +48,250 @@ use tokio_util::codec::{Decoder, Encoder, LinesCodec};
 /// [`Decoder`]: https://docs.rs/tokio-util/0.3.1/tokio_util/codec/trait.Decoder.html
 /// [`Serialize`]: https://docs.rs/serde/1/serde/ser/trait.Serialize.html
 /// [`DeserializeOwned`]: https://docs.rs/serde/1/serde/de/trait.DeserializeOwned.html
-#[derive(Default)]
-pub struct Codec<T> {
-    inner: LinesCodec,
+pub struct Codec<T, O = ()> {
+    /// Index of the next byte to search for a line separator.
+    next_index: usize,
+    /// Rest of a line exceeding the frame limit is skipped.
+    is_discarding: bool,
+    limits: Limits,
+    options: O,
     phantom: std::marker::PhantomData<T>,
 }
 
-/// Deserializes the message using [`serde_json::from_str`].
+impl<T, O: Default> Codec<T, O> {
+    /// Creates a new codec enforcing given limits.
+    pub fn with_limits(limits: Limits) -> Self {
+        Self::new(limits, O::default())
+    }
+}
+
+impl<T, O> Codec<T, O> {
+    /// Creates a new codec with given options and default limits.
+    ///
+    /// Options are in effect while messages are deserialized and serialized.
+    pub fn with_options(options: O) -> Self {
+        Self::new(Limits::default(), options)
+    }
+
+    /// Creates a new codec with given limits and options.
+    pub fn new(limits: Limits, options: O) -> Self {
+        Codec {
+            next_index: 0,
+            is_discarding: false,
+            limits,
+            options,
+            phantom: std::marker::PhantomData,
+        }
+    }
+
+    /// Returns codec options.
+    #[inline]
+    pub fn options(&self) -> &O {
+        &self.options
+    }
+
+    /// Returns mutable codec options.
+    #[inline]
+    pub fn options_mut(&mut self) -> &mut O {
+        &mut self.options
+    }
+}
+
+impl<T: DeserializeOwned, O: Scope> Codec<T, O> {
+    /// Deserializes a line without the separator.
+    #[inline(always)]
+    fn decode_line(&self, line: Bytes) -> Result<T, Error> {
+        if cfg!(debug_assertions) {
+            log::trace!("JSON deserialize body={}", String::from_utf8_lossy(&line));
+        }
+        check_limits(&line, &self.limits)?;
+        self.options
+            .scope(|| frame::scope(&line, || serde_json::from_slice(&line)))
+            .map_err(|err: serde_json::Error| match err.classify() {
+                Category::Syntax | Category::Eof => InvalidFrame::malformed(err).into(),
+                _ => InvalidFrame::new(err).into(),
+            })
+    }
+}
+
+impl<T, O: Default> Default for Codec<T, O> {
+    fn default() -> Self {
+        Self::with_limits(Limits::default())
+    }
+}
+
+impl<T, O> LimitedCodec for Codec<T, O> {
+    fn limits(&self) -> &Limits {
+        &self.limits
+    }
+
+    fn set_limits(&mut self, limits: Limits) {
+        // Partially received line is searched again with the new limit.
+        self.next_index = 0;
+        self.limits = limits;
+    }
+}
+
+/// Deserializes the message using [`serde_json::from_slice`].
 ///
-/// [`serde_json::from_str`]: https://docs.rs/serde_json/1/serde_json/fn.from_str.html
-impl<T: DeserializeOwned + Sized> Decoder for Codec<T> {
+/// [`serde_json::from_slice`]: https://docs.rs/serde_json/1/serde_json/fn.from_slice.html
+impl<T: DeserializeOwned + Sized, O: Scope> Decoder for Codec<T, O> {
     type Item = T;
     type Error = Error;
 
     #[inline(always)]
     fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
-        match self
-            .inner
-            .decode(src)
-            .map_err(|err| Error::new(ErrorKind::InvalidData, err))?
-        {
-            Some(msg) => {
-                if cfg!(debug_assertions) {
-                    log::trace!("JSON deserialize body={}", msg);
+        if self.is_discarding {
+            match memchr::memchr(b'\n', src) {
+                Some(offset) => {
+                    src.advance(offset + 1);
+                    self.is_discarding = false;
                 }
-                Ok(serde_json::from_str(&msg)
-                    .map_err(|err| Error::new(ErrorKind::InvalidData, err))?)
+                None => {
+                    src.clear();
+                    return Ok(None);
+                }
+            }
+        }
+        // Search for a separator only up to the frame limit.
+        let read_to = std::cmp::min(self.limits.max_frame_length.saturating_add(1), src.len())
+            .max(self.next_index);
+        match memchr::memchr(b'\n', &src[self.next_index..read_to]) {
+            Some(offset) => {
+                let newline_index = self.next_index + offset;
+                self.next_index = 0;
+                let line = src.split_to(newline_index + 1).freeze();
+                let line = line.slice(..without_carriage_return(&line[..newline_index]));
+                Ok(Some(self.decode_line(line)?))
+            }
+            None if src.len() > self.limits.max_frame_length => {
+                // Skip the oversized line so following lines can be decoded.
+                self.next_index = 0;
+                match memchr::memchr(b'\n', &src[read_to..]) {
+                    Some(offset) => src.advance(read_to + offset + 1),
+                    None => {
+                        src.clear();
+                        self.is_discarding = true;
+                    }
+                }
+                Err(LimitError::FrameTooLarge {
+                    limit: self.limits.max_frame_length,
+                }
+                .into())
+            }
+            None => {
+                self.next_index = read_to;
+                Ok(None)
+            }
+        }
+    }
+
+    fn decode_eof(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        match self.decode(src)? {
+            Some(item) => Ok(Some(item)),
+            // No terminating separator - decode remaining data, if any
+            None if src.is_empty() || &src[..] == b"\r" => Ok(None),
+            None => {
+                self.next_index = 0;
+                let line = src.split_to(src.len()).freeze();
+                let line = line.slice(..without_carriage_return(&line));
+                Ok(Some(self.decode_line(line)?))
             }
-            None => Ok(None),
         }
     }
 }
 
-impl<T: Serialize + Sized> Encoder<T> for Codec<T> {
+impl<T: Serialize + Sized, O: Scope> Encoder<T> for Codec<T, O> {
     type Error = Error;
 
     #[inline(always)]
     fn encode(&mut self, item: T, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        let start = dst.len();
         if cfg!(debug_assertions) {
-            let body = serde_json::to_string(&item)
+            let body = self
+                .options
+                .scope(|| serde_json::to_string(&item))
                 .map_err(|err| Error::new(ErrorKind::InvalidData, err))?;
             log::trace!("JSON codec body={}", body);
             dst.put_slice(body.as_bytes());
         } else {
-            serde_json::to_writer(dst.writer(), &item)
+            self.options
+                .scope(|| serde_json::to_writer(dst.writer(), &item))
                 .map_err(|err| Error::new(ErrorKind::InvalidData, err))?;
         }
+        if let Err(err) = self.limits.check_frame_length(dst.len() - start) {
+            dst.truncate(start);
+            return Err(err.into());
+        }
         dst.put_slice(b"\n");
         Ok(())
     }
 }
+
+/// Returns length of a line without trailing carriage return.
+#[inline(always)]
+fn without_carriage_return(line: &[u8]) -> usize {
+    match line.last() {
+        Some(b'\r') => line.len() - 1,
+        _ => line.len(),
+    }
+}
+
+/// Returns upper bound of nesting depth by counting opening brackets.
+#[inline(always)]
+fn count_brackets(body: &[u8]) -> usize {
+    // Counting in `u8` chunks lets the compiler vectorize the loop.
+    // Opening brackets `[` and `{` differ only by `0x20` bit.
+    body.chunks(255)
+        .map(|chunk| {
+            chunk
+                .iter()
+                .fold(0u8, |n, b| n + ((*b | 0x20) == b'{') as u8) as usize
+        })
+        .sum()
+}
+
+/// Checks nesting depth and string lengths of a JSON document.
+///
+/// It does not validate the document, syntax errors are left to the deserializer.
+pub fn check_limits(body: &[u8], limits: &Limits) -> Result<(), LimitError> {
+    // Skip scanning when neither of the limits can be exceeded.
+    if body.len() <= limits.max_string_length && count_brackets(body) <= limits.max_depth {
+        return Ok(());
+    }
+    let mut depth = 0usize;
+    let mut index = 0usize;
+    while index < body.len() {
+        match body[index] {
+            b'"' => {
+                // Skip to the closing quote, escaped characters are counted as encoded.
+                let start = index + 1;
+                let mut end = start;
+                loop {
+                    let rest = body.get(end..).unwrap_or_default();
+                    match rest.iter().position(|b| *b == b'"' || *b == b'\\') {
+                        Some(offset) if rest[offset] == b'\\' => end += offset + 2,
+                        Some(offset) => {
+                            end += offset;
+                            break;
+                        }
+                        None => {
+                            end = body.len();
+                            break;
+                        }
+                    }
+                }
+                limits.check_string_length(end - start)?;
+                index = end;
+            }
+            b'[' | b'{' => {
+                depth += 1;
+                limits.check_depth(depth)?;
+            }
+            b']' | b'}' => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+        index += 1;
+    }
+    Ok(())
+}
@@ -0,0 +1,219 @@
+use bytes::BytesMut;
+use serde_json::Value;
+use tokio_util::codec::{Decoder, Encoder};
+
+use crate::*;
+
+fn decode(limits: Limits, body: &str) -> std::io::Result<Option<Value>> {
+    let mut codec = Codec::<Value>::with_limits(limits);
+    codec.decode(&mut BytesMut::from(body))
+}
+
+fn limit_error(result: std::io::Result<Option<Value>>) -> LimitError {
+    *LimitError::from_io(&result.unwrap_err()).expect("limit error")
+}
+
+#[test]
+fn decode_within_limits() {
+    let limits = Limits::default()
+        .with_max_frame_length(32)
+        .with_max_depth(2)
+        .with_max_string_length(4);
+    let value = decode(limits, "{\"a\":[\"test\"]}\n").unwrap().unwrap();
+    assert_eq!(value, serde_json::json!({"a": ["test"]}));
+}
+
+#[test]
+fn decode_frame_too_large() {
+    let limits = Limits::default().with_max_frame_length(8);
+    assert_eq!(
+        limit_error(decode(limits, "[1,2,3,4,5,6]\n")),
+        LimitError::FrameTooLarge { limit: 8 }
+    );
+    // Limit is enforced before a line separator is received.
+    assert_eq!(
+        limit_error(decode(limits, "[1,2,3,4,5,6")),
+        LimitError::FrameTooLarge { limit: 8 }
+    );
+}
+
+#[test]
+fn decode_after_frame_too_large() {
+    let mut codec = Codec::<Value>::with_limits(Limits::default().with_max_frame_length(8));
+    let mut src = BytesMut::from("[1,2,3,4,5,6]\n[1]\n");
+    assert_eq!(
+        limit_error(codec.decode(&mut src)),
+        LimitError::FrameTooLarge { limit: 8 }
+    );
+    // Oversized line is skipped and following lines are decoded.
+    assert_eq!(
+        codec.decode(&mut src).unwrap(),
+        Some(serde_json::json!([1]))
+    );
+}
+
+#[test]
+fn decode_with_lowered_limits() {
+    let mut codec = Codec::<Value>::default();
+    let mut src = BytesMut::from("[1,2,3,4,5,6");
+    assert_eq!(codec.decode(&mut src).unwrap(), None);
+    // Partially received line is checked against the new limit.
+    codec.set_limits(Limits::default().with_max_frame_length(8));
+    src.extend_from_slice(b"]\n[1]\n");
+    assert_eq!(
+        limit_error(codec.decode(&mut src)),
+        LimitError::FrameTooLarge { limit: 8 }
+    );
+    assert_eq!(
+        codec.decode(&mut src).unwrap(),
+        Some(serde_json::json!([1]))
+    );
+}
+
+#[test]
+fn decode_nesting_too_deep() {
+    let limits = Limits::default().with_max_depth(2);
+    assert_eq!(
+        limit_error(decode(limits, "[[[]]]\n")),
+        LimitError::NestingTooDeep { limit: 2 }
+    );
+    // Brackets inside of strings are not counted.
+    assert!(decode(limits, "[\"[[[\"]\n").is_ok());
+}
+
+#[test]
+fn decode_string_too_long() {
+    let limits = Limits::default().with_max_string_length(4);
+    assert_eq!(
+        limit_error(decode(limits, "[\"test1\"]\n")),
+        LimitError::StringTooLong { limit: 4 }
+    );
+    assert!(decode(limits, "[\"t\\\"t\"]\n").is_ok());
+}
+
+#[test]
+fn encode_frame_too_large() {
+    let mut codec = Codec::<Value>::with_limits(Limits::default().with_max_frame_length(4));
+    let mut dst = BytesMut::new();
+    let err = codec
+        .encode(serde_json::json!([1, 2, 3]), &mut dst)
+        .unwrap_err();
+    assert_eq!(
+        LimitError::from_io(&err),
+        Some(&LimitError::FrameTooLarge { limit: 4 })
+    );
+    assert!(dst.is_empty());
+}
+
+#[test]
+fn decode_references_frame() {
+    use net3_proto_jsonrpc::Message;
+
+    let mut src = BytesMut::from("{\"id\":1,\"method\":\"submit\",\"params\":[\"share\"]}\r\n");
+    let range = src.as_ptr() as usize..src.as_ptr() as usize + src.len();
+    let message: Message = Codec::<Message>::default()
+        .decode(&mut src)
+        .unwrap()
+        .unwrap();
+    let params = message.params.value.expect("params");
+    assert_eq!(params.get(), "[\"share\"]");
+    assert!(range.contains(&(params.bytes().as_ptr() as usize)));
+}
+
+#[test]
+fn decode_eof_without_separator() {
+    let mut codec = Codec::<Value>::default();
+    let mut src = BytesMut::from("[1]\n[2]");
+    assert_eq!(
+        codec.decode(&mut src).unwrap(),
+        Some(serde_json::json!([1]))
+    );
+    assert_eq!(codec.decode(&mut src).unwrap(), None);
+    assert_eq!(
+        codec.decode_eof(&mut src).unwrap(),
+        Some(serde_json::json!([2]))
+    );
+    assert_eq!(codec.decode_eof(&mut src).unwrap(), None);
+}
+
+#[test]
+fn codec_options_scope() {
+    use net3_msg::prelude::*;
+    use net3_proto_jsonrpc::{Message, Options};
+
+    let options = Options::default().with_metadata_field("_meta");
+    let mut codec = Codec::<Message, Options>::with_options(options);
+    let message = Message::new_request(Id::Num(1), "test").with_meta("trace", "abc");
+    let mut dst = BytesMut::new();
+    codec.encode(message, &mut dst).unwrap();
+    assert_eq!(
+        &dst[..],
+        &b"{\"jsonrpc\":\"2.0\",\"id\":1,\"method\":\"test\",\"_meta\":{\"trace\":\"abc\"}}\n"[..]
+    );
+    let message = codec.decode(&mut dst).unwrap().unwrap();
+    assert_eq!(message.meta("trace"), Some("abc"));
+}
+
+#[test]
+fn decode_after_invalid_frame() {
+    let mut codec = Codec::<Value>::default();
+    let mut src = BytesMut::from("{\"id\":\n[1]\n");
+    let err = codec.decode(&mut src).unwrap_err();
+    assert!(InvalidFrame::from_io(&err).unwrap().is_malformed());
+    assert!(net3_codec_limits::is_recoverable(&err));
+    assert_eq!(
+        codec.decode(&mut src).unwrap(),
+        Some(serde_json::json!([1]))
+    );
+}
+
+#[test]
+fn decode_invalid_message() {
+    let mut codec = Codec::<Vec<u32>>::default();
+    let mut src = BytesMut::from("{\"id\":1}\n[1]\n");
+    let err = codec.decode(&mut src).unwrap_err();
+    assert!(!InvalidFrame::from_io(&err).unwrap().is_malformed());
+    assert!(net3_codec_limits::is_recoverable(&err));
+    assert_eq!(codec.decode(&mut src).unwrap(), Some(vec![1]));
+}
+
+#[test]
+fn decode_batch() {
+    use net3_msg::batch::Batch;
+    use net3_proto_jsonrpc::Message;
+
+    let mut codec = Codec::<Batch<Message>>::default();
+    let mut src = BytesMut::from(
+        "[{\"id\":1,\"method\":\"a\",\"params\":[]},{\"method\":\"b\"}]\n{\"id\":2,\"method\":\"c\"}\n[]\n",
+    );
+    match codec.decode(&mut src).unwrap().unwrap() {
+        Batch::Many(messages) => {
+            assert_eq!(messages.len(), 2);
+            assert_eq!(messages[0].method.as_deref(), Some("a"));
+            assert_eq!(messages[1].method.as_deref(), Some("b"));
+        }
+        batch => panic!("unexpected batch: {:?}", batch),
+    }
+    match codec.decode(&mut src).unwrap().unwrap() {
+        Batch::Single(message) => assert_eq!(message.method.as_deref(), Some("c")),
+        batch => panic!("unexpected batch: {:?}", batch),
+    }
+    assert_eq!(codec.decode(&mut src).unwrap(), Some(Batch::Many(vec![])));
+}
+
+#[test]
+fn encode_batch() {
+    use net3_msg::batch::Batch;
+
+    let mut codec = Codec::<Batch<Value>>::default();
+    let mut dst = BytesMut::new();
+    let batch = Batch::Many(vec![
+        serde_json::json!({"id": 1}),
+        serde_json::json!({"id": 2}),
+    ]);
+    codec.encode(batch, &mut dst).unwrap();
+    codec
+        .encode(Batch::Single(serde_json::json!({"id": 3})), &mut dst)
+        .unwrap();
+    assert_eq!(&dst[..], &b"[{\"id\":1},{\"id\":2}]\n{\"id\":3}\n"[..]);
+}
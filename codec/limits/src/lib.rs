@@ -0,0 +1,228 @@
+//! Frame size and resource limits shared by channel codecs.
+//!
+//! Every codec enforces the same [`Limits`] on incoming frames.
+//! Violations are reported as [`InvalidData`] errors wrapping a [`LimitError`],
+//! which can be recovered with [`LimitError::from_io`].
+//!
+//! [`Limits`]: struct.Limits.html
+//! [`LimitError`]: enum.LimitError.html
+//! [`LimitError::from_io`]: enum.LimitError.html#method.from_io
+//! [`InvalidData`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.InvalidData
+//!
+//! Frames read whole which can not be decoded into a message are reported
+//! as [`InvalidFrame`] errors. Such errors, as well as limit errors of frames
+//! read whole, are [recoverable] and the channel can keep reading frames.
+//!
+//! [`InvalidFrame`]: struct.InvalidFrame.html
+//! [recoverable]: fn.is_recoverable.html
+
+use std::{fmt, io};
+
+/// Default maximum frame length in bytes (8 MiB).
+pub const DEFAULT_MAX_FRAME_LENGTH: usize = 8 * 1024 * 1024;
+
+/// Default maximum nesting depth of arrays and objects.
+pub const DEFAULT_MAX_DEPTH: usize = 64;
+
+/// Default maximum length of a single string in bytes (1 MiB).
+pub const DEFAULT_MAX_STRING_LENGTH: usize = 1024 * 1024;
+
+/// Codec resource limits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Limits {
+    /// Maximum length of a single frame in bytes.
+    pub max_frame_length: usize,
+
+    /// Maximum nesting depth of arrays and objects.
+    pub max_depth: usize,
+
+    /// Maximum length of a single string or binary value in bytes.
+    pub max_string_length: usize,
+}
+
+impl Limits {
+    /// Creates limits that effectively do not restrict anything.
+    pub fn unlimited() -> Self {
+        Limits {
+            max_frame_length: usize::MAX,
+            max_depth: usize::MAX,
+            max_string_length: usize::MAX,
+        }
+    }
+
+    /// Sets maximum frame length in bytes.
+    #[inline]
+    pub fn with_max_frame_length(mut self, length: usize) -> Self {
+        self.max_frame_length = length;
+        self
+    }
+
+    /// Sets maximum nesting depth.
+    #[inline]
+    pub fn with_max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = depth;
+        self
+    }
+
+    /// Sets maximum string length in bytes.
+    #[inline]
+    pub fn with_max_string_length(mut self, length: usize) -> Self {
+        self.max_string_length = length;
+        self
+    }
+
+    /// Checks frame length against the limit.
+    #[inline]
+    pub fn check_frame_length(&self, length: usize) -> Result<(), LimitError> {
+        if length > self.max_frame_length {
+            Err(LimitError::FrameTooLarge {
+                limit: self.max_frame_length,
+            })
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Checks nesting depth against the limit.
+    #[inline]
+    pub fn check_depth(&self, depth: usize) -> Result<(), LimitError> {
+        if depth > self.max_depth {
+            Err(LimitError::NestingTooDeep {
+                limit: self.max_depth,
+            })
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Checks string length against the limit.
+    #[inline]
+    pub fn check_string_length(&self, length: usize) -> Result<(), LimitError> {
+        if length > self.max_string_length {
+            Err(LimitError::StringTooLong {
+                limit: self.max_string_length,
+            })
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits {
+            max_frame_length: DEFAULT_MAX_FRAME_LENGTH,
+            max_depth: DEFAULT_MAX_DEPTH,
+            max_string_length: DEFAULT_MAX_STRING_LENGTH,
+        }
+    }
+}
+
+/// Codec limit violation error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, err_derive::Error)]
+pub enum LimitError {
+    /// Frame exceeds maximum frame length.
+    #[error(display = "frame exceeds limit of {} bytes", limit)]
+    FrameTooLarge { limit: usize },
+
+    /// Message exceeds maximum nesting depth.
+    #[error(display = "nesting exceeds depth limit of {}", limit)]
+    NestingTooDeep { limit: usize },
+
+    /// String exceeds maximum string length.
+    #[error(display = "string exceeds limit of {} bytes", limit)]
+    StringTooLong { limit: usize },
+}
+
+impl LimitError {
+    /// Returns the limit error wrapped in an IO error, if any.
+    pub fn from_io(err: &io::Error) -> Option<&LimitError> {
+        err.get_ref().and_then(|inner| inner.downcast_ref())
+    }
+}
+
+impl From<LimitError> for io::Error {
+    fn from(err: LimitError) -> io::Error {
+        io::Error::new(io::ErrorKind::InvalidData, err)
+    }
+}
+
+/// Frame read whole which could not be decoded into a message.
+///
+/// The frame is consumed by the codec, following frames can be read.
+#[derive(Debug)]
+pub struct InvalidFrame {
+    cause: Box<dyn std::error::Error + Send + Sync>,
+    malformed: bool,
+}
+
+impl InvalidFrame {
+    /// Creates a new error with a cause of the decoding failure.
+    ///
+    /// The frame is well formed but it is not a valid message.
+    pub fn new<E: Into<Box<dyn std::error::Error + Send + Sync>>>(err: E) -> Self {
+        InvalidFrame {
+            cause: err.into(),
+            malformed: false,
+        }
+    }
+
+    /// Creates a new error of a frame which could not be parsed.
+    pub fn malformed<E: Into<Box<dyn std::error::Error + Send + Sync>>>(err: E) -> Self {
+        InvalidFrame {
+            cause: err.into(),
+            malformed: true,
+        }
+    }
+
+    /// Returns true if the frame could not be parsed.
+    pub fn is_malformed(&self) -> bool {
+        self.malformed
+    }
+
+    /// Returns the invalid frame error wrapped in an IO error, if any.
+    pub fn from_io(err: &io::Error) -> Option<&InvalidFrame> {
+        err.get_ref().and_then(|inner| inner.downcast_ref())
+    }
+}
+
+impl fmt::Display for InvalidFrame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.cause, f)
+    }
+}
+
+impl std::error::Error for InvalidFrame {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&*self.cause)
+    }
+}
+
+impl From<InvalidFrame> for io::Error {
+    fn from(err: InvalidFrame) -> io::Error {
+        io::Error::new(io::ErrorKind::InvalidData, err)
+    }
+}
+
+/// Returns true if a decoding error consumed its frame.
+///
+/// Channel can keep reading frames after such errors
+/// and answer the peer instead of closing the connection.
+pub fn is_recoverable(err: &io::Error) -> bool {
+    match LimitError::from_io(err) {
+        Some(LimitError::FrameTooLarge { .. }) => false,
+        Some(_) => true,
+        None => InvalidFrame::from_io(err).is_some(),
+    }
+}
+
+/// Codec with configurable resource limits.
+pub trait LimitedCodec {
+    /// Returns limits enforced by the codec.
+    fn limits(&self) -> &Limits;
+
+    /// Sets limits enforced by the codec.
+    ///
+    /// Partially received frame is checked against the new limits.
+    fn set_limits(&mut self, limits: Limits);
+}
@@ -1,27 +1,83 @@
 //! Msgpack message channel encoder and decoder implementation.
+//!
+//! Incoming frames are checked against codec [`Limits`] before deserialization.
+//!
+//! [`Limits`]: ../net3_codec_limits/struct.Limits.html
 
 use std::io::{Error, ErrorKind, Result};
 
 use bytes::{buf::ext::BufExt, BytesMut};
 use serde::{de::DeserializeOwned, Serialize};
-use tokio_util::codec::{Decoder, Encoder, LengthDelimitedCodec};
+use tokio_util::codec::{Decoder, Encoder, LengthDelimitedCodec, LengthDelimitedCodecError};
+
+pub use net3_codec_limits::{InvalidFrame, LimitError, LimitedCodec, Limits};
+
+#[cfg(test)]
+mod tests;
 
 /// Msgpack message channel codec.
-#[derive(Default)]
 pub struct Codec<T> {
     inner: LengthDelimitedCodec,
+    limits: Limits,
     _marker: std::marker::PhantomData<T>,
 }
 
+impl<T> Codec<T> {
+    /// Creates a new codec enforcing given limits.
+    pub fn with_limits(limits: Limits) -> Self {
+        let mut inner = LengthDelimitedCodec::new();
+        inner.set_max_frame_length(limits.max_frame_length);
+        Codec {
+            inner,
+            limits,
+            _marker: std::marker::PhantomData,
+        }
+    }
+
+    /// Converts frame length errors of the inner codec to [`LimitError`].
+    ///
+    /// [`LimitError`]: ../net3_codec_limits/enum.LimitError.html
+    fn map_frame_error(&self, err: Error) -> Error {
+        match err.get_ref() {
+            Some(inner) if inner.is::<LengthDelimitedCodecError>() => LimitError::FrameTooLarge {
+                limit: self.limits.max_frame_length,
+            }
+            .into(),
+            _ => err,
+        }
+    }
+}
+
+impl<T> Default for Codec<T> {
+    fn default() -> Self {
+        Self::with_limits(Limits::default())
+    }
+}
+
+impl<T> LimitedCodec for Codec<T> {
+    fn limits(&self) -> &Limits {
+        &self.limits
+    }
+
+    fn set_limits(&mut self, limits: Limits) {
+        self.inner.set_max_frame_length(limits.max_frame_length);
+        self.limits = limits;
+    }
+}
+
 impl<T: DeserializeOwned + Sized> Decoder for Codec<T> {
     type Item = T;
     type Error = Error;
 
     #[inline]
     fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>> {
-        if let Some(msg) = self.inner.decode(src)? {
-            rmp_serde::from_read(msg.reader())
-                .map_err(|err| Error::new(ErrorKind::InvalidData, err))
+        let frame = self
+            .inner
+            .decode(src)
+            .map_err(|err| self.map_frame_error(err))?;
+        if let Some(msg) = frame {
+            check_limits(&msg, &self.limits)?;
+            rmp_serde::from_read(msg.reader()).map_err(|err| InvalidFrame::new(err).into())
         } else {
             Ok(None)
         }
@@ -33,8 +89,86 @@ impl<T: Serialize + Sized> Encoder<T> for Codec<T> {
 
     #[inline]
     fn encode(&mut self, item: T, dst: &mut BytesMut) -> Result<()> {
-        let body =
-            rmp_serde::to_vec(&item).map_err(|err| Error::new(ErrorKind::InvalidData, err))?;
-        Ok(self.inner.encode(body.into(), dst)?)
+        // Structs are encoded as maps, positional encoding breaks on skipped optional fields.
+        let body = rmp_serde::to_vec_named(&item)
+            .map_err(|err| Error::new(ErrorKind::InvalidData, err))?;
+        self.inner
+            .encode(body.into(), dst)
+            .map_err(|err| self.map_frame_error(err))
     }
 }
+
+/// Checks nesting depth and string lengths of a msgpack value.
+///
+/// It does not validate the value, truncated or invalid input is left to the deserializer.
+pub fn check_limits(body: &[u8], limits: &Limits) -> std::result::Result<(), LimitError> {
+    // Remaining items in containers enclosing the current one.
+    let mut stack: Vec<usize> = Vec::new();
+    // Remaining items in the current container.
+    let mut remaining = 1usize;
+    let mut pos = 0usize;
+    loop {
+        while remaining == 0 {
+            match stack.pop() {
+                Some(parent) => remaining = parent,
+                None => return Ok(()),
+            }
+        }
+        remaining -= 1;
+        let marker = match body.get(pos) {
+            Some(marker) => *marker,
+            None => return Ok(()),
+        };
+        pos += 1;
+        // Length of a string, binary or extension value following the marker.
+        let mut data = None;
+        // Number of items of an array or map following the marker.
+        let mut items = None;
+        match marker {
+            0x80..=0x8f => items = Some((marker & 0x0f) as usize * 2),
+            0x90..=0x9f => items = Some((marker & 0x0f) as usize),
+            0xa0..=0xbf => data = Some((marker & 0x1f) as usize),
+            0xc4 | 0xd9 => data = read_length(body, &mut pos, 1),
+            0xc5 | 0xda => data = read_length(body, &mut pos, 2),
+            0xc6 | 0xdb => data = read_length(body, &mut pos, 4),
+            0xc7 => data = read_length(body, &mut pos, 1).map(|len| len + 1),
+            0xc8 => data = read_length(body, &mut pos, 2).map(|len| len + 1),
+            0xc9 => data = read_length(body, &mut pos, 4).map(|len| len + 1),
+            0xca | 0xce | 0xd2 => pos += 4,
+            0xcb | 0xcf | 0xd3 => pos += 8,
+            0xcc | 0xd0 => pos += 1,
+            0xcd | 0xd1 => pos += 2,
+            0xd4 => pos += 2,
+            0xd5 => pos += 3,
+            0xd6 => pos += 5,
+            0xd7 => pos += 9,
+            0xd8 => pos += 17,
+            0xdc => items = read_length(body, &mut pos, 2),
+            0xdd => items = read_length(body, &mut pos, 4),
+            0xde => items = read_length(body, &mut pos, 2).map(|len| len.saturating_mul(2)),
+            0xdf => items = read_length(body, &mut pos, 4).map(|len| len.saturating_mul(2)),
+            // Positive and negative fixint, nil, booleans and the unused marker.
+            _ => {}
+        }
+        if let Some(length) = data {
+            limits.check_string_length(length)?;
+            pos = pos.saturating_add(length);
+        }
+        if let Some(count) = items {
+            stack.push(remaining);
+            limits.check_depth(stack.len())?;
+            remaining = count;
+        }
+    }
+}
+
+/// Reads big-endian length of `size` bytes and advances the position.
+fn read_length(body: &[u8], pos: &mut usize, size: usize) -> Option<usize> {
+    let bytes = body.get(*pos..*pos + size)?;
+    *pos += size;
+    Some(
+        bytes
+            .iter()
+            .fold(0usize, |length, byte| (length << 8) | *byte as usize),
+    )
+}
@@ -0,0 +1,165 @@
+use bytes::BytesMut;
+use tokio_util::codec::{Decoder, Encoder};
+
+use crate::*;
+
+type Nested = Vec<Vec<Vec<u8>>>;
+
+fn frame(body: &[u8]) -> BytesMut {
+    let mut frame = BytesMut::from(&(body.len() as u32).to_be_bytes()[..]);
+    frame.extend_from_slice(body);
+    frame
+}
+
+fn limit_error<T: std::fmt::Debug>(result: std::io::Result<T>) -> LimitError {
+    *LimitError::from_io(&result.unwrap_err()).expect("limit error")
+}
+
+#[test]
+fn decode_within_limits() {
+    let mut codec = Codec::<Nested>::default();
+    let value = codec.decode(&mut frame(&[0x91, 0x91, 0x91, 0x01])).unwrap();
+    assert_eq!(value, Some(vec![vec![vec![1]]]));
+}
+
+#[test]
+fn decode_frame_too_large() {
+    let mut codec = Codec::<Nested>::with_limits(Limits::default().with_max_frame_length(2));
+    assert_eq!(
+        limit_error(codec.decode(&mut frame(&[0x91, 0x91, 0x90]))),
+        LimitError::FrameTooLarge { limit: 2 }
+    );
+}
+
+#[test]
+fn decode_nesting_too_deep() {
+    let mut codec = Codec::<Nested>::with_limits(Limits::default().with_max_depth(2));
+    assert_eq!(
+        limit_error(codec.decode(&mut frame(&[0x91, 0x91, 0x91, 0x01]))),
+        LimitError::NestingTooDeep { limit: 2 }
+    );
+}
+
+#[test]
+fn decode_string_too_long() {
+    let mut codec = Codec::<Vec<String>>::with_limits(Limits::default().with_max_string_length(2));
+    assert_eq!(
+        limit_error(codec.decode(&mut frame(&[0x92, 0xa1, b'a', 0xa3, b'a', b'b', b'c']))),
+        LimitError::StringTooLong { limit: 2 }
+    );
+}
+
+#[test]
+fn encode_frame_too_large() {
+    let mut codec = Codec::<Vec<u8>>::with_limits(Limits::default().with_max_frame_length(2));
+    let mut dst = BytesMut::new();
+    assert_eq!(
+        limit_error(codec.encode(vec![1, 2, 3], &mut dst)),
+        LimitError::FrameTooLarge { limit: 2 }
+    );
+}
+
+#[test]
+fn compact_binary_payload() {
+    use bytes::Bytes;
+    use net3_msg::{compact::Message, prelude::*};
+
+    let payload = Bytes::from_static(&[0xde, 0xad, 0xbe, 0xef]);
+    let msg = builder::new_request::<Message, _>(Id::Num(1), "upload", Some(&payload))
+        .unwrap()
+        .build();
+
+    let mut codec = Codec::<Message>::default();
+    let mut buf = BytesMut::new();
+    codec.encode(msg, &mut buf).unwrap();
+    // Payload is carried as a native msgpack binary value.
+    let encoded = [&[0xc4, 0x04][..], &payload[..]].concat();
+    assert!(buf
+        .windows(encoded.len())
+        .any(|window| window == &encoded[..]));
+
+    let msg = codec.decode(&mut buf).unwrap().unwrap();
+    assert_eq!(msg.id(), &Id::Num(1));
+    assert_eq!(msg.method(), Some("upload"));
+    assert_eq!(msg.read::<Bytes>().unwrap(), payload);
+}
+
+#[test]
+fn compact_json_string_payload() {
+    use std::collections::BTreeMap;
+
+    use net3_msg::{compact::Message, prelude::*};
+
+    // Previous format carried payloads as JSON encoded strings.
+    let msg = builder::new_request::<Message, _>(Id::Num(1), "upload", Some(&r#"{"a":1}"#))
+        .unwrap()
+        .build();
+
+    let mut codec = Codec::<Message>::default();
+    let mut buf = BytesMut::new();
+    codec.encode(msg, &mut buf).unwrap();
+    let msg = codec.decode(&mut buf).unwrap().unwrap();
+    let data: BTreeMap<String, u32> = msg.read().unwrap();
+    assert_eq!(data.get("a"), Some(&1));
+    // String payloads are read as they are.
+    assert_eq!(msg.read::<String>().unwrap(), r#"{"a":1}"#);
+}
+
+#[test]
+fn compact_error_data() {
+    use net3_msg::{compact::Message, prelude::*};
+
+    let request = builder::new_empty_request::<Message>(Id::Num(1), "upload").build();
+    let error = types::Error::from(ErrorKind::InternalError)
+        .with_data(&("field", "name"))
+        .unwrap();
+    let msg = builder::new_error_response(&request, error).build();
+
+    let mut codec = Codec::<Message>::default();
+    let mut buf = BytesMut::new();
+    codec.encode(msg, &mut buf).unwrap();
+    let msg = codec.decode(&mut buf).unwrap().unwrap();
+    let error = msg.into_error().unwrap();
+    assert_eq!(error.kind, ErrorKind::InternalError);
+    assert_eq!(
+        error.read_data::<(String, String)>().unwrap(),
+        Some(("field".to_owned(), "name".to_owned()))
+    );
+}
+
+#[test]
+fn error_kind_variant_indices() {
+    use net3_msg::types::ErrorKind;
+
+    /// Error kinds encoded by previous releases.
+    #[derive(serde_derive::Serialize)]
+    enum Previous {
+        InternalError,
+        MethodNotFound,
+        ErrorCode(i64),
+    }
+
+    let encoded = |kind: &ErrorKind| rmp_serde::to_vec(kind).unwrap();
+    let previous = |kind: &Previous| rmp_serde::to_vec(kind).unwrap();
+    assert_eq!(
+        encoded(&ErrorKind::InternalError),
+        previous(&Previous::InternalError)
+    );
+    assert_eq!(
+        encoded(&ErrorKind::MethodNotFound),
+        previous(&Previous::MethodNotFound)
+    );
+    assert_eq!(
+        encoded(&ErrorKind::ErrorCode(-1)),
+        previous(&Previous::ErrorCode(-1))
+    );
+    for kind in &[
+        ErrorKind::ParseError,
+        ErrorKind::InvalidRequest,
+        ErrorKind::InvalidParams,
+        ErrorKind::ServerError(-32000),
+    ] {
+        let decoded: ErrorKind = rmp_serde::from_slice(&encoded(kind)).unwrap();
+        assert_eq!(&decoded, kind);
+    }
+}
@@ -0,0 +1,99 @@
+//! Length prefixed channel message codec.
+
+use std::io::{Error, Result};
+
+use bytes::{Bytes, BytesMut};
+use tokio_util::codec::{
+    length_delimited::Builder, Decoder, Encoder, LengthDelimitedCodec, LengthDelimitedCodecError,
+};
+
+use net3_codec_limits::{LimitError, LimitedCodec, Limits};
+
+/// Length prefixed channel message codec.
+///
+/// By default frames payloads with a big-endian 4 byte length prefix.
+/// Other layouts can be configured using a [`Builder`].
+///
+/// [`Builder`]: https://docs.rs/tokio-util/0.3.1/tokio_util/codec/length_delimited/struct.Builder.html
+pub struct Codec<T> {
+    inner: LengthDelimitedCodec,
+    limits: Limits,
+    _marker: std::marker::PhantomData<T>,
+}
+
+impl<T> Codec<T> {
+    /// Creates a new codec enforcing given limits.
+    pub fn with_limits(limits: Limits) -> Self {
+        Self::from_builder(&LengthDelimitedCodec::builder(), limits)
+    }
+
+    /// Creates a new codec with length prefix layout configured by a [`Builder`].
+    ///
+    /// Maximum frame length of the builder is overwritten by the limits.
+    ///
+    /// [`Builder`]: https://docs.rs/tokio-util/0.3.1/tokio_util/codec/length_delimited/struct.Builder.html
+    pub fn from_builder(builder: &Builder, limits: Limits) -> Self {
+        let mut inner = builder.new_codec();
+        inner.set_max_frame_length(limits.max_frame_length);
+        Codec {
+            inner,
+            limits,
+            _marker: std::marker::PhantomData,
+        }
+    }
+
+    /// Converts frame length errors of the inner codec to [`LimitError`].
+    ///
+    /// [`LimitError`]: ../../net3_codec_limits/enum.LimitError.html
+    fn map_frame_error(&self, err: Error) -> Error {
+        match err.get_ref() {
+            Some(inner) if inner.is::<LengthDelimitedCodecError>() => LimitError::FrameTooLarge {
+                limit: self.limits.max_frame_length,
+            }
+            .into(),
+            _ => err,
+        }
+    }
+}
+
+impl<T> Default for Codec<T> {
+    fn default() -> Self {
+        Self::with_limits(Limits::default())
+    }
+}
+
+impl<T> LimitedCodec for Codec<T> {
+    fn limits(&self) -> &Limits {
+        &self.limits
+    }
+
+    fn set_limits(&mut self, limits: Limits) {
+        self.inner.set_max_frame_length(limits.max_frame_length);
+        self.limits = limits;
+    }
+}
+
+impl<T: From<Bytes>> Decoder for Codec<T> {
+    type Item = T;
+    type Error = Error;
+
+    #[inline]
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>> {
+        let frame = self
+            .inner
+            .decode(src)
+            .map_err(|err| self.map_frame_error(err))?;
+        Ok(frame.map(|frame| T::from(frame.freeze())))
+    }
+}
+
+impl<T: Into<Bytes>> Encoder<T> for Codec<T> {
+    type Error = Error;
+
+    #[inline]
+    fn encode(&mut self, item: T, dst: &mut BytesMut) -> Result<()> {
+        self.inner
+            .encode(item.into(), dst)
+            .map_err(|err| self.map_frame_error(err))
+    }
+}
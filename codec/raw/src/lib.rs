@@ -0,0 +1,21 @@
+//! Raw bytes channel message encoders and decoders.
+//!
+//! Codecs carry opaque payloads of messages convertible from and into [`Bytes`],
+//! such as [`raw::Message`] or `Bytes` itself.
+//!
+//! * [`netstring::Codec`] frames payloads as `<length>:<payload>,` [netstrings].
+//! * [`length::Codec`] frames payloads with a binary length prefix.
+//!
+//! [`Bytes`]: https://docs.rs/bytes/0.5/bytes/struct.Bytes.html
+//! [`raw::Message`]: ../net3_msg/raw/struct.Message.html
+//! [`netstring::Codec`]: netstring/struct.Codec.html
+//! [`length::Codec`]: length/struct.Codec.html
+//! [netstrings]: https://cr.yp.to/proto/netstrings.txt
+
+pub mod length;
+pub mod netstring;
+
+pub use net3_codec_limits::{LimitError, LimitedCodec, Limits};
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,112 @@
+//! Netstring channel message codec.
+
+use std::io::{Error, ErrorKind, Result};
+
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+use tokio_util::codec::{Decoder, Encoder};
+
+use net3_codec_limits::{LimitedCodec, Limits};
+
+/// Netstring channel message codec.
+///
+/// Frames payloads as `<length>:<payload>,` where length is a decimal
+/// number of payload bytes.
+pub struct Codec<T> {
+    limits: Limits,
+    _marker: std::marker::PhantomData<T>,
+}
+
+impl<T> Codec<T> {
+    /// Creates a new codec enforcing given limits.
+    pub fn with_limits(limits: Limits) -> Self {
+        Codec {
+            limits,
+            _marker: std::marker::PhantomData,
+        }
+    }
+}
+
+impl<T> Default for Codec<T> {
+    fn default() -> Self {
+        Self::with_limits(Limits::default())
+    }
+}
+
+impl<T> LimitedCodec for Codec<T> {
+    fn limits(&self) -> &Limits {
+        &self.limits
+    }
+
+    fn set_limits(&mut self, limits: Limits) {
+        self.limits = limits;
+    }
+}
+
+impl<T: From<Bytes>> Decoder for Codec<T> {
+    type Item = T;
+    type Error = Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>> {
+        // Parse decimal length up to the `:` separator.
+        let mut length = 0usize;
+        let mut header = None;
+        for (index, byte) in src.iter().enumerate() {
+            match byte {
+                b'0'..=b'9' if index > 0 && src[0] == b'0' => return Err(invalid("length")),
+                b'0'..=b'9' => {
+                    length = length
+                        .checked_mul(10)
+                        .and_then(|length| length.checked_add((byte - b'0') as usize))
+                        .ok_or_else(|| invalid("length"))?;
+                    self.limits.check_frame_length(length)?;
+                }
+                b':' if index > 0 => {
+                    header = Some(index + 1);
+                    break;
+                }
+                _ => return Err(invalid("length")),
+            }
+        }
+        let header = match header {
+            Some(header) => header,
+            None => return Ok(None),
+        };
+        // Wait for the whole payload and the trailing comma.
+        let frame_length = header + length + 1;
+        if src.len() < frame_length {
+            src.reserve(frame_length - src.len());
+            return Ok(None);
+        }
+        if src[frame_length - 1] != b',' {
+            return Err(invalid("terminator"));
+        }
+        src.advance(header);
+        let payload = src.split_to(length).freeze();
+        src.advance(1);
+        Ok(Some(T::from(payload)))
+    }
+}
+
+impl<T: Into<Bytes>> Encoder<T> for Codec<T> {
+    type Error = Error;
+
+    fn encode(&mut self, item: T, dst: &mut BytesMut) -> Result<()> {
+        let payload: Bytes = item.into();
+        self.limits.check_frame_length(payload.len())?;
+        let length = payload.len().to_string();
+        dst.reserve(length.len() + payload.len() + 2);
+        dst.put_slice(length.as_bytes());
+        dst.put_u8(b':');
+        dst.put_slice(&payload);
+        dst.put_u8(b',');
+        Ok(())
+    }
+}
+
+/// Creates an invalid netstring error.
+fn invalid(part: &str) -> Error {
+    Error::new(
+        ErrorKind::InvalidData,
+        format!("invalid netstring {}", part),
+    )
+}
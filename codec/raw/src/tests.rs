@@ -0,0 +1,174 @@
+use bytes::{Bytes, BytesMut};
+use tokio_util::codec::{Decoder, Encoder};
+
+use net3_msg::{raw::Message, traits::Read};
+
+use crate::*;
+
+fn limit_error<T: std::fmt::Debug>(result: std::io::Result<T>) -> LimitError {
+    *LimitError::from_io(&result.unwrap_err()).expect("limit error")
+}
+
+#[test]
+fn netstring_round_trip() {
+    let mut codec = netstring::Codec::<Message>::default();
+    let mut buf = BytesMut::new();
+    codec
+        .encode(Message::from(Bytes::from("hello")), &mut buf)
+        .unwrap();
+    codec.encode(Message::from(Bytes::new()), &mut buf).unwrap();
+    assert_eq!(&buf[..], b"5:hello,0:,");
+    let msg = codec.decode(&mut buf).unwrap().unwrap();
+    assert_eq!(msg.read::<String>().unwrap(), "hello");
+    let msg = codec.decode(&mut buf).unwrap().unwrap();
+    assert_eq!(msg.data(), Some(&Bytes::new()));
+    assert!(buf.is_empty());
+}
+
+#[test]
+fn netstring_partial_frame() {
+    let mut codec = netstring::Codec::<Bytes>::default();
+    let mut buf = BytesMut::from(&b"1"[..]);
+    assert_eq!(codec.decode(&mut buf).unwrap(), None);
+    buf.extend_from_slice(b"1:hello");
+    assert_eq!(codec.decode(&mut buf).unwrap(), None);
+    buf.extend_from_slice(b" world,");
+    assert_eq!(
+        codec.decode(&mut buf).unwrap(),
+        Some(Bytes::from("hello world"))
+    );
+}
+
+#[test]
+fn netstring_invalid() {
+    let mut codec = netstring::Codec::<Bytes>::default();
+    assert!(codec.decode(&mut BytesMut::from(&b"x:,"[..])).is_err());
+    assert!(codec.decode(&mut BytesMut::from(&b"01:a,"[..])).is_err());
+    assert!(codec.decode(&mut BytesMut::from(&b"1:ab"[..])).is_err());
+}
+
+#[test]
+fn netstring_frame_too_large() {
+    let mut codec =
+        netstring::Codec::<Bytes>::with_limits(Limits::default().with_max_frame_length(2));
+    assert_eq!(
+        limit_error(codec.decode(&mut BytesMut::from(&b"100"[..]))),
+        LimitError::FrameTooLarge { limit: 2 }
+    );
+    assert_eq!(
+        limit_error(codec.encode(Bytes::from("abc"), &mut BytesMut::new())),
+        LimitError::FrameTooLarge { limit: 2 }
+    );
+}
+
+#[test]
+fn length_round_trip() {
+    let mut codec = length::Codec::<Message>::default();
+    let mut buf = BytesMut::new();
+    codec
+        .encode(Message::from(Bytes::from("hello")), &mut buf)
+        .unwrap();
+    assert_eq!(&buf[..], b"\0\0\0\x05hello");
+    let msg = codec.decode(&mut buf).unwrap().unwrap();
+    assert_eq!(msg.into_data(), Some(Bytes::from("hello")));
+}
+
+#[test]
+fn length_from_builder() {
+    let mut builder = tokio_util::codec::length_delimited::Builder::new();
+    builder.length_field_length(2).little_endian();
+    let mut codec = length::Codec::<Bytes>::from_builder(&builder, Limits::default());
+    let mut buf = BytesMut::new();
+    codec.encode(Bytes::from("hi"), &mut buf).unwrap();
+    assert_eq!(&buf[..], b"\x02\0hi");
+    assert_eq!(codec.decode(&mut buf).unwrap(), Some(Bytes::from("hi")));
+}
+
+#[test]
+fn length_frame_too_large() {
+    let mut codec = length::Codec::<Bytes>::with_limits(Limits::default().with_max_frame_length(2));
+    assert_eq!(
+        limit_error(codec.decode(&mut BytesMut::from(&b"\0\0\0\x03abc"[..]))),
+        LimitError::FrameTooLarge { limit: 2 }
+    );
+}
+
+#[test]
+fn raw_payload_read() {
+    let msg = Message::from(Bytes::from("hello"));
+    assert_eq!(msg.read::<Vec<u8>>().unwrap(), b"hello".to_vec());
+    assert_eq!(msg.read::<Bytes>().unwrap(), Bytes::from("hello"));
+    assert_eq!(msg.read::<String>().unwrap(), "hello");
+    assert_eq!(
+        msg.read::<Option<String>>().unwrap(),
+        Some("hello".to_owned())
+    );
+    let empty = Message::from(Bytes::new());
+    assert_eq!(empty.read::<Option<Vec<u8>>>().unwrap(), None);
+    assert!(Message::from(Bytes::from(vec![0xff]))
+        .read::<String>()
+        .is_err());
+}
+
+/// Server handler replying with upper-cased frames.
+#[derive(Clone)]
+struct UpperHandler;
+
+#[async_trait::async_trait]
+impl net3_rpc_client::Handler for UpperHandler {
+    type Event = ();
+    type Message = Message;
+
+    async fn handle_notification(&mut self, message: Message) -> std::io::Result<Vec<Message>> {
+        let data = message.read::<Vec<u8>>()?;
+        Ok(vec![Message::from(Bytes::from(data.to_ascii_uppercase()))])
+    }
+}
+
+#[tokio::test]
+async fn raw_frames_through_rpc() {
+    use std::{io::ErrorKind, time::Duration};
+
+    use net3_msg::{
+        traits::{Id as _, Kind as _},
+        types::{Id, MessageKind},
+    };
+    use net3_rpc_client::{common::CloneBuilder, ClientBuilder, Error, NotificationHandler};
+    use net3_rpc_server::ServerBuilder;
+
+    type Codec = netstring::Codec<Message>;
+
+    let server = ServerBuilder::<Codec, _>::from(CloneBuilder(UpperHandler))
+        .bind("127.0.0.1:0")
+        .await
+        .unwrap();
+    let addr = server.local_addr().unwrap().to_string();
+    server.background();
+
+    let mut builder = ClientBuilder::<Codec, CloneBuilder<NotificationHandler<Message>>>::new()
+        .with_reconnect(&addr);
+    let mut client = builder.notifications();
+    builder.spawn();
+
+    // Frames are received by the server and the client as events without identifiers.
+    client
+        .handle
+        .send(Message::from(Bytes::from("hello")))
+        .unwrap();
+    let reply = client.recv().await.unwrap();
+    assert_eq!(reply.kind(), MessageKind::Event);
+    assert_eq!(reply.id(), &Id::Null);
+    assert_eq!(reply.read::<Vec<u8>>().unwrap(), b"HELLO".to_vec());
+
+    // Request ID is not transmitted, the reply is not correlated with the request.
+    let result = client
+        .handle
+        .request_timeout::<_, Bytes>("upper", Some(&"hi"), Duration::from_millis(200))
+        .await;
+    match result {
+        Err(Error::Io(err)) => assert_eq!(err.kind(), ErrorKind::TimedOut),
+        other => panic!("unexpected result: {:?}", other),
+    }
+    let reply = client.recv().await.unwrap();
+    assert_eq!(reply.read::<String>().unwrap(), "HI");
+}
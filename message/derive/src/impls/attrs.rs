@@ -0,0 +1,80 @@
+use darling::{ast, FromDeriveInput, FromField};
+use syn::*;
+
+/// Message envelope struct.
+#[derive(FromDeriveInput)]
+#[darling(attributes(net3), supports(struct_named))]
+pub struct MessageInput {
+    pub ident: Ident,
+    pub generics: Generics,
+    pub data: ast::Data<(), MessageField>,
+    /// Kind inference function.
+    #[darling(default)]
+    pub kind: Option<Path>,
+}
+
+/// Message envelope field.
+#[derive(FromField)]
+#[darling(attributes(net3))]
+pub struct MessageField {
+    pub ident: Option<Ident>,
+    #[darling(default)]
+    pub id: bool,
+    #[darling(default)]
+    pub method: bool,
+    #[darling(default)]
+    pub params: bool,
+    #[darling(default)]
+    pub result: bool,
+    #[darling(default)]
+    pub error: bool,
+    #[darling(default)]
+    pub metadata: bool,
+    #[darling(default)]
+    pub kind: bool,
+}
+
+/// Envelope fields selected with attributes.
+pub struct EnvelopeFields {
+    pub id: Ident,
+    pub method: Ident,
+    pub params: Ident,
+    pub result: Option<Ident>,
+    pub error: Ident,
+    pub metadata: Ident,
+    pub kind: Option<Ident>,
+}
+
+impl EnvelopeFields {
+    pub fn from_input(input: &MessageInput) -> Result<Self> {
+        let fields = match &input.data {
+            ast::Data::Struct(fields) => &fields.fields,
+            ast::Data::Enum(_) => unreachable!("enums are not supported"),
+        };
+        let find = |name: &str, select: fn(&MessageField) -> bool| -> Result<Option<Ident>> {
+            let mut selected = fields.iter().filter(|field| select(field));
+            let field = selected.next().and_then(|field| field.ident.clone());
+            match selected.next() {
+                Some(field) => Err(Error::new_spanned(
+                    &field.ident,
+                    format!("duplicate `#[net3({})]` field", name),
+                )),
+                None => Ok(field),
+            }
+        };
+        let require = |name: &str, select: fn(&MessageField) -> bool| -> Result<Ident> {
+            find(name, select)?.ok_or_else(|| {
+                Error::new_spanned(&input.ident, format!("missing `#[net3({})]` field", name))
+            })
+        };
+        Ok(EnvelopeFields {
+            id: require("id", |field| field.id)?,
+            method: require("method", |field| field.method)?,
+            params: require("params", |field| field.params)?,
+            result: find("result", |field| field.result)?,
+            error: require("error", |field| field.error)?,
+            metadata: require("metadata", |field| field.metadata)?,
+            kind: find("kind", |field| field.kind)?,
+        })
+    }
+}
@@ -0,0 +1,222 @@
+pub mod attrs;
+
+use darling::FromDeriveInput;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::*;
+
+use self::attrs::*;
+
+/// Returns expression evaluating to true if the message has a result.
+fn has_result(fields: &EnvelopeFields) -> TokenStream2 {
+    match &fields.result {
+        Some(result) => quote! { self.#result.is_some() },
+        None => quote! { false },
+    }
+}
+
+/// Returns expression evaluating to message kind.
+fn make_kind(input: &MessageInput, fields: &EnvelopeFields) -> TokenStream2 {
+    let EnvelopeFields {
+        id, method, error, ..
+    } = fields;
+    if let Some(kind) = &fields.kind {
+        return quote! { self.#kind };
+    }
+    if let Some(func) = &input.kind {
+        return quote! { #func(self) };
+    }
+    let has_result = has_result(fields);
+    quote! {
+        if self.#id.is_some() {
+            if self.#error.is_none() {
+                if #has_result || self.#method.is_none() {
+                    ::net3_msg::types::MessageKind::Response
+                } else {
+                    ::net3_msg::types::MessageKind::Request
+                }
+            } else if !(#has_result) {
+                ::net3_msg::types::MessageKind::ErrorResponse
+            } else {
+                ::net3_msg::types::MessageKind::Undefined
+            }
+        } else if !self.#method.as_deref().unwrap_or_default().is_empty() {
+            ::net3_msg::types::MessageKind::Event
+        } else {
+            ::net3_msg::types::MessageKind::Undefined
+        }
+    }
+}
+
+/// Returns statement storing serialized `payload` in the message.
+fn make_set_data(fields: &EnvelopeFields) -> TokenStream2 {
+    let EnvelopeFields { method, params, .. } = fields;
+    match &fields.result {
+        Some(result) => quote! {
+            if self.#method.is_none() {
+                self.#result = Some(payload);
+            } else {
+                self.#params = Some(payload);
+            }
+        },
+        None => quote! { self.#params = Some(payload); },
+    }
+}
+
+pub fn make_message(item: DeriveInput) -> Result<TokenStream2> {
+    let input = match MessageInput::from_derive_input(&item) {
+        Ok(input) => input,
+        Err(err) => return Ok(err.write_errors()),
+    };
+    let fields = EnvelopeFields::from_input(&input)?;
+    let kind = make_kind(&input, &fields);
+    let has_result = has_result(&fields);
+    let set_data = make_set_data(&fields);
+    let set_kind = match &fields.kind {
+        Some(field) => quote! { msg.#field = kind; },
+        None => quote! { let _ = kind; },
+    };
+    let read_result = fields
+        .result
+        .iter()
+        .map(|result| quote! { .or_else(|| self.#result.as_ref()) });
+
+    let EnvelopeFields {
+        id,
+        method,
+        params,
+        error,
+        metadata,
+        ..
+    } = &fields;
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::net3_msg::traits::Id for #name #ty_generics #where_clause {
+            #[inline]
+            fn id(&self) -> &::net3_msg::types::Id {
+                &self.#id
+            }
+        }
+
+        impl #impl_generics ::net3_msg::traits::Method for #name #ty_generics #where_clause {
+            #[inline]
+            fn method(&self) -> Option<&str> {
+                self.#method.as_deref()
+            }
+        }
+
+        impl #impl_generics ::net3_msg::traits::Kind for #name #ty_generics #where_clause {
+            fn kind(&self) -> ::net3_msg::types::MessageKind {
+                #kind
+            }
+        }
+
+        impl #impl_generics ::net3_msg::traits::Read for #name #ty_generics #where_clause {
+            fn read_optional<T: ::net3_msg::traits::DeserializeOwned>(
+                &self,
+            ) -> std::io::Result<Option<T>> {
+                match self.#params.as_ref() #(#read_result)* {
+                    Some(payload) => Ok(Some(::net3_msg::traits::Payload::read_data(payload)?)),
+                    None => Ok(None),
+                }
+            }
+        }
+
+        impl #impl_generics ::net3_msg::traits::Error for #name #ty_generics #where_clause {
+            #[inline]
+            fn error_kind(&self) -> Option<&::net3_msg::types::ErrorKind> {
+                self.#error.as_ref().map(|err| &err.kind)
+            }
+
+            #[inline]
+            fn description(&self) -> Option<&str> {
+                self.#error.as_ref().and_then(|err| err.description.as_deref())
+            }
+
+            #[inline]
+            fn into_error(self) -> Option<::net3_msg::types::Error> {
+                self.#error
+            }
+        }
+
+        impl #impl_generics ::net3_msg::traits::Metadata for #name #ty_generics #where_clause {
+            #[inline]
+            fn metadata(&self) -> &::net3_msg::types::Metadata {
+                &self.#metadata
+            }
+        }
+
+        impl #impl_generics ::net3_msg::traits::Validate for #name #ty_generics #where_clause {
+            fn validate(&self) -> Result<(), ::net3_msg::types::InvalidReason> {
+                use ::net3_msg::types::{InvalidReason, MessageKind};
+                let has_result = #has_result;
+                if self.#error.is_some() && has_result {
+                    return Err(InvalidReason::ResultAndError);
+                }
+                match ::net3_msg::traits::Kind::kind(self) {
+                    MessageKind::Undefined if self.#id.is_some() => Err(InvalidReason::UndefinedKind),
+                    MessageKind::Undefined if self.#error.is_some() || has_result => {
+                        Err(InvalidReason::MissingId)
+                    }
+                    MessageKind::Undefined => Err(InvalidReason::MissingMethod),
+                    _ => Ok(()),
+                }
+            }
+        }
+
+        impl #impl_generics ::net3_msg::traits::Message for #name #ty_generics #where_clause {}
+
+        impl #impl_generics ::net3_msg::builder::MessageBuilderExt for #name #ty_generics #where_clause {
+            type Builder = Self;
+        }
+
+        impl #impl_generics ::net3_msg::builder::MessageBuilder<Self> for #name #ty_generics #where_clause {
+            fn new(kind: ::net3_msg::types::MessageKind) -> Self {
+                let mut msg: Self = Default::default();
+                #set_kind
+                msg
+            }
+
+            fn new_response(request: &Self) -> Self {
+                let mut msg = Self::new(::net3_msg::types::MessageKind::Response);
+                msg.#id = request.#id.clone();
+                msg
+            }
+
+            fn new_error_response(request: &Self, error: ::net3_msg::types::Error) -> Self {
+                let mut msg = Self::new(::net3_msg::types::MessageKind::ErrorResponse);
+                msg.#id = request.#id.clone();
+                msg.#error = Some(error);
+                msg
+            }
+
+            fn set_id(&mut self, id: ::net3_msg::types::Id) {
+                self.#id = id;
+            }
+
+            fn set_data<T: ::net3_msg::traits::Serialize>(&mut self, data: &T) -> std::io::Result<()> {
+                let payload = ::net3_msg::traits::Payload::from_data(data)?;
+                #set_data
+                Ok(())
+            }
+
+            fn set_event_name<T: ToString>(&mut self, name: T) {
+                self.#id = ::net3_msg::types::Id::Null;
+                self.#method = Some(name.to_string());
+            }
+
+            fn set_method_name<T: ToString>(&mut self, method: T) {
+                self.#method = Some(method.to_string());
+            }
+
+            fn metadata_mut(&mut self) -> &mut ::net3_msg::types::Metadata {
+                &mut self.#metadata
+            }
+
+            fn build(self) -> Self {
+                self
+            }
+        }
+    })
+}
@@ -0,0 +1,41 @@
+//! Derive macro for network channel message envelopes.
+//!
+//! `#[derive(Net3Message)]` implements all of the [`Message`] traits
+//! and a [`MessageBuilder`] on a struct with named fields.
+//!
+//! Fields are selected with `#[net3(...)]` attributes:
+//!
+//! * `id` - message identifier of type [`Id`] (required),
+//! * `method` - method or event name of type `Option<String>` (required),
+//! * `params` - request parameters of type `Option<P>` (required),
+//! * `result` - response result of type `Option<P>`,
+//! * `error` - response error of type `Option<Error>` (required),
+//! * `metadata` - message [`Metadata`] (required, use `#[serde(skip)]` if not transmitted),
+//! * `kind` - explicit [`MessageKind`] of the message.
+//!
+//! Payload type `P` has to implement [`Payload`], e.g. [`Value`].
+//! Envelope has to implement `Default`, which is used to create new messages.
+//!
+//! Without a `kind` field, message kind is inferred the same way as in JSON-RPC.
+//! Inference can be replaced with a struct attribute `#[net3(kind = "path")]`
+//! pointing to a `fn(&Self) -> MessageKind`.
+//!
+//! [`Message`]: ../net3_msg/traits/trait.Message.html
+//! [`MessageBuilder`]: ../net3_msg/builder/trait.MessageBuilder.html
+//! [`Payload`]: ../net3_msg/traits/trait.Payload.html
+//! [`Id`]: ../net3_msg/types/enum.Id.html
+//! [`Metadata`]: ../net3_msg/types/type.Metadata.html
+//! [`MessageKind`]: ../net3_msg/types/enum.MessageKind.html
+//! [`Value`]: ../net3_msg/types/enum.Value.html
+
+mod impls;
+
+use proc_macro::TokenStream;
+
+#[proc_macro_derive(Net3Message, attributes(net3))]
+pub fn net3_message(item: TokenStream) -> TokenStream {
+    let item = syn::parse_macro_input!(item as syn::DeriveInput);
+    self::impls::make_message(item)
+        .unwrap_or_else(|e| e.to_compile_error())
+        .into()
+}
@@ -0,0 +1,111 @@
+#[macro_use]
+extern crate serde_derive;
+
+use net3_msg::{prelude::*, types::Value};
+use net3_msg_derive::Net3Message;
+
+/// Envelope with JSON-RPC like kind inference.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, Net3Message)]
+struct Envelope {
+    #[net3(id)]
+    #[serde(default, skip_serializing_if = "Id::is_none")]
+    id: Id,
+    #[net3(method)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    op: Option<String>,
+    #[net3(params)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    args: Option<Value>,
+    #[net3(result)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    ret: Option<Value>,
+    #[net3(error)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    err: Option<types::Error>,
+    #[net3(metadata)]
+    #[serde(skip)]
+    metadata: Metadata,
+}
+
+/// Envelope with kind inference hook.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, Net3Message)]
+#[net3(kind = "hooked_kind")]
+struct Hooked {
+    #[net3(id)]
+    id: Id,
+    #[net3(method)]
+    method: Option<String>,
+    #[net3(params)]
+    data: Option<Value>,
+    #[net3(error)]
+    error: Option<types::Error>,
+    #[net3(metadata)]
+    meta: Metadata,
+}
+
+fn hooked_kind(msg: &Hooked) -> MessageKind {
+    match msg.method.as_deref() {
+        Some("reply") => MessageKind::Response,
+        Some(_) => MessageKind::Request,
+        None => MessageKind::Undefined,
+    }
+}
+
+#[test]
+fn request_round_trip() {
+    let request = builder::new_request::<Envelope, _>(Id::Num(1), "add", Some(&(1, 2)))
+        .unwrap()
+        .build();
+    assert_eq!(request.kind(), MessageKind::Request);
+    let json = serde_json::to_string(&request).unwrap();
+    assert_eq!(json, r#"{"id":1,"op":"add","args":[1,2]}"#);
+
+    let request: Envelope = serde_json::from_str(&json).unwrap();
+    assert_eq!(request.kind(), MessageKind::Request);
+    assert_eq!(request.method(), Some("add"));
+    assert_eq!(request.read::<(u8, u8)>().unwrap(), (1, 2));
+
+    let response = builder::new_response(&request)
+        .with_data(&3)
+        .unwrap()
+        .build();
+    assert_eq!(response.kind(), MessageKind::Response);
+    assert_eq!(
+        serde_json::to_string(&response).unwrap(),
+        r#"{"id":1,"ret":3}"#
+    );
+    assert_eq!(response.read::<u8>().unwrap(), 3);
+}
+
+#[test]
+fn error_response() {
+    let request = builder::new_empty_request::<Envelope>(Id::Num(1), "add").build();
+    let response = builder::new_error_response(&request, ErrorKind::InvalidParams.into()).build();
+    assert_eq!(response.kind(), MessageKind::ErrorResponse);
+    assert_eq!(response.error_kind(), Some(&ErrorKind::InvalidParams));
+    assert_eq!(response.validate(), Ok(()));
+}
+
+#[test]
+fn event_and_validation() {
+    let event = Envelope::new_event("tick").with_meta("trace", "1");
+    assert_eq!(event.kind(), MessageKind::Event);
+    assert_eq!(event.meta("trace"), Some("1"));
+
+    let invalid: Envelope = serde_json::from_str(r#"{"ret":1}"#).unwrap();
+    assert_eq!(invalid.validate(), Err(InvalidReason::MissingId));
+    let invalid: Envelope = serde_json::from_str(r#"{}"#).unwrap();
+    assert_eq!(invalid.validate(), Err(InvalidReason::MissingMethod));
+}
+
+#[test]
+fn kind_hook() {
+    let msg = Hooked::new_request(Id::Num(1), "reply");
+    assert_eq!(msg.kind(), MessageKind::Response);
+    let msg = msg.with_data(&"data").unwrap();
+    assert_eq!(msg.read::<String>().unwrap(), "data");
+    assert_eq!(
+        Hooked::default().validate(),
+        Err(InvalidReason::MissingMethod)
+    );
+}
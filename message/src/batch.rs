@@ -0,0 +1,154 @@
+//! Batches of messages.
+//!
+//! Some protocols, like JSON-RPC, allow sending several messages in a single frame.
+//! Codecs of [`Batch`] decode such frames as [`Batch::Many`] and encode
+//! [`Batch::Many`] as a single frame, e.g. a JSON array.
+//!
+//! Channel loops are generic over a [`Frame`] type so channels of plain messages
+//! are supported as well, batches are sent over them one message per frame.
+//!
+//! Elements of a batch which are not valid messages are read as default messages.
+//! Those fail validation and are answered with an `InvalidRequest` error each,
+//! other messages of the batch are handled as usual.
+//!
+//! [`Batch`]: enum.Batch.html
+//! [`Batch::Many`]: enum.Batch.html#variant.Many
+//! [`Frame`]: trait.Frame.html
+
+use std::{fmt, marker::PhantomData};
+
+use serde::{
+    de::{value::MapAccessDeserializer, Deserialize, Deserializer, MapAccess, SeqAccess, Visitor},
+    ser::{Serialize, Serializer},
+};
+use serde_value::Value;
+
+use crate::{frame, traits::Message};
+
+/// Single message or a batch of messages.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Batch<M> {
+    /// Single message.
+    Single(M),
+
+    /// Batch of messages.
+    Many(Vec<M>),
+}
+
+impl<M> Batch<M> {
+    /// Returns amount of messages.
+    #[inline]
+    pub fn len(&self) -> usize {
+        match self {
+            Batch::Single(_) => 1,
+            Batch::Many(messages) => messages.len(),
+        }
+    }
+
+    /// Returns true if it is an empty batch.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns messages as a vector.
+    #[inline]
+    pub fn into_vec(self) -> Vec<M> {
+        match self {
+            Batch::Single(message) => vec![message],
+            Batch::Many(messages) => messages,
+        }
+    }
+}
+
+impl<M> From<M> for Batch<M> {
+    #[inline]
+    fn from(message: M) -> Self {
+        Batch::Single(message)
+    }
+}
+
+impl<M> From<Vec<M>> for Batch<M> {
+    #[inline]
+    fn from(messages: Vec<M>) -> Self {
+        Batch::Many(messages)
+    }
+}
+
+impl<M: Serialize> Serialize for Batch<M> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Batch::Single(message) => message.serialize(serializer),
+            Batch::Many(messages) => messages.serialize(serializer),
+        }
+    }
+}
+
+/// Deserializes a map as a single message and a sequence as a batch.
+///
+/// Elements of a sequence are buffered first, so an invalid element
+/// can be replaced with a default message without failing the batch.
+impl<'de, M: Deserialize<'de> + Default> Deserialize<'de> for Batch<M> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(BatchVisitor(PhantomData))
+    }
+}
+
+struct BatchVisitor<M>(PhantomData<M>);
+
+impl<'de, M: Deserialize<'de> + Default> Visitor<'de> for BatchVisitor<M> {
+    type Value = Batch<M>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a message or an array of messages")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+        M::deserialize(MapAccessDeserializer::new(map)).map(Batch::Single)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut messages = Vec::with_capacity(seq.size_hint().unwrap_or(0).min(64));
+        while let Some(element) = seq.next_element::<Value>()? {
+            messages.push(frame::buffered(|| M::deserialize(element)).unwrap_or_default());
+        }
+        Ok(Batch::Many(messages))
+    }
+}
+
+/// Channel frame carrying messages.
+pub trait Frame<M>: Sized {
+    /// Converts a received frame into messages.
+    fn into_batch(self) -> Batch<M>;
+
+    /// Converts messages into frames to send.
+    ///
+    /// Batch is split into single message frames if a frame can't carry it.
+    fn from_batch(batch: Batch<M>) -> Batch<Self>;
+}
+
+/// Frame of a single message.
+impl<M: Message> Frame<M> for M {
+    #[inline]
+    fn into_batch(self) -> Batch<M> {
+        Batch::Single(self)
+    }
+
+    #[inline]
+    fn from_batch(batch: Batch<M>) -> Batch<M> {
+        batch
+    }
+}
+
+/// Frame of a single message or a batch.
+impl<M: Message> Frame<M> for Batch<M> {
+    #[inline]
+    fn into_batch(self) -> Batch<M> {
+        self
+    }
+
+    #[inline]
+    fn from_batch(batch: Batch<M>) -> Batch<Self> {
+        Batch::Single(batch)
+    }
+}
@@ -70,6 +70,24 @@ pub trait MessageBuilder<M: Sized>: Sized {
 
     fn set_method_name<T: ToString>(&mut self, method: T);
 
+    fn metadata_mut(&mut self) -> &mut types::Metadata;
+
+    fn set_metadata(&mut self, metadata: types::Metadata) {
+        *self.metadata_mut() = metadata;
+    }
+
+    fn set_meta<K: ToString, V: ToString>(&mut self, key: K, value: V) {
+        self.metadata_mut()
+            .insert(key.to_string(), value.to_string());
+    }
+
+    /// Sets request timeout under [`TIMEOUT_META`] metadata key.
+    ///
+    /// [`TIMEOUT_META`]: ../types/constant.TIMEOUT_META.html
+    fn set_timeout(&mut self, timeout: std::time::Duration) {
+        self.set_meta(types::TIMEOUT_META, timeout.as_millis());
+    }
+
     fn with_id(mut self, id: types::Id) -> Self {
         self.set_id(id);
         self
@@ -90,6 +108,21 @@ pub trait MessageBuilder<M: Sized>: Sized {
         self
     }
 
+    fn with_metadata(mut self, metadata: types::Metadata) -> Self {
+        self.set_metadata(metadata);
+        self
+    }
+
+    fn with_meta<K: ToString, V: ToString>(mut self, key: K, value: V) -> Self {
+        self.set_meta(key, value);
+        self
+    }
+
+    fn with_timeout(mut self, timeout: std::time::Duration) -> Self {
+        self.set_timeout(timeout);
+        self
+    }
+
     fn new_event<T: ToString>(name: T) -> Self {
         let mut msg = Self::new(types::MessageKind::Event);
         msg.set_event_name(name);
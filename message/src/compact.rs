@@ -1,7 +1,22 @@
+//! Compact message format.
+//!
+//! Message payload is kept as a codec-native [`Value`], it is serialized
+//! as a nested value instead of an encoded string. Byte arrays serialized
+//! with [`serialize_bytes`] are carried as binary values where the codec supports it.
+//!
+//! Previous versions of the format carried payloads as JSON encoded strings.
+//! Payloads of such messages are still read: string payloads which can not be read
+//! as a requested type are read from the JSON they contain.
+//!
+//! [`Value`]: https://docs.rs/serde-value/0.7/serde_value/enum.Value.html
+//! [`serialize_bytes`]: https://docs.rs/serde/1/serde/trait.Serializer.html#tymethod.serialize_bytes
+
 use crate::{builder, traits, types};
 
 use serde::ser::Serialize;
 
+pub use crate::types::Value;
+
 #[derive(Default, Debug, Clone, Serialize, Deserialize)]
 pub struct Message {
     kind: types::MessageKind,
@@ -11,8 +26,18 @@ pub struct Message {
     id: types::Id,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     name: Option<String>,
+    #[serde(default, skip_serializing_if = "types::Metadata::is_empty")]
+    metadata: types::Metadata,
     #[serde(default, skip_serializing_if = "Option::is_none")]
-    data: Option<String>,
+    data: Option<Value>,
+}
+
+impl Message {
+    /// Returns payload of the message.
+    #[inline]
+    pub fn data(&self) -> Option<&Value> {
+        self.data.as_ref()
+    }
 }
 
 impl traits::Id for Message {
@@ -35,17 +60,49 @@ impl traits::Kind for Message {
 
 impl traits::Read for Message {
     fn read_optional<T: serde::de::DeserializeOwned>(&self) -> std::io::Result<Option<T>> {
-        match self.data.as_deref() {
-            Some(data) => {
-                Ok(Some(::serde_json::from_str(data).map_err(|_| {
-                    std::io::Error::from(std::io::ErrorKind::InvalidData)
-                })?))
+        let data = match self.data.as_ref() {
+            Some(data) => data,
+            None => return Ok(None),
+        };
+        match traits::Payload::read_data(data) {
+            Ok(value) => Ok(Some(value)),
+            // Payload of a message in the previous format.
+            Err(err) => match data {
+                Value::String(json) => serde_json::from_str(json).map(Some).map_err(|_| err),
+                _ => Err(err),
+            },
+        }
+    }
+}
+
+impl traits::Validate for Message {
+    fn validate(&self) -> Result<(), types::InvalidReason> {
+        use types::{InvalidReason, MessageKind};
+        match self.kind {
+            MessageKind::Undefined => Err(InvalidReason::UndefinedKind),
+            MessageKind::Event | MessageKind::Request
+                if self.name.as_deref().unwrap_or_default().is_empty() =>
+            {
+                Err(InvalidReason::MissingMethod)
+            }
+            MessageKind::Request | MessageKind::Response | MessageKind::ErrorResponse
+                if self.id.is_none() =>
+            {
+                Err(InvalidReason::MissingId)
             }
-            None => Ok(None),
+            MessageKind::ErrorResponse if self.error.is_none() => Err(InvalidReason::MissingError),
+            MessageKind::Response if self.error.is_some() => Err(InvalidReason::ResultAndError),
+            _ => Ok(()),
         }
     }
 }
 
+impl traits::Metadata for Message {
+    fn metadata(&self) -> &types::Metadata {
+        &self.metadata
+    }
+}
+
 impl traits::Error for Message {
     fn error_kind(&self) -> Option<&types::ErrorKind> {
         self.error.as_ref().map(|err| &err.kind)
@@ -74,6 +131,7 @@ impl builder::MessageBuilder<Message> for Message {
             kind,
             id: types::Id::Null,
             name: None,
+            metadata: Default::default(),
             data: None,
             error: None,
         }
@@ -108,11 +166,12 @@ impl builder::MessageBuilder<Message> for Message {
         self.name = Some(method.to_string());
     }
 
+    fn metadata_mut(&mut self) -> &mut types::Metadata {
+        &mut self.metadata
+    }
+
     fn set_data<T: Serialize>(&mut self, data: &T) -> std::io::Result<()> {
-        self.data = Some(
-            ::serde_json::to_string(data)
-                .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err))?,
-        );
+        self.data = Some(traits::Payload::from_data(data)?);
         Ok(())
     }
 
@@ -121,6 +180,7 @@ impl builder::MessageBuilder<Message> for Message {
             kind: self.kind,
             id: self.id,
             name: self.name,
+            metadata: self.metadata,
             error: self.error,
             data: self.data,
         }
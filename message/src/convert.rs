@@ -0,0 +1,251 @@
+//! Conversion of messages between formats.
+//!
+//! Any [`Message`] can be translated into another message type with [`convert`].
+//! Payloads and error details are carried through an intermediate [`Value`].
+//!
+//! Converted message is read back and compared with the source,
+//! information that could not be represented in the target format
+//! is reported as a list of [`Loss`]es.
+//!
+//! [`Message`]: ../traits/trait.Message.html
+//! [`convert`]: fn.convert.html
+//! [`Value`]: ../types/enum.Value.html
+//! [`Loss`]: enum.Loss.html
+
+use std::{collections::BTreeMap, fmt, io};
+
+use crate::{
+    builder::{self, MessageBuilder, MessageBuilderExt},
+    traits,
+    types::{self, MessageKind, Value},
+};
+
+/// Information lost in a conversion.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Loss {
+    /// Message kind changed.
+    Kind,
+
+    /// Message identifier changed.
+    Id,
+
+    /// Method or event name was dropped or changed.
+    Method,
+
+    /// Payload could not be represented.
+    Payload,
+
+    /// Error kind, description or details changed.
+    Error,
+
+    /// Metadata could not be represented.
+    Metadata,
+}
+
+impl Loss {
+    /// Returns short-string format of the loss.
+    #[inline]
+    pub fn description(&self) -> &'static str {
+        match self {
+            Loss::Kind => "message kind",
+            Loss::Id => "message identifier",
+            Loss::Method => "method name",
+            Loss::Payload => "payload",
+            Loss::Error => "error",
+            Loss::Metadata => "metadata",
+        }
+    }
+}
+
+impl fmt::Display for Loss {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.description())
+    }
+}
+
+/// Error of a conversion required to be lossless.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LossyConversion {
+    /// Information lost in the conversion.
+    pub losses: Vec<Loss>,
+}
+
+impl fmt::Display for LossyConversion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("conversion loses")?;
+        for (index, loss) in self.losses.iter().enumerate() {
+            let separator = if index == 0 { " " } else { ", " };
+            write!(f, "{}{}", separator, loss)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for LossyConversion {}
+
+impl From<LossyConversion> for io::Error {
+    fn from(err: LossyConversion) -> io::Error {
+        io::Error::new(io::ErrorKind::InvalidData, err)
+    }
+}
+
+/// Converted message.
+#[derive(Debug, Clone)]
+pub struct Conversion<T> {
+    /// Message in the target format.
+    pub message: T,
+
+    /// Information that could not be represented in the target format.
+    pub losses: Vec<Loss>,
+}
+
+impl<T> Conversion<T> {
+    /// Returns true if no information was lost.
+    #[inline]
+    pub fn is_lossless(&self) -> bool {
+        self.losses.is_empty()
+    }
+
+    /// Returns converted message if no information was lost.
+    pub fn lossless(self) -> Result<T, LossyConversion> {
+        if self.losses.is_empty() {
+            Ok(self.message)
+        } else {
+            Err(LossyConversion {
+                losses: self.losses,
+            })
+        }
+    }
+}
+
+/// Converts a message into another message format.
+///
+/// Fails only when the source payload can not be read,
+/// anything the target format can not carry is reported in [`Conversion::losses`].
+///
+/// [`Conversion::losses`]: struct.Conversion.html#structfield.losses
+pub fn convert<S, T>(source: &S) -> io::Result<Conversion<T>>
+where
+    S: traits::Message,
+    T: traits::Message,
+{
+    let kind = source.kind();
+    let id = source.id();
+    let method = source.method();
+    let data = source.read_optional::<Value>()?;
+    let error = source.clone().into_error();
+
+    let mut builder = match (kind, &error) {
+        (MessageKind::Response, _) => T::Builder::new_response(&request::<T>(id)),
+        (MessageKind::ErrorResponse, Some(error)) => {
+            T::Builder::new_error_response(&request::<T>(id), error.clone())
+        }
+        _ => builder::new::<T>(kind).with_id(id.clone()),
+    };
+    match (kind, method) {
+        (MessageKind::Event, Some(method)) => builder.set_event_name(method),
+        (MessageKind::Request, Some(method)) | (MessageKind::Undefined, Some(method)) => {
+            builder.set_method_name(method)
+        }
+        _ => {}
+    }
+    builder.set_metadata(source.metadata().clone());
+    let mut losses = Vec::new();
+    if let Some(data) = data.as_ref() {
+        if builder.set_data(data).is_err() {
+            losses.push(Loss::Payload);
+        }
+    }
+    let message = builder.build();
+
+    if message.kind() != kind {
+        losses.push(Loss::Kind);
+    }
+    if message.id() != id {
+        losses.push(Loss::Id);
+    }
+    if message.method() != method {
+        losses.push(Loss::Method);
+    }
+    if !losses.contains(&Loss::Payload) && !same_payload(data, message.read_optional()) {
+        losses.push(Loss::Payload);
+    }
+    if !same_error(error.as_ref(), message.clone().into_error().as_ref()) {
+        losses.push(Loss::Error);
+    }
+    if message.metadata() != source.metadata() {
+        losses.push(Loss::Metadata);
+    }
+    Ok(Conversion { message, losses })
+}
+
+/// Creates a request answered by a converted response.
+fn request<T: MessageBuilderExt>(id: &types::Id) -> T {
+    builder::new::<T>(MessageKind::Request)
+        .with_id(id.clone())
+        .build()
+}
+
+/// Compares payloads read from source and converted messages.
+fn same_payload(source: Option<Value>, target: io::Result<Option<Value>>) -> bool {
+    match target {
+        Ok(target) => source.map(normalize) == target.map(normalize),
+        Err(_) => false,
+    }
+}
+
+/// Compares errors of source and converted messages.
+///
+/// Missing description is equivalent to the description of an error kind.
+fn same_error(source: Option<&types::Error>, target: Option<&types::Error>) -> bool {
+    match (source, target) {
+        (Some(source), Some(target)) => {
+            let description = |err: &types::Error| {
+                err.description
+                    .clone()
+                    .unwrap_or_else(|| err.kind.description().into_owned())
+            };
+            source.kind == target.kind
+                && description(source) == description(target)
+                && source.data.clone().map(normalize) == target.data.clone().map(normalize)
+        }
+        (None, None) => true,
+        _ => false,
+    }
+}
+
+/// Normalizes a value to the representation shared by self-describing formats.
+///
+/// Numbers are widened, options and newtypes are unwrapped and characters become strings.
+fn normalize(value: Value) -> Value {
+    match value {
+        Value::U8(value) => Value::U64(value.into()),
+        Value::U16(value) => Value::U64(value.into()),
+        Value::U32(value) => Value::U64(value.into()),
+        Value::I8(value) => normalize_signed(value.into()),
+        Value::I16(value) => normalize_signed(value.into()),
+        Value::I32(value) => normalize_signed(value.into()),
+        Value::I64(value) => normalize_signed(value),
+        Value::F32(value) => Value::F64(value.into()),
+        Value::Char(value) => Value::String(value.to_string()),
+        Value::Option(None) => Value::Unit,
+        Value::Option(Some(value)) | Value::Newtype(value) => normalize(*value),
+        Value::Seq(values) => Value::Seq(values.into_iter().map(normalize).collect()),
+        Value::Map(map) => Value::Map(
+            map.into_iter()
+                .map(|(key, value)| (normalize(key), normalize(value)))
+                .collect::<BTreeMap<_, _>>(),
+        ),
+        value => value,
+    }
+}
+
+/// Normalizes a signed number, non-negative numbers are unsigned.
+fn normalize_signed(value: i64) -> Value {
+    if value >= 0 {
+        Value::U64(value as u64)
+    } else {
+        Value::I64(value)
+    }
+}
@@ -0,0 +1,89 @@
+//! Shared buffer of a frame being decoded.
+//!
+//! Codecs decoding messages from a [`Bytes`] frame can make it available
+//! to deserializers using [`scope`]. Payload types can then reference
+//! slices of the frame using [`slice`] instead of copying them.
+//!
+//! [`Bytes`]: https://docs.rs/bytes/0.5/bytes/struct.Bytes.html
+//! [`scope`]: fn.scope.html
+//! [`slice`]: fn.slice.html
+
+use std::cell::{Cell, RefCell};
+
+use bytes::Bytes;
+
+thread_local! {
+    /// Frame currently being decoded on this thread.
+    static FRAME: RefCell<Option<Bytes>> = const { RefCell::new(None) };
+
+    /// Whether a value buffered out of the frame is being deserialized on this thread.
+    static BUFFERED: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Runs a closure with `frame` set as a current frame.
+///
+/// Previous frame is restored after the closure returns or panics.
+pub fn scope<T, F: FnOnce() -> T>(frame: &Bytes, f: F) -> T {
+    let _restore = Restore(FRAME.with(|current| current.replace(Some(frame.clone()))));
+    f()
+}
+
+/// Restores a previous frame when dropped.
+struct Restore(Option<Bytes>);
+
+impl Drop for Restore {
+    fn drop(&mut self) {
+        let previous = self.0.take();
+        FRAME.with(|current| *current.borrow_mut() = previous);
+    }
+}
+
+/// Returns `true` if a frame is being decoded on this thread.
+pub fn is_set() -> bool {
+    FRAME.with(|current| current.borrow().is_some())
+}
+
+/// Runs a closure deserializing a value buffered out of the current frame.
+///
+/// Buffered values are deserialized by a format agnostic deserializer
+/// which does not borrow from the frame, see [`is_buffered`].
+///
+/// [`is_buffered`]: fn.is_buffered.html
+pub fn buffered<T, F: FnOnce() -> T>(f: F) -> T {
+    let _restore = RestoreBuffered(BUFFERED.with(|buffered| buffered.replace(true)));
+    f()
+}
+
+/// Restores a previous buffered state when dropped.
+struct RestoreBuffered(bool);
+
+impl Drop for RestoreBuffered {
+    fn drop(&mut self) {
+        let previous = self.0;
+        BUFFERED.with(|buffered| buffered.set(previous));
+    }
+}
+
+/// Returns `true` if a buffered value is being deserialized on this thread.
+pub fn is_buffered() -> bool {
+    BUFFERED.with(Cell::get)
+}
+
+/// Returns a slice of the current frame if `data` is contained in it.
+///
+/// Otherwise `data` is copied into a new buffer.
+pub fn slice(data: &[u8]) -> Bytes {
+    FRAME.with(|current| match current.borrow().as_ref() {
+        Some(frame) if contains(frame, data) => frame.slice_ref(data),
+        _ => Bytes::copy_from_slice(data),
+    })
+}
+
+/// Returns true if `data` points into `frame` memory.
+#[inline]
+fn contains(frame: &[u8], data: &[u8]) -> bool {
+    let start = frame.as_ptr() as usize;
+    let end = start + frame.len();
+    let data_start = data.as_ptr() as usize;
+    !data.is_empty() && data_start >= start && data_start + data.len() <= end
+}
@@ -1,19 +1,25 @@
 #[macro_use]
 extern crate serde_derive;
 
+pub mod batch;
 pub mod builder;
 pub mod compact;
+pub mod convert;
+pub mod frame;
+pub mod raw;
+pub mod scope;
 pub mod traits;
 pub mod types;
 
 pub mod prelude {
     pub use crate::{
+        batch::{Batch, Frame as _},
         builder::{self, MessageBuilder, MessageBuilderExt},
         traits::{
-            self, DeserializeOwned, Error as _, Id as _, Kind as _, Message as _, Method as _,
-            Read as _, Serialize,
+            self, DeserializeOwned, Error as _, Id as _, Kind as _, Message as _, Metadata as _,
+            Method as _, Read as _, Serialize, Validate as _,
         },
-        types::{self, ErrorKind, Id, MessageKind},
+        types::{self, ErrorKind, Id, InvalidReason, MessageKind, Metadata},
     };
 }
 
@@ -0,0 +1,406 @@
+//! Raw bytes message with an envelope.
+//!
+//! Wraps opaque payloads of peers that do not speak any structured protocol.
+//! Envelope fields (kind, ID, method and error) are kept only locally,
+//! raw codecs transmit the payload bytes alone.
+//!
+//! Frames received from a peer become [`Event`] messages without a method or ID.
+//! Requests sent over raw codecs cannot be correlated with responses,
+//! peers exchange events handled as notifications.
+//!
+//! [`Event`]: ../types/enum.MessageKind.html#variant.Event
+
+use std::{fmt, io};
+
+use bytes::Bytes;
+use serde::{
+    de::{self, value::SeqDeserializer, DeserializeOwned, Visitor},
+    ser::{self, Impossible, Serialize},
+};
+
+use crate::{builder, traits, types};
+
+/// Raw bytes message.
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Message {
+    kind: types::MessageKind,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    error: Option<types::Error>,
+    #[serde(default, skip_serializing_if = "types::Id::is_none")]
+    id: types::Id,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    #[serde(default, skip_serializing_if = "types::Metadata::is_empty")]
+    metadata: types::Metadata,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    data: Option<Bytes>,
+}
+
+impl Message {
+    /// Returns raw payload of the message.
+    #[inline]
+    pub fn data(&self) -> Option<&Bytes> {
+        self.data.as_ref()
+    }
+
+    /// Sets raw payload of the message.
+    #[inline]
+    pub fn set_bytes(&mut self, data: Bytes) {
+        self.data = Some(data);
+    }
+
+    /// Consumes the message and returns raw payload.
+    #[inline]
+    pub fn into_data(self) -> Option<Bytes> {
+        self.data
+    }
+}
+
+/// Creates an event message from a payload received from a peer.
+impl From<Bytes> for Message {
+    fn from(data: Bytes) -> Self {
+        Message {
+            kind: types::MessageKind::Event,
+            data: Some(data),
+            ..Default::default()
+        }
+    }
+}
+
+/// Returns payload transmitted to a peer.
+impl From<Message> for Bytes {
+    fn from(message: Message) -> Self {
+        message.data.unwrap_or_default()
+    }
+}
+
+impl traits::Id for Message {
+    fn id(&self) -> &types::Id {
+        &self.id
+    }
+}
+
+impl traits::Method for Message {
+    fn method(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+}
+
+impl traits::Kind for Message {
+    fn kind(&self) -> types::MessageKind {
+        self.kind
+    }
+}
+
+impl traits::Read for Message {
+    /// Deserializes payload from bytes.
+    ///
+    /// Target type has to accept bytes, e.g. `Bytes`, `Vec<u8>` or `String`.
+    fn read_optional<T: DeserializeOwned>(&self) -> io::Result<Option<T>> {
+        match self.data.as_ref() {
+            Some(data) => {
+                let deserializer = PayloadDeserializer(data);
+                Ok(Some(T::deserialize(deserializer).map_err(io::Error::from)?))
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+/// Raw messages carry no protocol structure and are always valid.
+impl traits::Validate for Message {
+    fn validate(&self) -> Result<(), types::InvalidReason> {
+        Ok(())
+    }
+}
+
+impl traits::Metadata for Message {
+    fn metadata(&self) -> &types::Metadata {
+        &self.metadata
+    }
+}
+
+impl traits::Error for Message {
+    fn error_kind(&self) -> Option<&types::ErrorKind> {
+        self.error.as_ref().map(|err| &err.kind)
+    }
+
+    fn description(&self) -> Option<&str> {
+        self.error
+            .as_ref()
+            .and_then(|err| err.description.as_deref())
+    }
+
+    fn into_error(self) -> Option<types::Error> {
+        self.error
+    }
+}
+
+impl traits::Message for Message {}
+
+impl builder::MessageBuilderExt for Message {
+    type Builder = Message;
+}
+
+impl builder::MessageBuilder<Message> for Message {
+    fn new(kind: types::MessageKind) -> Self {
+        Message {
+            kind,
+            ..Default::default()
+        }
+    }
+
+    fn new_response(request: &Message) -> Self {
+        let mut msg = Self::new(types::MessageKind::Response);
+        msg.id = request.id.clone();
+        msg
+    }
+
+    fn new_error_response(request: &Message, error: types::Error) -> Self {
+        let mut msg = Self::new(types::MessageKind::ErrorResponse);
+        msg.id = request.id.clone();
+        msg.error = Some(error);
+        msg
+    }
+
+    fn set_id(&mut self, id: types::Id) {
+        self.id = id;
+    }
+
+    fn set_event_name<T: ToString>(&mut self, name: T) {
+        self.name = Some(name.to_string());
+    }
+
+    fn set_method_name<T: ToString>(&mut self, method: T) {
+        self.name = Some(method.to_string());
+    }
+
+    fn metadata_mut(&mut self) -> &mut types::Metadata {
+        &mut self.metadata
+    }
+
+    /// Serializes payload to bytes.
+    ///
+    /// Value has to serialize as bytes or a string, e.g. `Bytes` or `&str`.
+    fn set_data<T: Serialize>(&mut self, data: &T) -> io::Result<()> {
+        self.data = Some(data.serialize(PayloadSerializer)?);
+        Ok(())
+    }
+
+    fn build(self) -> Message {
+        self
+    }
+}
+
+/// Raw payload serialization error.
+#[derive(Debug)]
+struct PayloadError(String);
+
+impl PayloadError {
+    fn unsupported() -> Self {
+        PayloadError("expected bytes or a string".to_owned())
+    }
+}
+
+impl fmt::Display for PayloadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "raw payload: {}", self.0)
+    }
+}
+
+impl std::error::Error for PayloadError {}
+
+impl ser::Error for PayloadError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        PayloadError(msg.to_string())
+    }
+}
+
+impl de::Error for PayloadError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        PayloadError(msg.to_string())
+    }
+}
+
+impl From<PayloadError> for io::Error {
+    fn from(err: PayloadError) -> io::Error {
+        io::Error::new(io::ErrorKind::InvalidInput, err)
+    }
+}
+
+/// Serializer of byte and string values into a raw payload.
+struct PayloadSerializer;
+
+macro_rules! unsupported {
+    ($($method:ident($($arg:ty),*),)*) => {
+        $(
+            fn $method(self, $(_: $arg),*) -> Result<Bytes, PayloadError> {
+                Err(PayloadError::unsupported())
+            }
+        )*
+    };
+}
+
+impl ser::Serializer for PayloadSerializer {
+    type Ok = Bytes;
+    type Error = PayloadError;
+    type SerializeSeq = Impossible<Bytes, PayloadError>;
+    type SerializeTuple = Impossible<Bytes, PayloadError>;
+    type SerializeTupleStruct = Impossible<Bytes, PayloadError>;
+    type SerializeTupleVariant = Impossible<Bytes, PayloadError>;
+    type SerializeMap = Impossible<Bytes, PayloadError>;
+    type SerializeStruct = Impossible<Bytes, PayloadError>;
+    type SerializeStructVariant = Impossible<Bytes, PayloadError>;
+
+    fn serialize_bytes(self, value: &[u8]) -> Result<Bytes, PayloadError> {
+        Ok(Bytes::copy_from_slice(value))
+    }
+
+    fn serialize_str(self, value: &str) -> Result<Bytes, PayloadError> {
+        Ok(Bytes::copy_from_slice(value.as_bytes()))
+    }
+
+    fn serialize_unit(self) -> Result<Bytes, PayloadError> {
+        Ok(Bytes::new())
+    }
+
+    fn serialize_none(self) -> Result<Bytes, PayloadError> {
+        Ok(Bytes::new())
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<Bytes, PayloadError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        _: &'static str,
+        value: &T,
+    ) -> Result<Bytes, PayloadError> {
+        value.serialize(self)
+    }
+
+    unsupported! {
+        serialize_bool(bool),
+        serialize_i8(i8),
+        serialize_i16(i16),
+        serialize_i32(i32),
+        serialize_i64(i64),
+        serialize_u8(u8),
+        serialize_u16(u16),
+        serialize_u32(u32),
+        serialize_u64(u64),
+        serialize_f32(f32),
+        serialize_f64(f64),
+        serialize_char(char),
+        serialize_unit_struct(&'static str),
+        serialize_unit_variant(&'static str, u32, &'static str),
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: &T,
+    ) -> Result<Bytes, PayloadError> {
+        Err(PayloadError::unsupported())
+    }
+
+    fn serialize_seq(self, _: Option<usize>) -> Result<Self::SerializeSeq, PayloadError> {
+        Err(PayloadError::unsupported())
+    }
+
+    fn serialize_tuple(self, _: usize) -> Result<Self::SerializeTuple, PayloadError> {
+        Err(PayloadError::unsupported())
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeTupleStruct, PayloadError> {
+        Err(PayloadError::unsupported())
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeTupleVariant, PayloadError> {
+        Err(PayloadError::unsupported())
+    }
+
+    fn serialize_map(self, _: Option<usize>) -> Result<Self::SerializeMap, PayloadError> {
+        Err(PayloadError::unsupported())
+    }
+
+    fn serialize_struct(
+        self,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeStruct, PayloadError> {
+        Err(PayloadError::unsupported())
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeStructVariant, PayloadError> {
+        Err(PayloadError::unsupported())
+    }
+}
+
+/// Deserializer of a raw payload into bytes, byte sequences or strings.
+struct PayloadDeserializer<'a>(&'a [u8]);
+
+impl<'de> de::Deserializer<'de> for PayloadDeserializer<'_> {
+    type Error = PayloadError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, PayloadError> {
+        visitor.visit_bytes(self.0)
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, PayloadError> {
+        match std::str::from_utf8(self.0) {
+            Ok(value) => visitor.visit_str(value),
+            Err(_) => visitor.visit_bytes(self.0),
+        }
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, PayloadError> {
+        self.deserialize_str(visitor)
+    }
+
+    /// Payload is read as a sequence of bytes, e.g. into `Vec<u8>`.
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, PayloadError> {
+        visitor.visit_seq(SeqDeserializer::new(self.0.iter().copied()))
+    }
+
+    /// Empty payload is read as `None`, same as it is serialized.
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, PayloadError> {
+        if self.0.is_empty() {
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, PayloadError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char bytes byte_buf
+        unit unit_struct tuple tuple_struct map struct enum identifier ignored_any
+    }
+}
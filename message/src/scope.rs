@@ -0,0 +1,23 @@
+//! Codec options in effect while messages are (de)serialized.
+//!
+//! Serialization formats can not carry settings of a codec to message types.
+//! Codecs run (de)serialization inside of [`Scope::scope`] so message types
+//! can read options of the codec instead of sharing them by the whole process.
+//!
+//! [`Scope::scope`]: trait.Scope.html#tymethod.scope
+
+/// Options of a codec applied while messages are (de)serialized.
+pub trait Scope {
+    /// Runs a closure with the options in effect.
+    ///
+    /// Previous options are restored after the closure returns or panics.
+    fn scope<T, F: FnOnce() -> T>(&self, f: F) -> T;
+}
+
+/// No options, messages are (de)serialized with their defaults.
+impl Scope for () {
+    #[inline(always)]
+    fn scope<T, F: FnOnce() -> T>(&self, f: F) -> T {
+        f()
+    }
+}
@@ -1,6 +1,7 @@
 use std::{
     fmt::Debug,
     io::{self, ErrorKind, Result},
+    time::Duration,
 };
 
 pub use serde::{de::DeserializeOwned, ser::Serialize};
@@ -31,6 +32,54 @@ pub trait Read {
     fn read_optional<T: serde::de::DeserializeOwned>(&self) -> Result<Option<T>>;
 }
 
+/// Message validation trait.
+pub trait Validate {
+    /// Returns a reason if the message is not valid.
+    fn validate(&self) -> std::result::Result<(), types::InvalidReason>;
+}
+
+/// Message metadata trait.
+pub trait Metadata {
+    fn metadata(&self) -> &types::Metadata;
+
+    /// Returns metadata value under the given key.
+    fn meta(&self, key: &str) -> Option<&str> {
+        self.metadata().get(key).map(String::as_str)
+    }
+
+    /// Returns request timeout encoded under [`TIMEOUT_META`] key.
+    ///
+    /// [`TIMEOUT_META`]: ../types/constant.TIMEOUT_META.html
+    fn timeout(&self) -> Option<Duration> {
+        self.meta(types::TIMEOUT_META)
+            .and_then(|timeout| timeout.parse().ok())
+            .map(Duration::from_millis)
+    }
+}
+
+/// Message payload trait.
+///
+/// Implemented by types holding serialized message data.
+pub trait Payload: Sized {
+    /// Serializes data into a payload.
+    fn from_data<T: Serialize>(data: &T) -> Result<Self>;
+
+    /// Deserializes data from a payload.
+    fn read_data<T: DeserializeOwned>(&self) -> Result<T>;
+}
+
+impl Payload for types::Value {
+    fn from_data<T: Serialize>(data: &T) -> Result<Self> {
+        serde_value::to_value(data).map_err(|err| io::Error::new(ErrorKind::InvalidData, err))
+    }
+
+    fn read_data<T: DeserializeOwned>(&self) -> Result<T> {
+        self.clone()
+            .deserialize_into()
+            .map_err(|err| io::Error::new(ErrorKind::InvalidData, err))
+    }
+}
+
 /// Error message trait.
 pub trait Error {
     fn error_kind(&self) -> Option<&types::ErrorKind>;
@@ -44,6 +93,8 @@ pub trait Message:
     + Kind
     + Read
     + Error
+    + Metadata
+    + Validate
     + MessageBuilderExt
     + DeserializeOwned
     + Serialize
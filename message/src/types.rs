@@ -1,4 +1,10 @@
-use std::{borrow::Cow, fmt};
+use std::{borrow::Cow, collections::BTreeMap, fmt, io};
+
+use serde::{de::DeserializeOwned, ser::Serialize};
+
+use crate::traits::Payload;
+
+pub use serde_value::Value;
 
 /// Message kind.
 ///
@@ -18,13 +24,25 @@ impl Default for MessageKind {
     }
 }
 
+/// Message metadata.
+///
+/// String key-value pairs carried alongside the message payload,
+/// such as authentication tokens, trace context or tenant identifiers.
+pub type Metadata = BTreeMap<String, String>;
+
+/// Metadata key of a request timeout in milliseconds.
+///
+/// Timeout is relative to the time of receiving the request,
+/// so it does not depend on clocks of the peers being in sync.
+pub const TIMEOUT_META: &str = "timeout";
+
 /// Message unique identifier in the context of a [`Channel`].
 ///
 /// NOTE: Current `TryInto<u64>` implementation returns `Ok(0)` on `Null` id.
 ///
 /// [`Channel`]: ../channel/type.Channel.html
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
-#[serde(deny_unknown_fields, untagged)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+#[serde(untagged)]
 pub enum Id {
     /// Empty message identifier.
     ///
@@ -64,6 +82,57 @@ impl Id {
     }
 }
 
+/// Deserializes identifier without buffering it like `untagged` enums do.
+impl<'de> serde::de::Deserialize<'de> for Id {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Id, D::Error>
+    where
+        D: serde::de::Deserializer<'de>,
+    {
+        deserializer.deserialize_any(IdVisitor)
+    }
+}
+
+struct IdVisitor;
+
+impl<'de> serde::de::Visitor<'de> for IdVisitor {
+    type Value = Id;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a string, an unsigned integer or null")
+    }
+
+    fn visit_unit<E: serde::de::Error>(self) -> std::result::Result<Id, E> {
+        Ok(Id::Null)
+    }
+
+    fn visit_none<E: serde::de::Error>(self) -> std::result::Result<Id, E> {
+        Ok(Id::Null)
+    }
+
+    fn visit_u64<E: serde::de::Error>(self, value: u64) -> std::result::Result<Id, E> {
+        Ok(Id::Num(value))
+    }
+
+    fn visit_i64<E: serde::de::Error>(self, value: i64) -> std::result::Result<Id, E> {
+        if value >= 0 {
+            Ok(Id::Num(value as u64))
+        } else {
+            Err(E::invalid_value(
+                serde::de::Unexpected::Signed(value),
+                &self,
+            ))
+        }
+    }
+
+    fn visit_str<E: serde::de::Error>(self, value: &str) -> std::result::Result<Id, E> {
+        Ok(Id::Str(value.to_owned()))
+    }
+
+    fn visit_string<E: serde::de::Error>(self, value: String) -> std::result::Result<Id, E> {
+        Ok(Id::Str(value))
+    }
+}
+
 impl std::convert::TryFrom<&Id> for u64 {
     type Error = std::num::ParseIntError;
 
@@ -101,6 +170,8 @@ impl Default for Id {
 }
 
 /// Error kind.
+///
+/// Binary formats encode variants by index, new variants are appended.
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ErrorKind {
     /// Internal server error.
@@ -109,8 +180,22 @@ pub enum ErrorKind {
     /// Method not found error.
     MethodNotFound,
 
-    /// Server error code.
+    /// Application error code.
     ErrorCode(i64),
+
+    /// Invalid message was received.
+    ParseError,
+
+    /// Message is not a valid request.
+    InvalidRequest,
+
+    /// Invalid method parameters.
+    InvalidParams,
+
+    /// Implementation-defined server error code.
+    ///
+    /// JSON-RPC reserves codes from `-32099` to `-32000` for server errors.
+    ServerError(i64),
 }
 
 impl ErrorKind {
@@ -118,8 +203,12 @@ impl ErrorKind {
     #[inline]
     pub fn description(&self) -> Cow<'static, str> {
         match self {
-            ErrorKind::InternalError => Cow::Borrowed("internal server error"),
+            ErrorKind::ParseError => Cow::Borrowed("parse error"),
+            ErrorKind::InvalidRequest => Cow::Borrowed("invalid request"),
             ErrorKind::MethodNotFound => Cow::Borrowed("method not found"),
+            ErrorKind::InvalidParams => Cow::Borrowed("invalid params"),
+            ErrorKind::InternalError => Cow::Borrowed("internal server error"),
+            ErrorKind::ServerError(code) => Cow::Owned(format!("server error: {}", code)),
             ErrorKind::ErrorCode(code) => Cow::Owned(format!("error code: {}", code)),
         }
     }
@@ -146,12 +235,34 @@ pub struct Error {
 
     /// Optional error description.
     pub description: Option<String>,
+
+    /// Optional structured error details.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<Value>,
 }
 
 impl Error {
     /// Creates a new error.
     pub fn new(kind: ErrorKind, description: Option<String>) -> Self {
-        Error { kind, description }
+        Error {
+            kind,
+            description,
+            data: None,
+        }
+    }
+
+    /// Sets structured error details.
+    pub fn with_data<T: Serialize>(mut self, data: &T) -> io::Result<Self> {
+        self.data = Some(Payload::from_data(data)?);
+        Ok(self)
+    }
+
+    /// Deserializes structured error details.
+    pub fn read_data<T: DeserializeOwned>(&self) -> io::Result<Option<T>> {
+        match self.data.as_ref() {
+            Some(data) => Ok(Some(data.read_data()?)),
+            None => Ok(None),
+        }
     }
 }
 
@@ -162,6 +273,7 @@ impl From<ErrorKind> for Error {
         Error {
             kind,
             description: None,
+            data: None,
         }
     }
 }
@@ -173,5 +285,60 @@ impl fmt::Display for Error {
     }
 }
 
+/// Reason of a message validation failure.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum InvalidReason {
+    /// Message kind could not be determined.
+    UndefinedKind,
+
+    /// Request or notification without a method name.
+    MissingMethod,
+
+    /// Response without a message identifier.
+    MissingId,
+
+    /// Error response without an error.
+    MissingError,
+
+    /// Message contains both a result and an error.
+    ResultAndError,
+
+    /// Protocol version is not supported.
+    UnsupportedVersion,
+}
+
+impl InvalidReason {
+    /// Returns short-string format of the reason.
+    #[inline]
+    pub fn description(&self) -> &'static str {
+        match self {
+            InvalidReason::UndefinedKind => "undefined message kind",
+            InvalidReason::MissingMethod => "missing method name",
+            InvalidReason::MissingId => "missing message id",
+            InvalidReason::MissingError => "missing error",
+            InvalidReason::ResultAndError => "both result and error are set",
+            InvalidReason::UnsupportedVersion => "unsupported protocol version",
+        }
+    }
+}
+
+impl std::error::Error for InvalidReason {}
+
+impl fmt::Display for InvalidReason {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.description())
+    }
+}
+
+impl From<InvalidReason> for Error {
+    fn from(reason: InvalidReason) -> Error {
+        Error::new(
+            ErrorKind::InvalidRequest,
+            Some(reason.description().to_owned()),
+        )
+    }
+}
+
 /// Result type alias with message error type.
 pub type Result<T> = std::result::Result<T, Error>;
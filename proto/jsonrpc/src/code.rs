@@ -7,6 +7,27 @@ use serde::ser::{Serialize, Serializer};
 
 use net3_msg::types::ErrorKind;
 
+/// Invalid JSON was received by the server.
+pub const PARSE_ERROR: i64 = -32700;
+
+/// The JSON sent is not a valid request object.
+pub const INVALID_REQUEST: i64 = -32600;
+
+/// The method does not exist or is not available.
+pub const METHOD_NOT_FOUND: i64 = -32601;
+
+/// Invalid method parameters.
+pub const INVALID_PARAMS: i64 = -32602;
+
+/// Internal JSON-RPC error.
+pub const INTERNAL_ERROR: i64 = -32603;
+
+/// Start of the range reserved for implementation-defined server errors.
+pub const SERVER_ERROR_START: i64 = -32099;
+
+/// End of the range reserved for implementation-defined server errors.
+pub const SERVER_ERROR_END: i64 = -32000;
+
 /// JSON-RPC error code.
 #[derive(Debug, PartialEq, Copy, Clone)]
 pub struct ErrorCode(pub ErrorKind);
@@ -33,8 +54,12 @@ impl ErrorCode {
     /// Returns integer code value
     pub fn code(&self) -> i64 {
         match self.0 {
-            ErrorKind::MethodNotFound => -32601,
-            ErrorKind::InternalError => -32603,
+            ErrorKind::ParseError => PARSE_ERROR,
+            ErrorKind::InvalidRequest => INVALID_REQUEST,
+            ErrorKind::MethodNotFound => METHOD_NOT_FOUND,
+            ErrorKind::InvalidParams => INVALID_PARAMS,
+            ErrorKind::InternalError => INTERNAL_ERROR,
+            ErrorKind::ServerError(code) => code,
             ErrorKind::ErrorCode(code) => code,
         }
     }
@@ -43,8 +68,12 @@ impl ErrorCode {
 impl From<i64> for ErrorCode {
     fn from(code: i64) -> Self {
         match code {
-            -32601 => ErrorCode(ErrorKind::MethodNotFound),
-            -32603 => ErrorCode(ErrorKind::InternalError),
+            PARSE_ERROR => ErrorCode(ErrorKind::ParseError),
+            INVALID_REQUEST => ErrorCode(ErrorKind::InvalidRequest),
+            METHOD_NOT_FOUND => ErrorCode(ErrorKind::MethodNotFound),
+            INVALID_PARAMS => ErrorCode(ErrorKind::InvalidParams),
+            INTERNAL_ERROR => ErrorCode(ErrorKind::InternalError),
+            SERVER_ERROR_START..=SERVER_ERROR_END => ErrorCode(ErrorKind::ServerError(code)),
             code => ErrorCode(ErrorKind::ErrorCode(code)),
         }
     }
@@ -1,12 +1,20 @@
 //! JSON-RPC error structures.
 
-use net3_msg::types::ErrorKind;
+use std::fmt;
 
-use super::{ErrorCode, Params};
+use serde::de::{
+    value::MapAccessDeserializer, Deserialize, Deserializer, Error as _, IgnoredAny, MapAccess,
+    SeqAccess, Visitor,
+};
+
+use net3_msg::types::{ErrorKind, Value};
+
+use super::{extensions::Mode, options, ErrorCode, Params};
 
 /// Error object as defined in Spec
-#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
-#[serde(deny_unknown_fields)]
+///
+/// Stratum errors in a form of `[code, message, traceback]` array are accepted as well.
+#[derive(Debug, PartialEq, Clone, Serialize)]
 pub struct Error {
     /// Code
     pub code: ErrorCode,
@@ -32,19 +40,126 @@ impl Error {
         Self::new(ErrorCode(ErrorKind::MethodNotFound))
     }
 
+    /// Creates new `ParseError`
+    pub fn parse_error() -> Self {
+        Self::new(ErrorCode(ErrorKind::ParseError))
+    }
+
+    /// Creates new `InvalidRequest`
+    pub fn invalid_request() -> Self {
+        Self::new(ErrorCode(ErrorKind::InvalidRequest))
+    }
+
+    /// Creates new `InvalidParams`
+    pub fn invalid_params() -> Self {
+        Self::new(ErrorCode(ErrorKind::InvalidParams))
+    }
+
     /// Creates new `InternalError`
     pub fn internal_error() -> Self {
         Self::new(ErrorCode(ErrorKind::InternalError))
     }
 
+    /// Converts into a message error.
+    ///
+    /// Error data is converted into a structured value.
+    /// Data that can not be converted is kept as a string of raw JSON.
     pub fn into_error(self) -> net3_msg::types::Error {
         net3_msg::types::Error {
             kind: self.code.0,
             description: Some(self.message),
+            data: self.data.and_then(|data| data.value).map(|data| {
+                serde_json::from_str(data.get()).unwrap_or_else(|err| {
+                    log::warn!("Error data kept as raw JSON: {}", err);
+                    Value::String(data.get().to_owned())
+                })
+            }),
         }
     }
 }
 
+/// Error object deserialization helper.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ErrorObject {
+    code: ErrorCode,
+    message: String,
+    data: Option<Params>,
+}
+
+/// Error object deserialization helper ignoring unknown fields.
+#[derive(Deserialize)]
+struct LenientErrorObject {
+    code: ErrorCode,
+    message: String,
+    data: Option<Params>,
+}
+
+impl From<LenientErrorObject> for ErrorObject {
+    fn from(error: LenientErrorObject) -> Self {
+        ErrorObject {
+            code: error.code,
+            message: error.message,
+            data: error.data,
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Error {
+    fn deserialize<D>(deserializer: D) -> Result<Error, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(ErrorVisitor)
+    }
+}
+
+struct ErrorVisitor;
+
+impl<'de> Visitor<'de> for ErrorVisitor {
+    type Value = Error;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("an error object or an array")
+    }
+
+    fn visit_map<A>(self, map: A) -> Result<Error, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let map = MapAccessDeserializer::new(map);
+        let error = match options::current().extensions {
+            Mode::Strict => ErrorObject::deserialize(map)?,
+            Mode::Extensions => LenientErrorObject::deserialize(map)?.into(),
+        };
+        Ok(Error {
+            code: error.code,
+            message: error.message,
+            data: error.data,
+        })
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Error, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let code = seq
+            .next_element()?
+            .ok_or_else(|| A::Error::invalid_length(0, &self))?;
+        let message = seq
+            .next_element::<Option<String>>()?
+            .ok_or_else(|| A::Error::invalid_length(1, &self))?
+            .unwrap_or_default();
+        let data = seq.next_element::<Params>()?.filter(Params::is_some);
+        while seq.next_element::<IgnoredAny>()?.is_some() {}
+        Ok(Error {
+            code,
+            message,
+            data,
+        })
+    }
+}
+
 impl std::fmt::Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         write!(f, "{}: {}", self.code.description(), self.message)
@@ -0,0 +1,36 @@
+//! JSON-RPC message extension fields.
+//!
+//! Some peers send non-standard top-level fields, like `worker` or `algo`.
+//! By default such messages are rejected. In [`Mode::Extensions`] unknown fields
+//! are kept in [`Message::extensions`] and sent back when the message is serialized.
+//! Mode is a codec option, see [`Options::extensions`].
+//!
+//! [`Mode::Extensions`]: enum.Mode.html#variant.Extensions
+//! [`Message::extensions`]: ../message/struct.Message.html#structfield.extensions
+//! [`Options::extensions`]: ../options/struct.Options.html#structfield.extensions
+
+use std::collections::BTreeMap;
+
+use super::Params;
+
+/// Extension fields of a message by name.
+pub type Extensions = BTreeMap<String, Params>;
+
+/// Handling of unknown message fields.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    /// Messages with unknown fields are rejected.
+    Strict,
+
+    /// Unknown top-level fields are kept as extensions.
+    ///
+    /// Unknown fields of error objects are ignored.
+    Extensions,
+}
+
+impl Default for Mode {
+    #[inline]
+    fn default() -> Self {
+        Mode::Strict
+    }
+}
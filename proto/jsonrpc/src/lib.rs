@@ -3,13 +3,18 @@ extern crate serde_derive;
 
 pub mod code;
 pub mod error;
+pub mod extensions;
 pub mod message;
+pub mod metadata;
+pub mod options;
 pub mod params;
 pub mod version;
 
 pub use self::code::ErrorCode;
 pub use self::error::Error;
+pub use self::extensions::Extensions;
 pub use self::message::*;
+pub use self::options::Options;
 pub use self::params::{Params, RawValue};
 pub use self::version::Version;
 
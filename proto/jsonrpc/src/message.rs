@@ -1,41 +1,331 @@
 //! JSON-RPC message structure.
 
-use std::io::Result;
+use std::{fmt, io::Result};
+
+use serde::{
+    de::{self, Deserializer, MapAccess, Visitor},
+    ser::{SerializeMap, Serializer},
+    Deserialize,
+};
 
 use net3_msg::prelude::*;
 
-use super::{Error, ErrorCode, Params, Version};
+use super::{
+    extensions::{Extensions, Mode},
+    options, Error, ErrorCode, Params, RawValue, Version,
+};
 
 /// JSON-RPC message structure.
 ///
 /// Designed to handle different stratum implementations.
 /// It should not be used directly by client facing APIs.
-#[derive(Debug, PartialEq, Clone, Serialize, Deserialize, Default)]
-#[serde(deny_unknown_fields)]
+///
+/// Unknown fields are rejected, except for the [`metadata`] extension field,
+/// unless they are kept as [`extensions`]. Name of the metadata field
+/// is read from codec [`Options`].
+///
+/// Messages without the `jsonrpc` field are read as [`Version::V1`] messages
+/// and serialized in the same dialect, messages with `"jsonrpc": "1.0"` keep the field.
+/// Responses mirror the request dialect. Messages without the field are rejected
+/// if [`Options::strict_version`] is set.
+///
+/// [`metadata`]: ../metadata/index.html
+/// [`Options`]: ../options/struct.Options.html
+/// [`Options::strict_version`]: ../options/struct.Options.html#structfield.strict_version
+/// [`extensions`]: ../extensions/index.html
+/// [`Version::V1`]: ../version/enum.Version.html#variant.V1
+#[derive(Debug, PartialEq, Clone, Default)]
 pub struct Message {
     /// Protocol version.
-    #[serde(default, rename = "jsonrpc")]
     pub version: Version,
 
     /// Message unique ID.
-    #[serde(default, skip_serializing_if = "Id::is_none")]
     pub id: Id,
 
     /// A String containing the name of the method to be invoked.
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub method: Option<String>,
 
     /// Notification parameters.
-    #[serde(skip_serializing_if = "Params::is_none")]
     pub params: Params,
 
     /// Method call result.
-    #[serde(skip_serializing_if = "Params::is_none")]
     pub result: Params,
 
     /// Method call error.
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub error: Option<Error>,
+
+    /// Message metadata extension.
+    pub metadata: Metadata,
+
+    /// Non-standard fields of the message.
+    ///
+    /// Names of the standard fields should not be used,
+    /// they are rejected by [`set_extension`](#method.set_extension).
+    pub extensions: Extensions,
+}
+
+impl Message {
+    /// Sets protocol version of the message.
+    #[inline]
+    pub fn with_version(mut self, version: Version) -> Self {
+        self.version = version;
+        self
+    }
+
+    /// Returns value of an extension field.
+    #[inline]
+    pub fn extension(&self, name: &str) -> Option<&Params> {
+        self.extensions.get(name)
+    }
+
+    /// Reads value of an extension field.
+    pub fn read_extension<T: DeserializeOwned>(&self, name: &str) -> Result<Option<T>> {
+        match self
+            .extension(name)
+            .and_then(|params| params.value.as_ref())
+        {
+            Some(value) => Ok(Some(serde_json::from_str(value.get()).map_err(|err| {
+                std::io::Error::new(std::io::ErrorKind::InvalidData, err)
+            })?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Sets value of an extension field.
+    ///
+    /// Names of the standard fields and of the metadata field are rejected.
+    pub fn set_extension<T: Serialize>(&mut self, name: &str, value: &T) -> Result<()> {
+        if FIELDS.contains(&name) || name == options::current().metadata_field {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("reserved extension field name `{}`", name),
+            ));
+        }
+        self.extensions.insert(name.to_owned(), Params::new(value)?);
+        Ok(())
+    }
+
+    /// Returns protocol version of a response to the message.
+    ///
+    /// Responses to messages of unsupported versions use the default version.
+    #[inline]
+    fn response_version(&self) -> Version {
+        match self.version {
+            Version::Unsupported => Version::default(),
+            version => version,
+        }
+    }
+}
+
+/// Names of the standard message fields.
+const FIELDS: &[&str] = &["jsonrpc", "id", "method", "params", "result", "error"];
+
+impl Serialize for Message {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        // JSON-RPC 1.0 messages always carry an `id`, calls carry `params`
+        // and responses carry both `result` and `error`.
+        // Error responses carry an `id` even if it is `null`.
+        let kind = self.kind();
+        // Calls of the default version are sent in the dialect of codec options.
+        let version = match (kind, self.version) {
+            (MessageKind::Request, Version::V2) | (MessageKind::Event, Version::V2) => {
+                options::current().request_version
+            }
+            (_, version) => version,
+        };
+        let v1 = version.is_v1();
+        let tagged = !v1 || version == Version::V1Tagged;
+        let has_id = v1 || self.id.is_some() || self.error.is_some();
+        let (is_call, is_response) = match kind {
+            MessageKind::Request | MessageKind::Event => (v1, false),
+            MessageKind::Response | MessageKind::ErrorResponse => (false, v1),
+            MessageKind::Undefined => (false, false),
+        };
+        let len = tagged as usize
+            + has_id as usize
+            + self.method.is_some() as usize
+            + (is_call || self.params.is_some()) as usize
+            + (is_response || self.result.is_some()) as usize
+            + (is_response || self.error.is_some()) as usize
+            + !self.metadata.is_empty() as usize
+            + self.extensions.len();
+        let mut state = serializer.serialize_map(Some(len))?;
+        if tagged {
+            state.serialize_entry("jsonrpc", &version)?;
+        }
+        if has_id {
+            state.serialize_entry("id", &self.id)?;
+        }
+        if let Some(method) = &self.method {
+            state.serialize_entry("method", method)?;
+        }
+        if self.params.is_some() {
+            state.serialize_entry("params", &self.params)?;
+        } else if is_call {
+            state.serialize_entry("params", &[(); 0])?;
+        }
+        if is_response || self.result.is_some() {
+            state.serialize_entry("result", &self.result)?;
+        }
+        if is_response || self.error.is_some() {
+            state.serialize_entry("error", &self.error)?;
+        }
+        if !self.metadata.is_empty() {
+            state.serialize_entry(options::current().metadata_field, &self.metadata)?;
+        }
+        for (name, value) in &self.extensions {
+            state.serialize_entry(name, value)?;
+        }
+        state.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for Message {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Message, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_struct("Message", FIELDS, MessageVisitor)
+    }
+}
+
+/// Message field identifier.
+#[derive(Clone, Copy)]
+enum Field {
+    Version,
+    Id,
+    Method,
+    Params,
+    Result,
+    Error,
+    Metadata,
+}
+
+/// Message object key.
+enum Key {
+    /// Standard field.
+    Field(Field),
+
+    /// Unknown field kept as an extension.
+    Extension(String),
+}
+
+impl<'de> Deserialize<'de> for Key {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Key, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_identifier(KeyVisitor)
+    }
+}
+
+struct KeyVisitor;
+
+impl<'de> Visitor<'de> for KeyVisitor {
+    type Value = Key;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a field name")
+    }
+
+    fn visit_str<E>(self, value: &str) -> std::result::Result<Key, E>
+    where
+        E: de::Error,
+    {
+        let field = match value {
+            "jsonrpc" => Field::Version,
+            "id" => Field::Id,
+            "method" => Field::Method,
+            "params" => Field::Params,
+            "result" => Field::Result,
+            "error" => Field::Error,
+            _ if value == options::current().metadata_field => Field::Metadata,
+            _ => match options::current().extensions {
+                Mode::Extensions => return Ok(Key::Extension(value.to_owned())),
+                Mode::Strict => return Err(de::Error::unknown_field(value, FIELDS)),
+            },
+        };
+        Ok(Key::Field(field))
+    }
+
+    fn visit_bytes<E>(self, value: &[u8]) -> std::result::Result<Key, E>
+    where
+        E: de::Error,
+    {
+        match std::str::from_utf8(value) {
+            Ok(value) => self.visit_str(value),
+            Err(_) => Err(de::Error::invalid_value(
+                de::Unexpected::Bytes(value),
+                &self,
+            )),
+        }
+    }
+}
+
+struct MessageVisitor;
+
+impl<'de> Visitor<'de> for MessageVisitor {
+    type Value = Message;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a JSON-RPC message")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> std::result::Result<Message, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut message = Message::default();
+        let mut seen = [false; 7];
+        while let Some(key) = map.next_key::<Key>()? {
+            let field = match key {
+                Key::Field(field) => field,
+                Key::Extension(name) => {
+                    if message.extensions.contains_key(&name) {
+                        return Err(de::Error::custom(format_args!(
+                            "duplicate field `{}`",
+                            name
+                        )));
+                    }
+                    let value = map.next_value::<RawValue>()?;
+                    message
+                        .extensions
+                        .insert(name, Params { value: Some(value) });
+                    continue;
+                }
+            };
+            let index = field as usize;
+            if seen[index] {
+                let name = FIELDS
+                    .get(index)
+                    .copied()
+                    .unwrap_or_else(|| options::current().metadata_field);
+                return Err(de::Error::duplicate_field(name));
+            }
+            seen[index] = true;
+            match field {
+                Field::Version => message.version = map.next_value()?,
+                Field::Id => message.id = map.next_value()?,
+                Field::Method => message.method = map.next_value()?,
+                Field::Params => message.params = map.next_value()?,
+                // Explicit `null` result is kept to tell it apart from a missing one.
+                Field::Result => message.result.value = Some(map.next_value::<RawValue>()?),
+                Field::Error => message.error = map.next_value()?,
+                Field::Metadata => message.metadata = map.next_value()?,
+            }
+        }
+        if !seen[Field::Version as usize] {
+            message.version = if options::current().strict_version {
+                Version::Unsupported
+            } else {
+                Version::V1
+            };
+        }
+        Ok(message)
+    }
 }
 
 impl traits::Id for Message {
@@ -69,11 +359,38 @@ impl traits::Error for Message {
     }
 }
 
+impl traits::Validate for Message {
+    fn validate(&self) -> std::result::Result<(), InvalidReason> {
+        if self.version == Version::Unsupported {
+            return Err(InvalidReason::UnsupportedVersion);
+        }
+        if self.error.is_some() && !self.result.is_null() {
+            return Err(InvalidReason::ResultAndError);
+        }
+        if self.id.is_none() {
+            if self.error.is_some() || self.result.is_some() {
+                return Err(InvalidReason::MissingId);
+            }
+            if is_str_empty(&self.method) {
+                return Err(InvalidReason::MissingMethod);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl traits::Metadata for Message {
+    #[inline(always)]
+    fn metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+}
+
 impl traits::Read for Message {
     fn read_optional<T: DeserializeOwned>(&self) -> Result<Option<T>> {
         let params = if self.params.is_some() {
             self.params.value.as_ref()
-        } else if self.result.is_some() {
+        } else if !self.result.is_null() {
             self.result.value.as_ref()
         } else {
             None
@@ -92,6 +409,10 @@ impl traits::Kind for Message {
     ///
     /// Implementation includes tweaks to accept `method` in [`Response`] message[^1].
     ///
+    /// Messages without an `id` are notifications and read as [`Event`] messages
+    /// if they name a method, otherwise their kind is undefined.
+    ///
+    /// [`Event`]: ../../message/enum.MessageKind.html#variant.Event
     /// [`specification`]: https://www.jsonrpc.org/specification
     /// [`Response`]: ../../message/enum.MessageKind.html#variant.Response
     /// [^1]: https://github.com/mwcproject/mwc-node/blob/master/doc/stratum.md
@@ -109,7 +430,8 @@ impl traits::Kind for Message {
                 } else {
                     MessageKind::Request
                 }
-            } else if self.result.is_none() {
+            } else if self.result.is_null() {
+                // JSON-RPC 1.0 error responses carry a `null` result.
                 MessageKind::ErrorResponse
             } else {
                 MessageKind::Undefined
@@ -119,9 +441,9 @@ impl traits::Kind for Message {
             // Params field can be left `None` as `ping` command does use it.
             // https://www.jsonrpc.org/specification#notification
             if is_str_empty(&self.method) {
-                MessageKind::Event
-            } else {
                 MessageKind::Undefined
+            } else {
+                MessageKind::Event
             }
         }
     }
@@ -154,34 +476,51 @@ impl MessageBuilder<Message> for Message {
         self.method = Some(method.to_string());
     }
 
+    fn metadata_mut(&mut self) -> &mut Metadata {
+        &mut self.metadata
+    }
+
     fn new_response(request: &Message) -> Self {
         Message {
-            version: Version::V2,
+            version: request.response_version(),
             id: request.id.clone(),
             // Default parameters
             error: None,
             method: None,
             params: Params::empty(),
             result: Params::empty(),
+            metadata: Metadata::new(),
+            extensions: Extensions::new(),
         }
     }
 
     fn new_error_response(request: &Message, error: types::Error) -> Self {
         let kind = error.kind;
         Message {
-            version: Version::V2,
+            version: request.response_version(),
             error: Some(Error {
                 message: error
                     .description
                     .unwrap_or_else(|| kind.description().to_string()),
                 code: ErrorCode(error.kind),
-                data: None,
+                data: error
+                    .data
+                    .as_ref()
+                    .and_then(|data| match Params::new(data) {
+                        Ok(data) => Some(data),
+                        Err(err) => {
+                            log::error!("Error data of {:?} dropped: {}", kind, err);
+                            None
+                        }
+                    }),
             }),
             id: request.id.clone(),
             // Default parameters
             method: None,
             result: Params::empty(),
             params: Params::empty(),
+            metadata: Metadata::new(),
+            extensions: Extensions::new(),
         }
     }
 
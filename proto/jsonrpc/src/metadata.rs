@@ -0,0 +1,10 @@
+//! JSON-RPC message metadata extension field.
+//!
+//! JSON-RPC has no standard place for message metadata, it is carried
+//! in an extension field of the message object. Name of the field
+//! is a codec option, see [`Options::with_metadata_field`].
+//!
+//! [`Options::with_metadata_field`]: ../options/struct.Options.html#method.with_metadata_field
+
+/// Default name of the metadata extension field.
+pub const DEFAULT_FIELD: &str = "meta";
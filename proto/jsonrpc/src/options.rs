@@ -0,0 +1,120 @@
+//! JSON-RPC codec options.
+//!
+//! Options are set on a codec and are in effect while it deserializes
+//! and serializes messages, so peers of different connections can use
+//! different dialects in the same process.
+//!
+//! ```
+//! use net3_msg::{prelude::*, scope::Scope};
+//! use net3_proto_jsonrpc::{Message, Options};
+//!
+//! let message = Message::new_request(Id::Num(1), "test").with_meta("trace", "abc");
+//! let options = Options::default().with_metadata_field("_meta");
+//! let body = options.scope(|| serde_json::to_string(&message)).unwrap();
+//! assert_eq!(
+//!     body,
+//!     r#"{"jsonrpc":"2.0","id":1,"method":"test","_meta":{"trace":"abc"}}"#
+//! );
+//! ```
+//!
+//! Messages (de)serialized outside of a codec use default options.
+
+use std::cell::Cell;
+
+use net3_msg::scope::Scope;
+
+use super::{extensions::Mode, metadata, Version};
+
+/// JSON-RPC codec options.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Options {
+    /// Name of the metadata extension field.
+    ///
+    /// Peers have to agree on the name of the field.
+    pub metadata_field: &'static str,
+
+    /// Rejects messages without the `jsonrpc` field.
+    ///
+    /// Such messages are read as an unsupported version and fail validation.
+    /// By default they are read as JSON-RPC 1.0 messages.
+    pub strict_version: bool,
+
+    /// Version of requests and notifications sent by the codec.
+    ///
+    /// Calls built with the default version are sent in this dialect,
+    /// responses mirror the version of requests.
+    pub request_version: Version,
+
+    /// Handling of unknown message fields.
+    pub extensions: Mode,
+}
+
+impl Options {
+    /// Sets name of the metadata extension field.
+    #[inline]
+    pub fn with_metadata_field(mut self, name: &'static str) -> Self {
+        self.metadata_field = name;
+        self
+    }
+
+    /// Sets handling of unknown message fields.
+    #[inline]
+    pub fn with_extensions(mut self, mode: Mode) -> Self {
+        self.extensions = mode;
+        self
+    }
+
+    /// Sets version of requests and notifications sent by the codec.
+    #[inline]
+    pub fn with_request_version(mut self, version: Version) -> Self {
+        self.request_version = version;
+        self
+    }
+
+    /// Sets rejection of messages without the `jsonrpc` field.
+    #[inline]
+    pub fn with_strict_version(mut self, strict: bool) -> Self {
+        self.strict_version = strict;
+        self
+    }
+}
+
+impl Default for Options {
+    #[inline]
+    fn default() -> Self {
+        Options {
+            metadata_field: metadata::DEFAULT_FIELD,
+            strict_version: false,
+            request_version: Version::V2,
+            extensions: Mode::default(),
+        }
+    }
+}
+
+thread_local! {
+    /// Options in effect on this thread.
+    static CURRENT: Cell<Option<Options>> = const { Cell::new(None) };
+}
+
+/// Returns options in effect, defaults outside of a codec scope.
+#[inline]
+pub(crate) fn current() -> Options {
+    CURRENT.with(Cell::get).unwrap_or_default()
+}
+
+impl Scope for Options {
+    fn scope<T, F: FnOnce() -> T>(&self, f: F) -> T {
+        let _restore = Restore(CURRENT.with(|current| current.replace(Some(*self))));
+        f()
+    }
+}
+
+/// Restores previous options when dropped.
+struct Restore(Option<Options>);
+
+impl Drop for Restore {
+    fn drop(&mut self) {
+        let previous = self.0;
+        CURRENT.with(|current| current.set(previous));
+    }
+}
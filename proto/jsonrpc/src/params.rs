@@ -1,9 +1,104 @@
 //! JSON-RPC message parameters type.
 
-use std::str::FromStr;
+use std::{fmt, str::FromStr};
 
-/// Boxed raw JSON value.
-pub type RawValue = Box<serde_json::value::RawValue>;
+use bytes::Bytes;
+use serde::de::{Deserialize, Deserializer, Error as _};
+use serde::ser::{Error as _, Serialize, Serializer};
+use serde_json::value::RawValue as JsonRawValue;
+
+use net3_msg::frame;
+
+/// Raw JSON value backed by shared bytes.
+///
+/// Values are (de)serialized as [`serde_json::value::RawValue`]. When deserialized
+/// inside of a [`frame::scope`], which is always done by a borrowing deserializer
+/// such as [`serde_json::from_slice`], it references the decoded frame instead of
+/// copying the value. Other deserializers, e.g. [`serde_json::from_reader`] or
+/// [`serde_json::from_value`], produce an owned copy. Values buffered out of
+/// a frame, see [`frame::buffered`], are copied through a [`serde_json::Value`].
+///
+/// [`serde_json::value::RawValue`]: https://docs.rs/serde_json/1/serde_json/value/struct.RawValue.html
+/// [`frame::scope`]: ../../net3_msg/frame/fn.scope.html
+/// [`frame::buffered`]: ../../net3_msg/frame/fn.buffered.html
+/// [`serde_json::Value`]: https://docs.rs/serde_json/1/serde_json/enum.Value.html
+/// [`serde_json::from_slice`]: https://docs.rs/serde_json/1/serde_json/fn.from_slice.html
+/// [`serde_json::from_reader`]: https://docs.rs/serde_json/1/serde_json/fn.from_reader.html
+/// [`serde_json::from_value`]: https://docs.rs/serde_json/1/serde_json/fn.from_value.html
+#[derive(Clone)]
+pub struct RawValue {
+    bytes: Bytes,
+}
+
+impl RawValue {
+    /// Creates raw value from owned JSON string.
+    #[inline]
+    pub fn from_string(string: String) -> Result<Self, serde_json::Error> {
+        Ok(JsonRawValue::from_string(string)?.into())
+    }
+
+    /// Returns raw JSON string.
+    #[inline]
+    pub fn get(&self) -> &str {
+        // Values are only created from JSON strings.
+        std::str::from_utf8(&self.bytes).expect("raw value is not valid UTF-8")
+    }
+
+    /// Returns raw JSON bytes.
+    #[inline]
+    pub fn bytes(&self) -> &Bytes {
+        &self.bytes
+    }
+}
+
+impl From<Box<JsonRawValue>> for RawValue {
+    #[inline]
+    fn from(value: Box<JsonRawValue>) -> Self {
+        let value: Box<str> = value.into();
+        RawValue {
+            bytes: Bytes::from(String::from(value)),
+        }
+    }
+}
+
+impl fmt::Debug for RawValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("RawValue").field(&self.get()).finish()
+    }
+}
+
+impl Serialize for RawValue {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        // Borrowing the value as a `serde_json` raw value validates it without copying.
+        let value: &JsonRawValue = serde_json::from_str(self.get()).map_err(S::Error::custom)?;
+        value.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for RawValue {
+    fn deserialize<D>(deserializer: D) -> Result<RawValue, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        if frame::is_buffered() {
+            // Buffered values are not read by a JSON deserializer.
+            let value = serde_json::Value::deserialize(deserializer)?;
+            serde_json::value::to_raw_value(&value)
+                .map(RawValue::from)
+                .map_err(D::Error::custom)
+        } else if frame::is_set() {
+            let value = <&'de JsonRawValue>::deserialize(deserializer)?;
+            Ok(RawValue {
+                bytes: frame::slice(value.get().as_bytes()),
+            })
+        } else {
+            Box::<JsonRawValue>::deserialize(deserializer).map(RawValue::from)
+        }
+    }
+}
 
 /// Request parameters
 #[derive(Clone, Default, Debug, Deserialize, Serialize)]
@@ -16,14 +111,18 @@ impl Params {
     /// Creates `Params` by serializing value to a JSON string.
     #[inline]
     pub fn new<T: serde::ser::Serialize>(value: &T) -> Result<Self, serde_json::Error> {
-        Self::from_string(serde_json::to_string(value)?)
+        Ok(Params {
+            value: Some(RawValue {
+                bytes: Bytes::from(serde_json::to_vec(value)?),
+            }),
+        })
     }
 
     /// Creates `Params` from owned JSON string.
     #[inline]
     pub fn from_string(string: String) -> Result<Self, serde_json::Error> {
         Ok(Params {
-            value: Some(serde_json::value::RawValue::from_string(string)?),
+            value: Some(RawValue::from_string(string)?),
         })
     }
 
@@ -39,6 +138,15 @@ impl Params {
         self.value.is_some()
     }
 
+    /// Returns true if `value` is empty or a JSON `null`.
+    #[inline]
+    pub fn is_null(&self) -> bool {
+        match &self.value {
+            Some(value) => value.get() == "null",
+            None => true,
+        }
+    }
+
     /// Creates an empty params. Explicit alias to `Default::default()`.
     #[inline]
     pub fn empty() -> Self {
@@ -31,7 +31,7 @@ fn count_checks(msg: &Message) -> usize {
 
 fn parse_error() -> Error {
     Error {
-        code: ErrorCode(ErrorKind::ErrorCode(-32700)),
+        code: ErrorCode(ErrorKind::ParseError),
         message: "Parse error".to_owned(),
         data: None,
     }
@@ -200,4 +200,449 @@ fn handle_incorrect_responses() {
     let deserialized: Message = serde_json::from_str(dsr).unwrap();
     assert_eq!(count_checks(&deserialized), 0);
     assert_eq!(deserialized.kind(), MessageKind::Undefined);
+    assert_eq!(deserialized.validate(), Err(InvalidReason::ResultAndError));
+}
+
+#[test]
+fn owned_deserializers() {
+    let json = r#"{"jsonrpc":"2.0","id":1,"method":"echo","params":[1,{"a":"b"}]}"#;
+    let params = r#"[1,{"a":"b"}]"#;
+
+    let message: Message = serde_json::from_reader(json.as_bytes()).unwrap();
+    assert_eq!(message.params.value.as_ref().unwrap().get(), params);
+
+    let value: serde_json::Value = serde_json::from_str(json).unwrap();
+    let message: Message = serde_json::from_value(value).unwrap();
+    assert_eq!(message.params.value.as_ref().unwrap().get(), params);
+
+    let serialized = serde_json::to_string(&message.params).unwrap();
+    assert_eq!(serialized, params);
+    let params: Params = serde_json::from_reader(serialized.as_bytes()).unwrap();
+    assert_eq!(
+        params,
+        Params::new(&(1, serde_json::json!({"a": "b"}))).unwrap()
+    );
+}
+
+#[test]
+fn metadata_serialize() {
+    let msg = Message::new_request(Id::Num(1), "test").with_meta("trace", "abc");
+    assert_eq!(msg.meta("trace"), Some("abc"));
+
+    let serialized = serde_json::to_string(&msg).unwrap();
+    assert_eq!(
+        serialized,
+        r#"{"jsonrpc":"2.0","id":1,"method":"test","meta":{"trace":"abc"}}"#
+    );
+}
+
+#[test]
+fn metadata_field_option() {
+    use net3_msg::scope::Scope;
+
+    let options = Options::default().with_metadata_field("_meta");
+    let msg = Message::new_request(Id::Num(1), "test").with_meta("trace", "abc");
+    let serialized = options.scope(|| serde_json::to_string(&msg)).unwrap();
+    assert_eq!(
+        serialized,
+        r#"{"jsonrpc":"2.0","id":1,"method":"test","_meta":{"trace":"abc"}}"#
+    );
+    let deserialized: Message = options.scope(|| serde_json::from_str(&serialized)).unwrap();
+    assert_eq!(deserialized.meta("trace"), Some("abc"));
+    // Other codecs keep the default field name.
+    assert!(serde_json::from_str::<Message>(&serialized).is_err());
+}
+
+#[test]
+fn metadata_deserialize() {
+    let dsr = r#"{"jsonrpc":"2.0","id":1,"method":"test","meta":{"tenant":"a"}}"#;
+
+    let deserialized: Message = serde_json::from_str(dsr).unwrap();
+    assert_eq!(deserialized.kind(), MessageKind::Request);
+    assert_eq!(deserialized.meta("tenant"), Some("a"));
+
+    let unknown = r#"{"jsonrpc":"2.0","id":1,"method":"test","other":{}}"#;
+    assert!(serde_json::from_str::<Message>(unknown).is_err());
+    let duplicate = r#"{"jsonrpc":"2.0","id":1,"id":2}"#;
+    assert!(serde_json::from_str::<Message>(duplicate).is_err());
+}
+
+#[test]
+fn error_data_round_trip() {
+    let request = Message::new_request(Id::Num(1), "test");
+    let error = types::Error::new(ErrorKind::ServerError(-32000), Some("retry".to_owned()))
+        .with_data(&vec![("retry_after", 5)])
+        .unwrap();
+    let msg = Message::new_error_response(&request, error.clone());
+
+    let serialized = serde_json::to_string(&msg).unwrap();
+    assert_eq!(
+        serialized,
+        r#"{"jsonrpc":"2.0","id":1,"error":{"code":-32000,"message":"retry","data":[["retry_after",5]]}}"#
+    );
+
+    let deserialized: Message = serde_json::from_str(&serialized).unwrap();
+    let received = deserialized.into_error().unwrap();
+    assert_eq!(
+        received.read_data::<Vec<(String, u64)>>().unwrap(),
+        Some(vec![("retry_after".to_owned(), 5)])
+    );
+}
+
+#[test]
+fn error_code_mapping() {
+    let codes = [
+        (-32700, ErrorKind::ParseError),
+        (-32600, ErrorKind::InvalidRequest),
+        (-32601, ErrorKind::MethodNotFound),
+        (-32602, ErrorKind::InvalidParams),
+        (-32603, ErrorKind::InternalError),
+        (-32000, ErrorKind::ServerError(-32000)),
+        (-32099, ErrorKind::ServerError(-32099)),
+        (-32100, ErrorKind::ErrorCode(-32100)),
+        (1, ErrorKind::ErrorCode(1)),
+    ];
+    for (code, kind) in codes.iter() {
+        let error: ErrorCode = serde_json::from_str(&code.to_string()).unwrap();
+        assert_eq!(error, ErrorCode(*kind));
+        assert_eq!(serde_json::to_string(&error).unwrap(), code.to_string());
+    }
+}
+
+#[test]
+fn validate_messages() {
+    let cases = [
+        (r#"{"jsonrpc":"2.0","id":1,"method":"test"}"#, Ok(())),
+        (r#"{"jsonrpc":"2.0","method":"test"}"#, Ok(())),
+        (r#"{"jsonrpc":"2.0","id":1,"result":1}"#, Ok(())),
+        (r#"{"jsonrpc":"2.0"}"#, Err(InvalidReason::MissingMethod)),
+        (
+            r#"{"jsonrpc":"2.0","result":1}"#,
+            Err(InvalidReason::MissingId),
+        ),
+        (
+            r#"{"jsonrpc":"2.0","id":1,"result":1,"error":{"code":-32000,"message":""}}"#,
+            Err(InvalidReason::ResultAndError),
+        ),
+        (
+            r#"{"jsonrpc":"1.5","id":1,"method":"test"}"#,
+            Err(InvalidReason::UnsupportedVersion),
+        ),
+    ];
+    for (message, expected) in cases.iter() {
+        let message: Message = serde_json::from_str(message).unwrap();
+        assert_eq!(message.validate(), *expected, "{:?}", message);
+    }
+}
+
+#[test]
+fn invalid_request_response() {
+    let request: Message = serde_json::from_str(r#"{"jsonrpc":"2.0","method":""}"#).unwrap();
+    let reason = request.validate().unwrap_err();
+    assert_eq!(reason, InvalidReason::MissingMethod);
+    let response = Message::new_error_response(&request, reason.into());
+    assert_eq!(
+        serde_json::to_string(&response).unwrap(),
+        r#"{"jsonrpc":"2.0","id":null,"error":{"code":-32600,"message":"missing method name"}}"#
+    );
+
+    let request: Message =
+        serde_json::from_str(r#"{"jsonrpc":"1.5","id":1,"method":"test"}"#).unwrap();
+    let reason = request.validate().unwrap_err();
+    let response = Message::new_error_response(&request, reason.into());
+    assert_eq!(
+        serde_json::to_string(&response).unwrap(),
+        r#"{"jsonrpc":"2.0","id":1,"error":{"code":-32600,"message":"unsupported protocol version"}}"#
+    );
+}
+
+#[test]
+fn notification_kind() {
+    let msg: Message =
+        serde_json::from_str(r#"{"jsonrpc":"2.0","method":"update","params":[1]}"#).unwrap();
+    assert_eq!(msg.kind(), MessageKind::Event);
+    assert!(is_notification(&msg));
+    let msg: Message = serde_json::from_str(r#"{"jsonrpc":"2.0","method":"ping"}"#).unwrap();
+    assert_eq!(msg.kind(), MessageKind::Event);
+    let msg: Message = serde_json::from_str(r#"{"jsonrpc":"2.0","params":[1]}"#).unwrap();
+    assert_eq!(msg.kind(), MessageKind::Undefined);
+    let msg: Message = serde_json::from_str(r#"{"jsonrpc":"2.0","method":""}"#).unwrap();
+    assert_eq!(msg.kind(), MessageKind::Undefined);
+}
+
+#[test]
+fn convert_to_compact() {
+    use net3_msg::{compact, convert::convert};
+
+    let request = Message::new_request(Id::Num(1), "add")
+        .with_data(&(1, 2))
+        .unwrap()
+        .with_meta("trace", "abc");
+    let converted = convert::<_, compact::Message>(&request).unwrap();
+    assert!(converted.is_lossless(), "{:?}", converted.losses);
+    let compact = converted.message;
+    assert_eq!(compact.kind(), MessageKind::Request);
+    assert_eq!(compact.method(), Some("add"));
+    assert_eq!(compact.read::<(u8, u8)>().unwrap(), (1, 2));
+    assert_eq!(compact.meta("trace"), Some("abc"));
+
+    let back = convert::<_, Message>(&compact).unwrap().lossless().unwrap();
+    assert_eq!(
+        serde_json::to_string(&back).unwrap(),
+        serde_json::to_string(&request).unwrap()
+    );
+
+    let event = Message::new_event("update").with_data(&"tick").unwrap();
+    let converted = convert::<_, compact::Message>(&event).unwrap();
+    assert!(converted.is_lossless(), "{:?}", converted.losses);
+    assert_eq!(converted.message.kind(), MessageKind::Event);
+
+    let error = types::Error::new(ErrorKind::InvalidParams, Some("bad".to_owned()))
+        .with_data(&[1, 2])
+        .unwrap();
+    let response = Message::new_error_response(&request, error.clone());
+    let compact = convert::<_, compact::Message>(&response)
+        .unwrap()
+        .lossless()
+        .unwrap();
+    assert_eq!(compact.kind(), MessageKind::ErrorResponse);
+    assert_eq!(compact.id(), &Id::Num(1));
+    let converted = compact.into_error().unwrap();
+    assert_eq!(converted.kind, error.kind);
+    assert_eq!(converted.description, error.description);
+    assert_eq!(converted.read_data::<Vec<u8>>().unwrap(), Some(vec![1, 2]));
+}
+
+#[test]
+fn convert_reports_losses() {
+    use net3_msg::{compact, convert::convert, convert::Loss};
+
+    // Binary payloads have no JSON representation.
+    let compact = compact::Message::new_request(Id::Num(1), "store")
+        .with_data(&bytes::Bytes::from_static(b"\x00\x01"))
+        .unwrap()
+        .build();
+    let converted = convert::<_, Message>(&compact).unwrap();
+    assert_eq!(converted.losses, vec![Loss::Payload]);
+    assert!(converted.lossless().is_err());
+
+    // JSON-RPC notifications do not carry an identifier.
+    let event = compact::Message::new_event("update")
+        .with_id(Id::Num(2))
+        .build();
+    let converted = convert::<_, Message>(&event).unwrap();
+    assert_eq!(converted.losses, vec![Loss::Id]);
+    assert_eq!(converted.message.kind(), MessageKind::Event);
+}
+
+#[test]
+fn v1_messages_round_trip() {
+    let cases = [
+        (
+            r#"{"id":1,"method":"mining.subscribe","params":[]}"#,
+            MessageKind::Request,
+        ),
+        (
+            r#"{"id":null,"method":"mining.notify","params":[1]}"#,
+            MessageKind::Event,
+        ),
+        (
+            r#"{"id":1,"result":true,"error":null}"#,
+            MessageKind::Response,
+        ),
+        (
+            r#"{"id":1,"result":null,"error":null}"#,
+            MessageKind::Response,
+        ),
+        (
+            r#"{"id":1,"result":null,"error":{"code":-32601,"message":"Method not found"}}"#,
+            MessageKind::ErrorResponse,
+        ),
+    ];
+    for (body, kind) in cases.iter() {
+        let message: Message = serde_json::from_str(body).unwrap();
+        assert_eq!(message.version, Version::V1);
+        assert_eq!(message.kind(), *kind, "{}", body);
+        assert_eq!(message.validate(), Ok(()), "{}", body);
+        assert_eq!(serde_json::to_string(&message).unwrap(), *body);
+    }
+}
+
+#[test]
+fn v1_messages_serialize() {
+    let request = builder::new_request::<Message, ()>(Id::Num(1), "mining.authorize", None)
+        .unwrap()
+        .build()
+        .with_version(Version::V1);
+    assert_eq!(
+        serde_json::to_string(&request).unwrap(),
+        r#"{"id":1,"method":"mining.authorize","params":[]}"#
+    );
+    let event = builder::new_event::<Message, _>("mining.notify", Some(&[1]))
+        .unwrap()
+        .build()
+        .with_version(Version::V1);
+    assert_eq!(
+        serde_json::to_string(&event).unwrap(),
+        r#"{"id":null,"method":"mining.notify","params":[1]}"#
+    );
+}
+
+#[test]
+fn v1_tagged_messages_round_trip() {
+    let body = r#"{"jsonrpc":"1.0","id":1,"method":"mining.subscribe","params":[]}"#;
+    let request: Message = serde_json::from_str(body).unwrap();
+    assert_eq!(request.version, Version::V1Tagged);
+    assert_eq!(serde_json::to_string(&request).unwrap(), body);
+    let response = builder::new_response(&request)
+        .with_data(&true)
+        .unwrap()
+        .build();
+    assert_eq!(
+        serde_json::to_string(&response).unwrap(),
+        r#"{"jsonrpc":"1.0","id":1,"result":true,"error":null}"#
+    );
+}
+
+#[test]
+fn strict_version_option() {
+    use net3_msg::scope::Scope;
+
+    let options = Options::default().with_strict_version(true);
+    let read = |body: &str| {
+        options
+            .scope(|| serde_json::from_str::<Message>(body))
+            .unwrap()
+    };
+    let message = read(r#"{"id":1,"method":"test","params":[]}"#);
+    assert_eq!(message.validate(), Err(InvalidReason::UnsupportedVersion));
+    let message = read(r#"{"jsonrpc":"3.0","id":1,"method":"test"}"#);
+    assert_eq!(message.validate(), Err(InvalidReason::UnsupportedVersion));
+    let message = read(r#"{"jsonrpc":"1.0","id":1,"method":"test","params":[]}"#);
+    assert_eq!(message.validate(), Ok(()));
+    let message = read(r#"{"jsonrpc":"2.0","id":1,"method":"test"}"#);
+    assert_eq!(message.validate(), Ok(()));
+}
+
+#[test]
+fn request_version_option() {
+    use net3_msg::scope::Scope;
+
+    let options = Options::default().with_request_version(Version::V1);
+    let request = builder::new_request::<Message, _>(Id::Num(1), "test", Some(&[1]))
+        .unwrap()
+        .build();
+    assert_eq!(
+        options.scope(|| serde_json::to_string(&request)).unwrap(),
+        r#"{"id":1,"method":"test","params":[1]}"#
+    );
+    let response = builder::new_response(&request)
+        .with_data(&true)
+        .unwrap()
+        .build();
+    assert_eq!(
+        options.scope(|| serde_json::to_string(&response)).unwrap(),
+        r#"{"jsonrpc":"2.0","id":1,"result":true}"#
+    );
+}
+
+#[test]
+fn responses_mirror_request_version() {
+    let request: Message = serde_json::from_str(r#"{"id":1,"method":"test"}"#).unwrap();
+    let response = builder::new_response(&request)
+        .with_data(&true)
+        .unwrap()
+        .build();
+    assert_eq!(
+        serde_json::to_string(&response).unwrap(),
+        r#"{"id":1,"result":true,"error":null}"#
+    );
+    let error = builder::new_error_response(&request, ErrorKind::MethodNotFound.into()).build();
+    assert_eq!(
+        serde_json::to_string(&error).unwrap(),
+        r#"{"id":1,"result":null,"error":{"code":-32601,"message":"method not found"}}"#
+    );
+
+    let request: Message =
+        serde_json::from_str(r#"{"jsonrpc":"2.0","id":1,"method":"test"}"#).unwrap();
+    let response = builder::new_response(&request)
+        .with_data(&true)
+        .unwrap()
+        .build();
+    assert_eq!(
+        serde_json::to_string(&response).unwrap(),
+        r#"{"jsonrpc":"2.0","id":1,"result":true}"#
+    );
+}
+
+#[test]
+fn null_result_response() {
+    let body = r#"{"jsonrpc":"2.0","id":1,"result":null}"#;
+    let message: Message = serde_json::from_str(body).unwrap();
+    assert_eq!(message.kind(), MessageKind::Response);
+    assert_eq!(message.read_optional::<u32>().unwrap(), None);
+    assert_eq!(serde_json::to_string(&message).unwrap(), body);
+}
+
+#[test]
+fn array_error_response() {
+    let body = r#"{"id":4,"result":null,"error":[21,"Job not found",null]}"#;
+    let message: Message = serde_json::from_str(body).unwrap();
+    assert_eq!(message.kind(), MessageKind::ErrorResponse);
+    assert_eq!(message.error_kind(), Some(&ErrorKind::ErrorCode(21)));
+
+    let body = r#"{"id":4,"result":null,"error":[23,null]}"#;
+    let message: Message = serde_json::from_str(body).unwrap();
+    assert_eq!(message.error_kind(), Some(&ErrorKind::ErrorCode(23)));
+}
+
+#[test]
+fn extension_fields() {
+    use net3_msg::scope::Scope;
+
+    let options = Options::default().with_extensions(extensions::Mode::Extensions);
+    let read = |body: &str| options.scope(|| serde_json::from_str::<Message>(body));
+    let body =
+        r#"{"jsonrpc":"2.0","id":1,"method":"test","worker":"rig1","algo":{"name":"sha256d"}}"#;
+    let message = read(body);
+    let error =
+        read(r#"{"id":1,"result":null,"error":{"code":21,"message":"stale","traceback":null}}"#);
+    let duplicate = read(r#"{"jsonrpc":"2.0","id":1,"method":"test","worker":"a","worker":"b"}"#);
+
+    let message = message.unwrap();
+    assert_eq!(message.kind(), MessageKind::Request);
+    assert_eq!(
+        message.extension("worker"),
+        Some(&"\"rig1\"".parse().unwrap())
+    );
+    assert_eq!(
+        message.read_extension::<String>("worker").unwrap(),
+        Some("rig1".to_owned())
+    );
+    assert_eq!(message.read_extension::<String>("other").unwrap(), None);
+    assert_eq!(
+        serde_json::to_string(&message).unwrap(),
+        r#"{"jsonrpc":"2.0","id":1,"method":"test","algo":{"name":"sha256d"},"worker":"rig1"}"#
+    );
+    assert_eq!(error.unwrap().error_kind(), Some(&ErrorKind::ErrorCode(21)));
+    assert!(duplicate.is_err());
+
+    assert!(serde_json::from_str::<Message>(body).is_err());
+}
+
+#[test]
+fn extension_fields_serialize() {
+    let mut message = Message::new_request(Id::Num(1), "test");
+    message.set_extension("worker", &"rig1").unwrap();
+    assert_eq!(
+        serde_json::to_string(&message).unwrap(),
+        r#"{"jsonrpc":"2.0","id":1,"method":"test","worker":"rig1"}"#
+    );
+    for name in &[
+        "jsonrpc", "id", "method", "params", "result", "error", "meta",
+    ] {
+        let err = message.set_extension(name, &1).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput, "{}", name);
+    }
+    assert_eq!(message.extensions.len(), 1);
 }
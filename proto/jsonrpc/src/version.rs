@@ -1,6 +1,7 @@
 //! JSON-RPC message version type.
 
 use serde::de::{self, Visitor};
+use serde::ser;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 use std::fmt;
@@ -8,8 +9,35 @@ use std::fmt;
 /// JSON-RPC message version type.
 #[derive(Debug, PartialEq, Clone, Copy, Hash, Eq)]
 pub enum Version {
+    /// JSON-RPC 1.0
+    ///
+    /// Messages without the `jsonrpc` field, as sent by legacy and stratum peers.
+    /// Responses carry both `result` and `error` fields, one of them `null`.
+    V1,
+
+    /// JSON-RPC 1.0 with the `"jsonrpc": "1.0"` field.
+    ///
+    /// Same dialect as [`Version::V1`], the field is kept when the message is serialized.
+    ///
+    /// [`Version::V1`]: enum.Version.html#variant.V1
+    V1Tagged,
+
     /// JSON-RPC 2.0
     V2,
+
+    /// Unsupported version received from a peer.
+    ///
+    /// It is kept so the message can be rejected by validation
+    /// instead of failing to decode. It can not be serialized.
+    Unsupported,
+}
+
+impl Version {
+    /// Returns true if it is a JSON-RPC 1.0 dialect.
+    #[inline]
+    pub fn is_v1(&self) -> bool {
+        matches!(self, Version::V1 | Version::V1Tagged)
+    }
 }
 
 impl Default for Version {
@@ -25,7 +53,9 @@ impl Serialize for Version {
         S: Serializer,
     {
         match *self {
+            Version::V1 | Version::V1Tagged => serializer.serialize_str("1.0"),
             Version::V2 => serializer.serialize_str("2.0"),
+            Version::Unsupported => Err(ser::Error::custom("unsupported version")),
         }
     }
 }
@@ -53,8 +83,9 @@ impl<'a> Visitor<'a> for VersionVisitor {
         E: de::Error,
     {
         match value {
+            "1.0" => Ok(Version::V1Tagged),
             "2.0" => Ok(Version::V2),
-            _ => Err(de::Error::custom("invalid version")),
+            _ => Ok(Version::Unsupported),
         }
     }
 }
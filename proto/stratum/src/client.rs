@@ -0,0 +1,53 @@
+//! Stratum client.
+//!
+//! Client sends requests with [`Stratum`] methods of its handle
+//! and receives pool [`Notification`]s.
+//!
+//! Requests are sent in JSON-RPC 1.0 dialect, see [`Dialect`].
+//! Responses of pools echoing the request `method` are accepted,
+//! as well as errors in a form of `[code, message, traceback]` array.
+//!
+//! [`Stratum`]: ../server/trait.Stratum.html
+//! [`Notification`]: ../notification/enum.Notification.html
+//! [`Dialect`]: struct.Dialect.html
+
+use net3_msg::scope::Scope;
+use net3_proto_jsonrpc::{Message, Options, Version};
+use net3_rpc_client::{common::CloneBuilder, NotificationHandler, Notifications};
+
+use crate::notification::Notification;
+
+/// Stratum codec options.
+///
+/// Requests and notifications are sent as JSON-RPC 1.0 messages,
+/// responses mirror the version of requests.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Dialect;
+
+impl Scope for Dialect {
+    fn scope<T, F: FnOnce() -> T>(&self, f: F) -> T {
+        Options::default()
+            .with_request_version(Version::V1)
+            .scope(f)
+    }
+}
+
+/// Stratum JSON lines codec.
+pub type Codec = net3_codec_json_lines::Codec<Message, Dialect>;
+
+/// Stratum client builder delivering pool notifications.
+pub type ClientBuilder =
+    net3_rpc_client::ClientBuilder<Codec, CloneBuilder<NotificationHandler<Message, Notification>>>;
+
+/// Stratum client handle with a receiver of pool notifications.
+pub type Client = Notifications<Message, Notification>;
+
+/// Connects to a pool in background, reconnecting on failure.
+///
+/// Connection is closed when all client handles are dropped.
+pub fn connect(addr: &str) -> Client {
+    let mut builder = ClientBuilder::new().with_reconnect(addr);
+    let client = builder.notifications();
+    builder.spawn();
+    client
+}
@@ -0,0 +1,29 @@
+//! Stratum error codes.
+//!
+//! Stratum errors are sent as `[code, message, traceback]` arrays by most pools,
+//! they are read as regular JSON-RPC errors.
+
+use net3_msg::types::{Error, ErrorKind};
+
+/// Other or unknown error.
+pub const OTHER: i64 = 20;
+
+/// Job not found, the share is stale.
+pub const JOB_NOT_FOUND: i64 = 21;
+
+/// Duplicate share.
+pub const DUPLICATE_SHARE: i64 = 22;
+
+/// Share difficulty is too low.
+pub const LOW_DIFFICULTY: i64 = 23;
+
+/// Worker is not authorized.
+pub const UNAUTHORIZED: i64 = 24;
+
+/// Connection is not subscribed.
+pub const NOT_SUBSCRIBED: i64 = 25;
+
+/// Creates a stratum error with a code and a message.
+pub fn error(code: i64, message: &str) -> Error {
+    Error::new(ErrorKind::ErrorCode(code), Some(message.to_owned()))
+}
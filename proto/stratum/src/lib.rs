@@ -0,0 +1,54 @@
+//! Stratum V1 mining protocol.
+//!
+//! Stratum is a JSON-RPC 1.0 dialect, messages are [`Message`]s of [`net3_proto_jsonrpc`]
+//! exchanged over a JSON lines channel.
+//!
+//! Miner requests are answered by a [`Stratum`] implementation wrapped in a [`StratumHandler`].
+//! Pool notifications are typed as [`Notification`]s and received by a [`Client`].
+//!
+//! [`Message`]: ../net3_proto_jsonrpc/message/struct.Message.html
+//! [`net3_proto_jsonrpc`]: ../net3_proto_jsonrpc/index.html
+//! [`Stratum`]: server/trait.Stratum.html
+//! [`StratumHandler`]: server/struct.StratumHandler.html
+//! [`Notification`]: notification/enum.Notification.html
+//! [`Client`]: client/type.Client.html
+
+pub mod client;
+pub mod error;
+pub mod notification;
+pub mod params;
+pub mod server;
+
+pub use self::client::{connect, Client};
+pub use self::notification::Notification;
+pub use self::params::*;
+pub use self::server::{Stratum, StratumHandler};
+
+pub use net3_proto_jsonrpc::Message;
+
+/// Stratum method names.
+pub mod method {
+    /// Subscribes to mining jobs.
+    pub const SUBSCRIBE: &str = "mining.subscribe";
+
+    /// Authorizes a worker.
+    pub const AUTHORIZE: &str = "mining.authorize";
+
+    /// Submits a share.
+    pub const SUBMIT: &str = "mining.submit";
+
+    /// Subscribes to extranonce changes.
+    pub const EXTRANONCE_SUBSCRIBE: &str = "mining.extranonce.subscribe";
+
+    /// Sets share difficulty.
+    pub const SET_DIFFICULTY: &str = "mining.set_difficulty";
+
+    /// Sets extranonce of the connection.
+    pub const SET_EXTRANONCE: &str = "mining.set_extranonce";
+
+    /// Notifies about a new mining job.
+    pub const NOTIFY: &str = "mining.notify";
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,80 @@
+//! Stratum notifications sent by a pool.
+
+use std::io::Result;
+
+use net3_msg::prelude::*;
+use net3_proto_jsonrpc::{Message, Version};
+use net3_rpc_client::Handle;
+
+use crate::{
+    method,
+    params::{NotifyParams, SetDifficultyParams, SetExtranonceParams},
+};
+
+/// Stratum notification.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Notification {
+    /// `mining.set_difficulty` notification.
+    SetDifficulty(SetDifficultyParams),
+
+    /// `mining.set_extranonce` notification.
+    SetExtranonce(SetExtranonceParams),
+
+    /// `mining.notify` notification.
+    Notify(NotifyParams),
+
+    /// Notification of another method or with invalid parameters.
+    Other(Message),
+}
+
+impl Notification {
+    /// Returns method name of the notification.
+    pub fn method(&self) -> Option<&str> {
+        match self {
+            Notification::SetDifficulty(_) => Some(method::SET_DIFFICULTY),
+            Notification::SetExtranonce(_) => Some(method::SET_EXTRANONCE),
+            Notification::Notify(_) => Some(method::NOTIFY),
+            Notification::Other(message) => message.method(),
+        }
+    }
+
+    /// Creates a JSON-RPC 1.0 notification message.
+    pub fn to_message(&self) -> Result<Message> {
+        let builder = match self {
+            Notification::SetDifficulty(params) => {
+                builder::new_event::<Message, _>(method::SET_DIFFICULTY, Some(params))?
+            }
+            Notification::SetExtranonce(params) => {
+                builder::new_event::<Message, _>(method::SET_EXTRANONCE, Some(params))?
+            }
+            Notification::Notify(params) => {
+                builder::new_event::<Message, _>(method::NOTIFY, Some(params))?
+            }
+            Notification::Other(message) => return Ok(message.clone()),
+        };
+        Ok(builder.build().with_version(Version::V1))
+    }
+
+    /// Sends the notification to a network channel.
+    pub fn send<U>(&self, handle: &Handle<Message, U>) -> Result<()> {
+        handle.send(self.to_message()?)
+    }
+}
+
+/// Reads a typed notification, falls back to [`Notification::Other`].
+///
+/// [`Notification::Other`]: enum.Notification.html#variant.Other
+impl From<Message> for Notification {
+    fn from(message: Message) -> Self {
+        let notification = match message.method() {
+            Some(method::SET_DIFFICULTY) => message.read().map(Notification::SetDifficulty),
+            Some(method::SET_EXTRANONCE) => message.read().map(Notification::SetExtranonce),
+            Some(method::NOTIFY) => message.read().map(Notification::Notify),
+            _ => return Notification::Other(message),
+        };
+        notification.unwrap_or_else(|err| {
+            log::debug!("Invalid stratum notification parameters: {}", err);
+            Notification::Other(message)
+        })
+    }
+}
@@ -0,0 +1,202 @@
+//! Stratum message parameters and results.
+//!
+//! Stratum parameters are positional, they are serialized as JSON arrays.
+//! Optional trailing parameters may be omitted and unknown trailing parameters are ignored.
+
+use std::fmt;
+
+use serde::{
+    de::{Deserialize, Deserializer, Error as _, IgnoredAny, SeqAccess, Visitor},
+    ser::{Serialize, SerializeSeq, Serializer},
+};
+
+/// Defines a structure serialized as an array of its fields.
+///
+/// Fields listed in the `optional` block are optional trailing array elements.
+macro_rules! positional {
+    (
+        $(#[$meta:meta])*
+        pub struct $name:ident {
+            $(
+                $(#[$field_meta:meta])*
+                pub $field:ident: $ty:ty,
+            )*
+        }
+        $(optional {
+            $(
+                $(#[$opt_meta:meta])*
+                pub $opt:ident: $opt_ty:ty,
+            )*
+        })?
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, PartialEq)]
+        pub struct $name {
+            $(
+                $(#[$field_meta])*
+                pub $field: $ty,
+            )*
+            $($(
+                $(#[$opt_meta])*
+                pub $opt: Option<$opt_ty>,
+            )*)?
+        }
+
+        impl Serialize for $name {
+            #[allow(unused_mut, unused_variables, unused_assignments)]
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                // Optional parameters are serialized up to the last one present.
+                let present: &[bool] = &[$($(self.$opt.is_some(),)*)?];
+                let optional = present.iter().rposition(|present| *present).map_or(0, |i| i + 1);
+                let required: &[&str] = &[$(stringify!($field),)*];
+                let mut seq = serializer.serialize_seq(Some(required.len() + optional))?;
+                $(seq.serialize_element(&self.$field)?;)*
+                let mut index = 0;
+                $($(
+                    if index < optional {
+                        seq.serialize_element(&self.$opt)?;
+                    }
+                    index += 1;
+                )*)?
+                seq.end()
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                struct ParamsVisitor;
+
+                impl<'de> Visitor<'de> for ParamsVisitor {
+                    type Value = $name;
+
+                    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                        formatter.write_str(concat!(stringify!($name), " array"))
+                    }
+
+                    #[allow(unused_mut, unused_variables, unused_assignments)]
+                    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<$name, A::Error> {
+                        let mut index = 0;
+                        $(
+                            let $field = seq
+                                .next_element()?
+                                .ok_or_else(|| A::Error::invalid_length(index, &self))?;
+                            index += 1;
+                        )*
+                        $($(
+                            let $opt = seq.next_element::<Option<$opt_ty>>()?.flatten();
+                        )*)?
+                        while seq.next_element::<IgnoredAny>()?.is_some() {}
+                        Ok($name {
+                            $($field,)*
+                            $($($opt,)*)?
+                        })
+                    }
+                }
+
+                deserializer.deserialize_seq(ParamsVisitor)
+            }
+        }
+    };
+}
+
+positional! {
+    /// Parameters of a `mining.subscribe` request.
+    pub struct SubscribeParams {}
+    optional {
+        /// Miner software name and version.
+        pub user_agent: String,
+        /// Session identifier to resume a previous subscription.
+        pub session_id: String,
+    }
+}
+
+positional! {
+    /// Result of a `mining.subscribe` request.
+    pub struct SubscribeResult {
+        /// Subscribed notification names with subscription identifiers.
+        pub subscriptions: Vec<(String, String)>,
+        /// Hex-encoded extranonce prefix of the connection.
+        pub extranonce1: String,
+        /// Size of the extranonce part rolled by the miner in bytes.
+        pub extranonce2_size: usize,
+    }
+}
+
+positional! {
+    /// Parameters of a `mining.authorize` request.
+    pub struct AuthorizeParams {
+        /// Worker name.
+        pub username: String,
+    }
+    optional {
+        /// Worker password.
+        pub password: String,
+    }
+}
+
+positional! {
+    /// Parameters of a `mining.submit` request.
+    pub struct SubmitParams {
+        /// Worker name.
+        pub username: String,
+        /// Identifier of the job.
+        pub job_id: String,
+        /// Hex-encoded extranonce part rolled by the miner.
+        pub extranonce2: String,
+        /// Hex-encoded block time.
+        pub ntime: String,
+        /// Hex-encoded block nonce.
+        pub nonce: String,
+    }
+    optional {
+        /// Hex-encoded rolled block version bits (BIP 310).
+        pub version_bits: String,
+    }
+}
+
+positional! {
+    /// Parameters of a `mining.extranonce.subscribe` request.
+    pub struct ExtranonceSubscribeParams {}
+}
+
+positional! {
+    /// Parameters of a `mining.set_difficulty` notification.
+    pub struct SetDifficultyParams {
+        /// Share difficulty of the following jobs.
+        pub difficulty: f64,
+    }
+}
+
+positional! {
+    /// Parameters of a `mining.set_extranonce` notification.
+    pub struct SetExtranonceParams {
+        /// Hex-encoded extranonce prefix of the connection.
+        pub extranonce1: String,
+        /// Size of the extranonce part rolled by the miner in bytes.
+        pub extranonce2_size: usize,
+    }
+}
+
+positional! {
+    /// Parameters of a `mining.notify` notification.
+    pub struct NotifyParams {
+        /// Identifier of the job.
+        pub job_id: String,
+        /// Hex-encoded hash of the previous block.
+        pub prev_hash: String,
+        /// Hex-encoded coinbase transaction part preceding the extranonce.
+        pub coinbase1: String,
+        /// Hex-encoded coinbase transaction part following the extranonce.
+        pub coinbase2: String,
+        /// Hex-encoded merkle branch hashes of the coinbase transaction.
+        pub merkle_branch: Vec<String>,
+        /// Hex-encoded block version.
+        pub version: String,
+        /// Hex-encoded compact network difficulty target.
+        pub nbits: String,
+        /// Hex-encoded block time.
+        pub ntime: String,
+        /// True if previous jobs should be abandoned.
+        pub clean_jobs: bool,
+    }
+}
@@ -0,0 +1,63 @@
+//! Stratum server interface.
+
+use std::io::Result;
+
+use async_trait::async_trait;
+
+use net3_proto_jsonrpc::Message;
+use net3_rpc_client::Handler;
+use net3_rpc_derive::rpc;
+use net3_rpc_error::Result as RpcResult;
+
+use crate::params::*;
+
+/// Stratum requests sent by a miner.
+///
+/// Implemented for client [`Handle`] sending the requests to a pool.
+///
+/// [`Handle`]: ../../net3_rpc_client/handle/struct.Handle.html
+#[rpc]
+pub trait Stratum {
+    /// Subscribes to mining jobs.
+    #[rpc(name = "mining.subscribe")]
+    async fn subscribe(&mut self, params: &SubscribeParams) -> RpcResult<SubscribeResult>;
+
+    /// Authorizes a worker.
+    #[rpc(name = "mining.authorize")]
+    async fn authorize(&mut self, params: &AuthorizeParams) -> RpcResult<bool>;
+
+    /// Submits a share.
+    #[rpc(name = "mining.submit")]
+    async fn submit(&mut self, params: &SubmitParams) -> RpcResult<bool>;
+
+    /// Subscribes to extranonce changes.
+    #[rpc(name = "mining.extranonce.subscribe")]
+    async fn extranonce_subscribe(&mut self, params: &ExtranonceSubscribeParams)
+        -> RpcResult<bool>;
+}
+
+/// Connection handler answering miner requests with a [`Stratum`] implementation.
+///
+/// Responses are sent in the dialect of the request.
+/// Handler is forked by cloning to handle requests concurrently.
+///
+/// [`Stratum`]: trait.Stratum.html
+#[derive(Debug, Clone)]
+pub struct StratumHandler<S>(pub S);
+
+#[async_trait]
+impl<S> Handler for StratumHandler<S>
+where
+    S: Stratum + Clone + Send + Sync,
+{
+    type Event = ();
+    type Message = Message;
+
+    async fn handle_request(&mut self, message: Message) -> Result<Vec<Message>> {
+        Ok(vec![self.0.handle_message(message).await?])
+    }
+
+    fn fork(&self) -> Option<Self> {
+        Some(self.clone())
+    }
+}
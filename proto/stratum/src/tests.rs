@@ -0,0 +1,269 @@
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    net::{TcpListener, TcpStream},
+    sync::mpsc::{unbounded_channel, UnboundedReceiver},
+};
+
+use net3_msg::prelude::*;
+use net3_rpc_error::Result as RpcResult;
+
+use crate::*;
+
+#[test]
+fn params_serialize() {
+    let params = SubscribeParams {
+        user_agent: Some("miner/1.0".to_owned()),
+        session_id: None,
+    };
+    assert_eq!(serde_json::to_value(&params).unwrap(), json!(["miner/1.0"]));
+    let params = SubscribeParams {
+        user_agent: None,
+        session_id: Some("abc".to_owned()),
+    };
+    assert_eq!(serde_json::to_value(&params).unwrap(), json!([null, "abc"]));
+    let params = ExtranonceSubscribeParams {};
+    assert_eq!(serde_json::to_value(&params).unwrap(), json!([]));
+    let params = AuthorizeParams {
+        username: "worker".to_owned(),
+        password: Some("x".to_owned()),
+    };
+    assert_eq!(
+        serde_json::to_value(&params).unwrap(),
+        json!(["worker", "x"])
+    );
+}
+
+#[test]
+fn params_deserialize() {
+    let params: AuthorizeParams = serde_json::from_value(json!(["worker"])).unwrap();
+    assert_eq!(params.username, "worker");
+    assert_eq!(params.password, None);
+    let params: AuthorizeParams = serde_json::from_value(json!(["worker", null, 1])).unwrap();
+    assert_eq!(params.password, None);
+    let params: SubmitParams = serde_json::from_value(json!([
+        "worker", "1f", "00000001", "5e8f0a2b", "b2957c02", "00002000"
+    ]))
+    .unwrap();
+    assert_eq!(params.version_bits.as_deref(), Some("00002000"));
+    let result: SubscribeResult = serde_json::from_value(json!([
+        [["mining.set_difficulty", "1"], ["mining.notify", "1"]],
+        "08000002",
+        4
+    ]))
+    .unwrap();
+    assert_eq!(result.subscriptions.len(), 2);
+    assert_eq!(result.extranonce1, "08000002");
+    assert_eq!(result.extranonce2_size, 4);
+    assert!(serde_json::from_value::<SubmitParams>(json!(["worker", "1f"])).is_err());
+    assert!(serde_json::from_value::<SetDifficultyParams>(json!({"difficulty": 1})).is_err());
+}
+
+fn notify_params() -> Value {
+    json!([
+        "bf",
+        "4d16b6f85af6e2198f44ae2a6de67f78487ae5611b77c6c0440b921e00000000",
+        "01000000010000",
+        "072f736c7573682f000000000100f2052a010000001976a914d23fcdf86f7e756a64a7a9688ef9903327048ed988ac00000000",
+        [],
+        "00000002",
+        "1c2ac4af",
+        "504e86b9",
+        false
+    ])
+}
+
+/// Reads a message, raw values can't be read from a `Value`.
+fn read_message(value: Value) -> Result<Message, serde_json::Error> {
+    serde_json::from_str(&value.to_string())
+}
+
+#[test]
+fn notification_from_message() {
+    let message = read_message(json!({
+        "id": null,
+        "method": "mining.notify",
+        "params": notify_params(),
+    }))
+    .unwrap();
+    let notification = Notification::from(message);
+    assert_eq!(notification.method(), Some(method::NOTIFY));
+    match &notification {
+        Notification::Notify(params) => {
+            assert_eq!(params.job_id, "bf");
+            assert!(!params.clean_jobs);
+        }
+        other => panic!("unexpected notification: {:?}", other),
+    }
+    let message = notification.to_message().unwrap();
+    let value = serde_json::to_value(&message).unwrap();
+    assert_eq!(value.get("jsonrpc"), None);
+    assert_eq!(value["id"], Value::Null);
+    assert_eq!(value["params"], notify_params());
+
+    let message = read_message(json!({
+        "id": null,
+        "method": "mining.set_difficulty",
+        "params": ["high"],
+    }))
+    .unwrap();
+    match Notification::from(message) {
+        Notification::Other(message) => assert_eq!(message.method(), Some(method::SET_DIFFICULTY)),
+        other => panic!("unexpected notification: {:?}", other),
+    }
+}
+
+/// Accepts a single miner connection and answers its requests like some pools do.
+///
+/// Responses repeat the request `method` and errors are sent as arrays.
+async fn start_quirky_pool() -> (String, UnboundedReceiver<String>) {
+    let (requests, received) = unbounded_channel();
+    let mut listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap().to_string();
+    tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let (reader, mut writer) = tokio::io::split(stream);
+        let mut lines = BufReader::new(reader).lines();
+        while let Some(line) = lines.next_line().await.unwrap() {
+            let request: Value = serde_json::from_str(&line).unwrap();
+            // Requests are sent in JSON-RPC 1.0 dialect.
+            assert_eq!(request.get("jsonrpc"), None, "{}", line);
+            assert!(request["params"].is_array(), "{}", line);
+            requests.send(line).unwrap();
+            let id = request["id"].clone();
+            let method = request["method"].clone();
+            let response = match method.as_str().unwrap() {
+                method::SUBSCRIBE => json!({
+                    "id": id,
+                    "method": method,
+                    "result": [[["mining.notify", "ae6812eb4cd7735a302a8a9dd95cf71f"]], "08000002", 4],
+                    "error": null,
+                }),
+                method::SUBMIT => json!({
+                    "id": id,
+                    "result": null,
+                    "error": [21, "Job not found", null],
+                }),
+                _ => json!({"id": id, "method": method, "result": true, "error": null}),
+            };
+            let mut response = serde_json::to_vec(&response).unwrap();
+            response.push(b'\n');
+            writer.write_all(&response).await.unwrap();
+            if method == method::AUTHORIZE {
+                let notify =
+                    json!({"id": null, "method": "mining.notify", "params": notify_params()});
+                let mut notify = serde_json::to_vec(&notify).unwrap();
+                notify.push(b'\n');
+                writer.write_all(&notify).await.unwrap();
+            }
+        }
+    });
+    (addr, received)
+}
+
+#[tokio::test]
+async fn client_quirky_responses() {
+    let (addr, mut requests) = start_quirky_pool().await;
+    let mut client = connect(&addr);
+
+    let subscribe = SubscribeParams {
+        user_agent: Some("net3/0.1".to_owned()),
+        session_id: None,
+    };
+    let result = client.handle.subscribe(&subscribe).await.unwrap();
+    let request = requests.recv().await.unwrap();
+    let id = serde_json::from_str::<Value>(&request).unwrap()["id"].clone();
+    assert_eq!(
+        request,
+        format!(
+            r#"{{"id":{},"method":"mining.subscribe","params":["net3/0.1"]}}"#,
+            id
+        )
+    );
+    assert_eq!(result.extranonce1, "08000002");
+    assert_eq!(result.extranonce2_size, 4);
+
+    let authorize = AuthorizeParams {
+        username: "worker".to_owned(),
+        password: None,
+    };
+    assert!(client.handle.authorize(&authorize).await.unwrap());
+    match client.recv().await.unwrap() {
+        Notification::Notify(params) => assert_eq!(params.job_id, "bf"),
+        other => panic!("unexpected notification: {:?}", other),
+    }
+
+    let submit = SubmitParams {
+        username: "worker".to_owned(),
+        job_id: "bf".to_owned(),
+        extranonce2: "00000001".to_owned(),
+        ntime: "504e86b9".to_owned(),
+        nonce: "b2957c02".to_owned(),
+        version_bits: None,
+    };
+    match client.handle.submit(&submit).await {
+        Err(net3_rpc_error::Error::Rpc(err)) => {
+            assert_eq!(err.kind, ErrorKind::ErrorCode(error::JOB_NOT_FOUND));
+            assert_eq!(err.description.as_deref(), Some("Job not found"));
+        }
+        other => panic!("unexpected submit result: {:?}", other),
+    }
+}
+
+#[derive(Clone)]
+struct Pool;
+
+#[async_trait]
+impl Stratum for Pool {
+    async fn subscribe(&mut self, _params: &SubscribeParams) -> RpcResult<SubscribeResult> {
+        Ok(SubscribeResult {
+            subscriptions: vec![(method::NOTIFY.to_owned(), "1".to_owned())],
+            extranonce1: "08000002".to_owned(),
+            extranonce2_size: 4,
+        })
+    }
+
+    async fn authorize(&mut self, params: &AuthorizeParams) -> RpcResult<bool> {
+        Ok(params.username == "worker")
+    }
+
+    async fn submit(&mut self, _params: &SubmitParams) -> RpcResult<bool> {
+        Err(error::error(error::DUPLICATE_SHARE, "Duplicate share").into())
+    }
+
+    async fn extranonce_subscribe(&mut self, _: &ExtranonceSubscribeParams) -> RpcResult<bool> {
+        Ok(true)
+    }
+}
+
+#[tokio::test]
+async fn server_responds_v1() {
+    use net3_rpc_server::{common::CloneBuilder, ServerBuilder};
+
+    let server = ServerBuilder::<client::Codec, _>::from(CloneBuilder(StratumHandler(Pool)))
+        .bind("127.0.0.1:0")
+        .await
+        .unwrap();
+    let addr = server.local_addr().unwrap();
+    server.background();
+
+    let stream = TcpStream::connect(addr).await.unwrap();
+    let (reader, mut writer) = tokio::io::split(stream);
+    let mut lines = BufReader::new(reader).lines();
+    writer
+        .write_all(b"{\"id\":1,\"method\":\"mining.authorize\",\"params\":[\"worker\",\"x\"]}\n")
+        .await
+        .unwrap();
+    let response: Value = serde_json::from_str(&lines.next_line().await.unwrap().unwrap()).unwrap();
+    assert_eq!(response, json!({"id": 1, "result": true, "error": null}));
+
+    writer
+        .write_all(b"{\"id\":2,\"method\":\"mining.submit\",\"params\":[\"worker\",\"bf\",\"00000001\",\"504e86b9\",\"b2957c02\"]}\n")
+        .await
+        .unwrap();
+    let response: Value = serde_json::from_str(&lines.next_line().await.unwrap().unwrap()).unwrap();
+    assert_eq!(response["id"], json!(2));
+    assert_eq!(response["result"], Value::Null);
+    assert_eq!(response["error"]["code"], json!(error::DUPLICATE_SHARE));
+}
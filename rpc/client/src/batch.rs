@@ -0,0 +1,150 @@
+//! Batches of calls.
+//!
+//! Requests and notifications of a [`BatchRequest`] are sent in a single frame.
+//! Responses to the requests are received independently with [`BatchCall`].
+//!
+//! [`BatchRequest`]: struct.BatchRequest.html
+//! [`BatchCall`]: struct.BatchCall.html
+
+use std::{
+    io::{Error, ErrorKind},
+    marker::PhantomData,
+    time::Duration,
+};
+
+use serde::{de::DeserializeOwned, ser::Serialize};
+use tokio::sync::oneshot::channel;
+
+use net3_msg::{
+    builder::{self, MessageBuilder},
+    traits::Message,
+    types::{Id, Metadata},
+};
+use net3_rpc_error::Result;
+
+use crate::{
+    handle::{receive_response, Handle},
+    handler::internal::{ClientMessage, ResponseReceiver, ResponseSender},
+    queue::QueueSender,
+};
+
+/// Batch of requests and notifications sent in a single frame.
+///
+/// Created with [`Handle::batch`]. Nothing is sent until [`send`] is called.
+///
+/// [`Handle::batch`]: ../handle/struct.Handle.html#method.batch
+/// [`send`]: #method.send
+pub struct BatchRequest<'a, M: Message, U> {
+    handle: &'a Handle<M, U>,
+    messages: Vec<(M, Option<ResponseSender<M>>)>,
+}
+
+impl<'a, M: Message, U> BatchRequest<'a, M, U> {
+    pub(crate) fn new(handle: &'a Handle<M, U>) -> Self {
+        BatchRequest {
+            handle,
+            messages: Vec::new(),
+        }
+    }
+
+    /// Adds a method call request to the batch.
+    ///
+    /// Returns a call receiving the response once the batch is sent.
+    /// Default timeout duration is used to await for the response.
+    pub fn request<V: Serialize, R: DeserializeOwned>(
+        &mut self,
+        method: &str,
+        params: Option<&V>,
+    ) -> Result<BatchCall<M, R>> {
+        let mut metadata = Metadata::new();
+        let request_timeout = self
+            .handle
+            .call_timeout(&mut metadata, self.handle.inner.request_timeout);
+        let id = self.handle.next_id();
+        let message = builder::new_request::<M, V>(id.clone(), method, params)?
+            .with_metadata(metadata)
+            .build();
+        let (sender, receiver) = channel();
+        self.messages.push((message, Some(sender)));
+        Ok(BatchCall {
+            id,
+            receiver,
+            control: self.handle.inner.control.clone(),
+            request_timeout,
+            _marker: PhantomData,
+        })
+    }
+
+    /// Adds an event message to the batch.
+    pub fn notification<V: Serialize>(
+        &mut self,
+        method: &str,
+        params: Option<&V>,
+    ) -> std::io::Result<()> {
+        let message = builder::new_event::<M, V>(method, params)?.build();
+        self.messages.push((message, None));
+        Ok(())
+    }
+
+    /// Returns amount of messages in the batch.
+    pub fn len(&self) -> usize {
+        self.messages.len()
+    }
+
+    /// Returns true if the batch is empty.
+    pub fn is_empty(&self) -> bool {
+        self.messages.is_empty()
+    }
+
+    /// Sends the batch to a network channel.
+    ///
+    /// Empty batch is not sent. Fails with `WouldBlock` if the message queue is full.
+    pub fn send(self) -> std::io::Result<()> {
+        if self.messages.is_empty() {
+            return Ok(());
+        }
+        tracing::info!(len = self.messages.len(), "sending batch to channel");
+        self.handle
+            .inner
+            .sender
+            .try_send(ClientMessage::Batch(
+                self.messages,
+                tracing::info_span!("send_batch"),
+            ))
+            .map_err(Error::from)
+    }
+}
+
+/// Method call request of a batch awaiting a response.
+///
+/// Receiving the response fails if the batch is dropped without sending.
+pub struct BatchCall<M, R> {
+    id: Id,
+    receiver: ResponseReceiver<M>,
+    /// Sender of a request cancellation.
+    control: QueueSender<ClientMessage<M>>,
+    request_timeout: Duration,
+    _marker: PhantomData<fn() -> R>,
+}
+
+impl<M: Message, R: DeserializeOwned> BatchCall<M, R> {
+    /// Returns identifier of the request.
+    pub fn id(&self) -> &Id {
+        &self.id
+    }
+
+    /// Receives the response to the request.
+    pub async fn response(self) -> Result<R> {
+        match self.response_opt().await? {
+            Some(res) => Ok(res),
+            None => Err(Error::new(ErrorKind::InvalidData, "empty response").into()),
+        }
+    }
+
+    /// Receives the response to the request.
+    pub async fn response_opt(self) -> Result<Option<R>> {
+        let message =
+            receive_response(&self.control, self.id, self.receiver, self.request_timeout).await?;
+        Ok(message.read_optional()?)
+    }
+}
@@ -3,34 +3,31 @@
 use std::{
     ops::DerefMut,
     sync::{
-        atomic::{AtomicU64, Ordering},
+        atomic::{AtomicBool, AtomicU64, Ordering},
         Arc,
     },
     time::Duration,
 };
 
-use tokio::{
-    net::TcpStream,
-    sync::{
-        mpsc::{unbounded_channel, UnboundedSender},
-        Mutex,
-    },
-    task::JoinHandle,
-    time::delay_for,
-};
+use tokio::{net::TcpStream, sync::Mutex, task::JoinHandle, time::delay_for};
 use tokio_util::codec::{Decoder, Encoder};
 
 use crate::{
     common::{BuilderInitFunc, CloneBuilder, InitClosure},
     handle::{Handle, InnerHandle},
     handler::{internal::ClientMessage, ClientHandler, ClonedReceiver},
+    id::IdGenerator,
     notifications::{NotificationHandler, Notifications},
+    queue::{self, QueueConfig, QueueSender},
     traits::*,
 };
 
 use net3_channel::Channel;
-use net3_msg::traits::Message;
-use net3_rpc_conn::start_loop;
+use net3_codec_limits::{LimitedCodec, Limits};
+use net3_msg::{batch::Frame, traits::Message};
+use net3_rpc_conn::{
+    start_loop, Cancellation, Closing, Disconnect, Overflow, DEFAULT_MAX_READ_AHEAD,
+};
 
 /// Client builder error types.
 pub mod errors {
@@ -90,15 +87,25 @@ pub struct Builder<C: Decoder, B: HandlerBuilder> {
     /// Network connection channel.
     channel: Option<Channel<C>>,
     /// Sender of messages forwarded to network.
-    sender: UnboundedSender<ClientMessage<<<B as HandlerBuilder>::Handler as Handler>::Message>>,
+    sender: QueueSender<ClientMessage<<<B as HandlerBuilder>::Handler as Handler>::Message>>,
     /// Receiver of messages forwarded to network.
     receiver: ClonedReceiver<ClientMessage<<<B as HandlerBuilder>::Handler as Handler>::Message>>,
     /// Atomic request counter for message ID.
     requests: Arc<AtomicU64>,
+    /// Request ID generation strategy.
+    id_generator: IdGenerator,
     /// Default request timeout set on client handles.
     request_timeout: Duration,
+    /// Encode request timeout in the request metadata.
+    propagate_deadlines: bool,
+    /// Method name of request cancellation notifications.
+    cancel_method: Option<String>,
+    /// Limit of requests handled concurrently.
+    max_concurrent_requests: Option<usize>,
     /// Interval between reconnect retries.
     reconnect_interval: Duration,
+    /// Codec resource limits, if set explicitly.
+    limits: Option<Limits>,
     /// Handler builder.
     handler_builder: Option<B>,
     /// Connection initializers.
@@ -108,9 +115,29 @@ pub struct Builder<C: Decoder, B: HandlerBuilder> {
     /// Counter of client instances.
     client_handles: Arc<AtomicU64>,
     /// Sender of internal events.
-    event_sender: UnboundedSender<<<B as HandlerBuilder>::Handler as Handler>::Event>,
+    event_sender: QueueSender<<<B as HandlerBuilder>::Handler as Handler>::Event>,
     /// Receiver of internal events.
     event_receiver: ClonedReceiver<<<B as HandlerBuilder>::Handler as Handler>::Event>,
+    /// Sender of messages sent while a remote message is handled.
+    outbox_sender: QueueSender<<<B as HandlerBuilder>::Handler as Handler>::Message>,
+    /// Receiver of messages sent while a remote message is handled.
+    outbox_receiver: ClonedReceiver<<<B as HandlerBuilder>::Handler as Handler>::Message>,
+    /// Sender of control messages.
+    control_sender:
+        QueueSender<ClientMessage<<<B as HandlerBuilder>::Handler as Handler>::Message>>,
+    /// Receiver of control messages.
+    control_receiver:
+        ClonedReceiver<ClientMessage<<<B as HandlerBuilder>::Handler as Handler>::Message>>,
+    /// Sender of closing requests.
+    closing_sender: QueueSender<Closing>,
+    /// Receiver of closing requests.
+    closing_receiver: ClonedReceiver<Closing>,
+    /// Capacity and overflow policy of notification queues.
+    notification_queue: QueueConfig,
+    /// Capacity and overflow policy of queues of partial results.
+    stream_queue: QueueConfig,
+    /// Queue of frames read ahead while a remote message is handled.
+    read_ahead_queue: QueueConfig,
 }
 
 impl<C: Decoder, T> Builder<C, CloneBuilder<NotificationHandler<<C as Decoder>::Item, T>>>
@@ -119,7 +146,7 @@ where
     T: From<<C as Decoder>::Item> + Send + Sync + Clone,
 {
     /// Creates a notification sink handler and sets it by default.
-    pub fn notify(&mut self, sender: UnboundedSender<T>) {
+    pub fn notify(&mut self, sender: QueueSender<T>) {
         let handler = NotificationHandler::from(sender);
         self.handler_builder = Some(CloneBuilder::from(handler));
     }
@@ -127,8 +154,11 @@ where
     /// Creates a notification sink handler and sets it by default.
     ///
     /// Calling this method twice will make previous receivers useless.
+    /// Notification queue is configured with [`with_notification_queue`].
+    ///
+    /// [`with_notification_queue`]: #method.with_notification_queue
     pub fn notifications(&mut self) -> Notifications<<C as Decoder>::Item, T> {
-        let (sender, receiver) = unbounded_channel();
+        let (sender, receiver) = queue::channel(self.notification_queue);
         self.notify(sender);
         let handle = self.handle();
         Notifications { handle, receiver }
@@ -142,22 +172,39 @@ impl<C: Decoder, B: HandlerBuilder> Builder<C, B> {
     /// Use `Default::default()` for simple use cases.
     #[inline]
     pub fn new() -> Self {
-        let (sender, receiver) = unbounded_channel();
-        let (event_sender, event_receiver) = unbounded_channel();
+        let (sender, receiver) = queue::channel(QueueConfig::default());
+        let (event_sender, event_receiver) = queue::channel(QueueConfig::default());
+        let (outbox_sender, outbox_receiver) = queue::channel(QueueConfig::default());
+        let (control_sender, control_receiver) = queue::channel(QueueConfig::unbounded());
+        let (closing_sender, closing_receiver) = queue::channel(QueueConfig::unbounded());
         Builder {
             client_id: None,
             channel: None,
             sender,
             receiver: receiver.into(),
             requests: Default::default(),
+            id_generator: IdGenerator::default(),
             request_timeout: Duration::from_secs(3),
+            propagate_deadlines: false,
+            cancel_method: None,
+            max_concurrent_requests: None,
             reconnect_interval: Duration::from_millis(100),
+            limits: None,
             handler_builder: None,
             initializers: vec![],
             reconnect: None,
             client_handles: Default::default(),
             event_sender,
             event_receiver: event_receiver.into(),
+            outbox_sender,
+            outbox_receiver: outbox_receiver.into(),
+            control_sender,
+            control_receiver: control_receiver.into(),
+            closing_sender,
+            closing_receiver: closing_receiver.into(),
+            notification_queue: QueueConfig::default(),
+            stream_queue: QueueConfig::default(),
+            read_ahead_queue: QueueConfig::bounded(DEFAULT_MAX_READ_AHEAD, Overflow::Wait),
         }
     }
 
@@ -166,22 +213,39 @@ impl<C: Decoder, B: HandlerBuilder> Builder<C, B> {
     /// [`Channel`]: ../channel/struct.Channel.html
     #[inline]
     pub fn from_channel(channel: Channel<C>) -> Self {
-        let (sender, receiver) = unbounded_channel();
-        let (event_sender, event_receiver) = unbounded_channel();
+        let (sender, receiver) = queue::channel(QueueConfig::default());
+        let (event_sender, event_receiver) = queue::channel(QueueConfig::default());
+        let (outbox_sender, outbox_receiver) = queue::channel(QueueConfig::default());
+        let (control_sender, control_receiver) = queue::channel(QueueConfig::unbounded());
+        let (closing_sender, closing_receiver) = queue::channel(QueueConfig::unbounded());
         Builder {
             client_id: None,
             channel: Some(channel),
             sender,
             receiver: receiver.into(),
             requests: Default::default(),
+            id_generator: IdGenerator::default(),
             request_timeout: Duration::from_secs(3),
+            propagate_deadlines: false,
+            cancel_method: None,
+            max_concurrent_requests: None,
             reconnect_interval: Duration::from_millis(100),
+            limits: None,
             handler_builder: None,
             initializers: vec![],
             reconnect: None,
             client_handles: Default::default(),
             event_sender,
             event_receiver: event_receiver.into(),
+            outbox_sender,
+            outbox_receiver: outbox_receiver.into(),
+            control_sender,
+            control_receiver: control_receiver.into(),
+            closing_sender,
+            closing_receiver: closing_receiver.into(),
+            notification_queue: QueueConfig::default(),
+            stream_queue: QueueConfig::default(),
+            read_ahead_queue: QueueConfig::bounded(DEFAULT_MAX_READ_AHEAD, Overflow::Wait),
         }
     }
 
@@ -249,6 +313,15 @@ impl<C: Decoder, B: HandlerBuilder> Builder<C, B> {
         self
     }
 
+    /// Sets request ID generation strategy.
+    ///
+    /// Default strategy creates string identifiers from a request counter.
+    #[inline]
+    pub fn with_id_generator(mut self, generator: IdGenerator) -> Self {
+        self.id_generator = generator;
+        self
+    }
+
     /// Sets default timeout on [`request`] call.
     ///
     /// Default request timeout is set to 3 seconds.
@@ -260,6 +333,127 @@ impl<C: Decoder, B: HandlerBuilder> Builder<C, B> {
         self
     }
 
+    /// Encodes request timeout in the metadata of sent requests.
+    ///
+    /// Server handles the request only until the timeout elapses,
+    /// see [`deadline`] module. Disabled by default.
+    ///
+    /// [`deadline`]: ../deadline/index.html
+    #[inline]
+    pub fn with_deadline_propagation(mut self) -> Self {
+        self.propagate_deadlines = true;
+        self
+    }
+
+    /// Enables remote cancellation of requests using given notification method name.
+    ///
+    /// Requests that time out or whose futures are dropped are cancelled on the peer.
+    /// Handlers of requests cancelled by the peer are aborted.
+    /// Peers are expected to use [`DEFAULT_CANCEL_METHOD`] unless agreed otherwise.
+    ///
+    /// [`DEFAULT_CANCEL_METHOD`]: ../constant.DEFAULT_CANCEL_METHOD.html
+    #[inline]
+    pub fn with_cancel_method<T: ToString>(mut self, method: T) -> Self {
+        self.cancel_method = Some(method.to_string());
+        self
+    }
+
+    /// Handles up to `limit` requests of a connection concurrently.
+    ///
+    /// Requests and notifications, single or in batches, are handled by handlers
+    /// created with [`Handler::fork`], their responses are sent as they complete.
+    /// Requests are handled one by one by default.
+    ///
+    /// # Panics
+    ///
+    /// Connection panics when it starts if the handler cannot be forked.
+    ///
+    /// [`Handler::fork`]: ../traits/trait.Handler.html#method.fork
+    #[inline]
+    pub fn with_concurrent_requests(mut self, limit: usize) -> Self {
+        self.max_concurrent_requests = Some(limit.max(1));
+        self
+    }
+
+    /// Sets capacity and overflow policy of the queue of messages sent to the network.
+    ///
+    /// Applies to messages sent with client handles and partial results.
+    /// Cancellations and closing requests are sent on separate queues,
+    /// they are never rejected or dropped. Queue is unbounded by default.
+    ///
+    /// # Panics
+    ///
+    /// Panics if client handles exist, queue has to be set before creating handles.
+    #[inline]
+    pub fn with_message_queue(mut self, config: QueueConfig) -> Self {
+        self.assert_no_handles();
+        let (sender, receiver) = queue::channel(config);
+        let (outbox_sender, outbox_receiver) = queue::channel(config);
+        self.sender = sender;
+        self.receiver = receiver.into();
+        self.outbox_sender = outbox_sender;
+        self.outbox_receiver = outbox_receiver.into();
+        self
+    }
+
+    /// Sets capacity and overflow policy of the internal event queue.
+    ///
+    /// Queue is unbounded by default.
+    ///
+    /// # Panics
+    ///
+    /// Panics if client handles exist, queue has to be set before creating handles.
+    #[inline]
+    pub fn with_event_queue(mut self, config: QueueConfig) -> Self {
+        self.assert_no_handles();
+        let (event_sender, event_receiver) = queue::channel(config);
+        self.event_sender = event_sender;
+        self.event_receiver = event_receiver.into();
+        self
+    }
+
+    /// Sets capacity and overflow policy of queues of partial results of streaming calls.
+    ///
+    /// Final responses are delivered regardless of the policy.
+    /// Queue is unbounded by default.
+    #[inline]
+    pub fn with_stream_queue(mut self, config: QueueConfig) -> Self {
+        self.stream_queue = config;
+        self
+    }
+
+    /// Sets queue of frames read ahead while a remote message is handled.
+    ///
+    /// Frames are read ahead to receive cancellations when a cancel method is set.
+    /// Queue holds up to [`DEFAULT_MAX_READ_AHEAD`] frames and waits by default.
+    ///
+    /// [`DEFAULT_MAX_READ_AHEAD`]: ../../net3_rpc_conn/constant.DEFAULT_MAX_READ_AHEAD.html
+    #[inline]
+    pub fn with_read_ahead_queue(mut self, config: QueueConfig) -> Self {
+        self.read_ahead_queue = config;
+        self
+    }
+
+    /// Panics if client handles exist.
+    fn assert_no_handles(&self) {
+        assert_eq!(
+            self.client_handles.load(Ordering::SeqCst),
+            0,
+            "queues have to be configured before creating client handles"
+        );
+    }
+
+    /// Sets capacity and overflow policy of queues created by [`notifications`].
+    ///
+    /// Queue is unbounded by default.
+    ///
+    /// [`notifications`]: #method.notifications
+    #[inline]
+    pub fn with_notification_queue(mut self, config: QueueConfig) -> Self {
+        self.notification_queue = config;
+        self
+    }
+
     /// Sets interval between reconnect retries after a failure.
     ///
     /// Default retry interval is set to 100 milliseconds.
@@ -271,6 +465,19 @@ impl<C: Decoder, B: HandlerBuilder> Builder<C, B> {
         self
     }
 
+    /// Sets codec resource limits.
+    ///
+    /// Limits are applied to the codec of every connection made by the client.
+    /// Limits of a codec of a supplied channel are kept unless set explicitly.
+    /// Default limits are defined in [`Limits`].
+    ///
+    /// [`Limits`]: ../../net3_codec_limits/struct.Limits.html
+    #[inline]
+    pub fn with_limits(mut self, limits: Limits) -> Self {
+        self.limits = Some(limits);
+        self
+    }
+
     /// Sets client [`Handler`] builder structure.
     ///
     /// [`Handler`]: ../../handler/trait.Handler.html
@@ -321,8 +528,14 @@ impl<C: Decoder, B: HandlerBuilder> Builder<C, B> {
                 client_id: self.client_id,
                 events: self.event_sender.clone(),
                 sender: self.sender.clone(),
+                control: self.control_sender.clone(),
                 requests: self.requests.clone(),
+                id_generator: self.id_generator.clone(),
                 request_timeout: self.request_timeout,
+                propagate_deadlines: self.propagate_deadlines,
+                outbox: self.outbox_sender.clone(),
+                closing: self.closing_sender.clone(),
+                stream_queue: self.stream_queue,
                 instances: self.client_handles.clone(),
             }),
             is_owned: true,
@@ -350,11 +563,10 @@ impl<C: Decoder, B: HandlerBuilder> Builder<C, B> {
 
 impl<C, B> Builder<C, B>
 where
-    C: Default + Send + Sync + 'static,
+    C: Default + LimitedCodec + Send + Sync + 'static,
     B: HandlerBuilder + Send + Sync + 'static,
-    C: Decoder<Item = <<B as HandlerBuilder>::Handler as Handler>::Message, Error = std::io::Error>,
-    <C as Decoder>::Item: Message + Clone,
-    C: Encoder<<<B as HandlerBuilder>::Handler as Handler>::Message, Error = std::io::Error>,
+    C: Decoder<Error = std::io::Error> + Encoder<<C as Decoder>::Item, Error = std::io::Error>,
+    <C as Decoder>::Item: Frame<<<B as HandlerBuilder>::Handler as Handler>::Message> + Send,
     <B as HandlerBuilder>::Handler: Send + Sync + 'static,
     <<B as HandlerBuilder>::Handler as Handler>::Message: Clone,
 {
@@ -364,23 +576,23 @@ where
     /// [`start`]: #method.start
     /// [`JoinHandle`]: https://docs.rs/tokio/0.2/tokio/task/struct.JoinHandle.html
     #[inline]
-    pub fn spawn(self) -> JoinHandle<Result<(), BuilderError>> {
+    pub fn spawn(self) -> JoinHandle<Result<Disconnect, BuilderError>> {
         tokio::spawn(async move {
-            if let Err(e) = self.start().await {
+            let result = self.start().await;
+            if let Err(e) = &result {
                 log::error!("Spawn error: {:?}", e);
-                Err(e)
-            } else {
-                Ok(())
             }
+            result
         })
     }
 
     /// Spawns client handler loop using [`start_loop`] implementation.
-    /// Returns an error on any channel protocol or TCP connection error.
+    /// Returns an error on any channel protocol or TCP connection error,
+    /// otherwise reason of the disconnection.
     ///
     /// [`start_loop`]: ../handler/fn.start_loop.html
     #[inline]
-    pub async fn start(self) -> Result<(), BuilderError> {
+    pub async fn start(self) -> Result<Disconnect, BuilderError> {
         assert!(
             self.handler_builder.is_some(),
             "Handler builder is required"
@@ -393,11 +605,14 @@ where
     }
 
     #[inline]
-    async fn start_loop(mut self) -> Result<(), BuilderError> {
+    async fn start_loop(mut self) -> Result<Disconnect, BuilderError> {
         let handle = self.handle();
         let mut channel = self
             .channel
             .ok_or_else(|| std::io::Error::from(std::io::ErrorKind::NotConnected))?;
+        if let Some(limits) = self.limits {
+            channel.codec_mut().set_limits(limits);
+        }
         for initializer in self.initializers.iter_mut() {
             initializer.init(&handle).await?;
         }
@@ -410,10 +625,15 @@ where
             channel.deref_mut(),
             ClientHandler::new(
                 self.receiver,
+                self.control_receiver,
                 handler,
                 handle.into(),
-                self.client_handles.clone(),
-            ),
+                self.cancel_method.as_deref().map(Cancellation::new),
+                self.outbox_receiver,
+                self.closing_receiver,
+            )
+            .with_concurrent_requests(self.max_concurrent_requests)
+            .with_read_ahead_queue(self.read_ahead_queue),
             Some(self.event_receiver.clone()),
         )
         .await
@@ -421,7 +641,7 @@ where
     }
 
     #[inline]
-    async fn start_loop_reconnect(self) -> Result<(), BuilderError> {
+    async fn start_loop_reconnect(self) -> Result<Disconnect, BuilderError> {
         let handle = self.handle();
         let receiver: ClonedReceiver<
             ClientMessage<<<B as HandlerBuilder>::Handler as Handler>::Message>,
@@ -437,17 +657,23 @@ where
             // Check if client handles still exist.
             if self.client_handles.load(Ordering::SeqCst) == 0 {
                 log::debug!("No more client handles exist for {:?}", reconnect);
-                return Ok(());
+                return Ok(Disconnect::Closed);
             }
             // Connect to TCP stream.
             let mut channel =
                 Channel::<C>::connect_infinite(&reconnect, self.reconnect_interval).await;
+            if let Some(limits) = self.limits {
+                channel.codec_mut().set_limits(limits);
+            }
             let handle_ = handle.clone();
             let initializers_ = initializers.clone();
+            let init_failed = Arc::new(AtomicBool::new(false));
+            let init_failed_ = init_failed.clone();
             tokio::spawn(async move {
                 // Initialize connection.
                 for initializer in initializers_.lock().await.iter_mut() {
                     if initializer.init(&handle_).await.is_err() {
+                        init_failed_.store(true, Ordering::SeqCst);
                         let _ok = handle_.close();
                         break;
                     }
@@ -460,15 +686,29 @@ where
                 channel.deref_mut(),
                 ClientHandler::new(
                     receiver.clone(),
+                    self.control_receiver.clone(),
                     handler,
                     handle.clone().into(),
-                    self.client_handles.clone(),
-                ),
+                    self.cancel_method.as_deref().map(Cancellation::new),
+                    self.outbox_receiver.clone(),
+                    self.closing_receiver.clone(),
+                )
+                .with_concurrent_requests(self.max_concurrent_requests)
+                .with_read_ahead_queue(self.read_ahead_queue),
                 Some(self.event_receiver.clone()),
             )
             .await
             {
-                Ok(()) => continue,
+                // Connection closed after a failed initialization is made again.
+                Ok(Disconnect::Closed) if init_failed.load(Ordering::SeqCst) => {
+                    delay_for(self.reconnect_interval).await;
+                    log::trace!(
+                        "Connection initialization failed, reconnecting in {:?}.",
+                        self.reconnect_interval
+                    );
+                }
+                // Connection was closed on request.
+                Ok(reason) => return Ok(reason),
                 Err(err) => {
                     delay_for(self.reconnect_interval).await;
                     log::trace!(
@@ -496,22 +736,39 @@ where
 impl<C: Decoder, B: HandlerBuilder> From<B> for Builder<C, B> {
     #[inline]
     fn from(handler: B) -> Self {
-        let (sender, receiver) = unbounded_channel();
-        let (event_sender, event_receiver) = unbounded_channel();
+        let (sender, receiver) = queue::channel(QueueConfig::default());
+        let (event_sender, event_receiver) = queue::channel(QueueConfig::default());
+        let (outbox_sender, outbox_receiver) = queue::channel(QueueConfig::default());
+        let (control_sender, control_receiver) = queue::channel(QueueConfig::unbounded());
+        let (closing_sender, closing_receiver) = queue::channel(QueueConfig::unbounded());
         Builder {
             client_id: None,
             channel: None,
             sender,
             receiver: receiver.into(),
             requests: Default::default(),
+            id_generator: IdGenerator::default(),
             request_timeout: Duration::from_secs(3),
+            propagate_deadlines: false,
+            cancel_method: None,
+            max_concurrent_requests: None,
             reconnect_interval: Duration::from_millis(100),
+            limits: None,
             handler_builder: Some(handler),
             initializers: vec![],
             reconnect: None,
             client_handles: Default::default(),
             event_sender,
             event_receiver: event_receiver.into(),
+            outbox_sender,
+            outbox_receiver: outbox_receiver.into(),
+            control_sender,
+            control_receiver: control_receiver.into(),
+            closing_sender,
+            closing_receiver: closing_receiver.into(),
+            notification_queue: QueueConfig::default(),
+            stream_queue: QueueConfig::default(),
+            read_ahead_queue: QueueConfig::bounded(DEFAULT_MAX_READ_AHEAD, Overflow::Wait),
         }
     }
 }
@@ -526,22 +783,39 @@ where
     /// [`Channel`]: ../channel/struct.Channel.html
     #[inline]
     fn default() -> Self {
-        let (sender, receiver) = unbounded_channel();
-        let (event_sender, event_receiver) = unbounded_channel();
+        let (sender, receiver) = queue::channel(QueueConfig::default());
+        let (event_sender, event_receiver) = queue::channel(QueueConfig::default());
+        let (outbox_sender, outbox_receiver) = queue::channel(QueueConfig::default());
+        let (control_sender, control_receiver) = queue::channel(QueueConfig::unbounded());
+        let (closing_sender, closing_receiver) = queue::channel(QueueConfig::unbounded());
         Builder {
             client_id: None,
             channel: None,
             sender,
             receiver: receiver.into(),
             requests: Default::default(),
+            id_generator: IdGenerator::default(),
             request_timeout: Duration::from_secs(3),
+            propagate_deadlines: false,
+            cancel_method: None,
+            max_concurrent_requests: None,
             reconnect_interval: Duration::from_millis(100),
+            limits: None,
             handler_builder: Some(Default::default()),
             initializers: vec![],
             reconnect: None,
             client_handles: Default::default(),
             event_sender,
             event_receiver: event_receiver.into(),
+            outbox_sender,
+            outbox_receiver: outbox_receiver.into(),
+            control_sender,
+            control_receiver: control_receiver.into(),
+            closing_sender,
+            closing_receiver: closing_receiver.into(),
+            notification_queue: QueueConfig::default(),
+            stream_queue: QueueConfig::default(),
+            read_ahead_queue: QueueConfig::bounded(DEFAULT_MAX_READ_AHEAD, Overflow::Wait),
         }
     }
 }
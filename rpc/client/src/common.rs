@@ -67,7 +67,7 @@ pub struct ClonedHandler<H: Handler> {
 }
 
 #[async_trait]
-impl<H: Handler + Send> Handler for ClonedHandler<H> {
+impl<H: Handler + Send + Clone> Handler for ClonedHandler<H> {
     type Event = H::Event;
     type Message = H::Message;
 
@@ -85,6 +85,14 @@ impl<H: Handler + Send> Handler for ClonedHandler<H> {
     async fn handle_internal_event(&mut self, event: Self::Event) -> Result<Vec<Self::Message>> {
         self.handler.handle_internal_event(event).await
     }
+
+    /// Forks the inner handler or clones it when it can not be forked.
+    fn fork(&self) -> Option<Self> {
+        Some(ClonedHandler {
+            handler: self.handler.fork().unwrap_or_else(|| self.handler.clone()),
+            handle: self.handle.clone(),
+        })
+    }
 }
 
 /// Handler builder using `From` conversion.
@@ -0,0 +1,61 @@
+//! Request deadlines.
+//!
+//! Clients built with [`with_deadline_propagation`] encode request timeout
+//! in the request metadata under the [`TIMEOUT_META`] key.
+//!
+//! Requests are handled within their deadline, handlers are cancelled
+//! once it has passed and no response is sent, as the requester already gave up.
+//! Deadline of the request being handled is available to handlers with [`current`].
+//!
+//! [`with_deadline_propagation`]: ../builder/struct.Builder.html#method.with_deadline_propagation
+//! [`TIMEOUT_META`]: ../../net3_msg/types/constant.TIMEOUT_META.html
+//! [`current`]: fn.current.html
+
+use std::{future::Future, io::Result, time::Duration};
+
+use tokio::time::{timeout_at, Instant};
+
+tokio::task_local! {
+    static DEADLINE: Instant;
+}
+
+/// Returns deadline of the request being handled.
+pub fn current() -> Option<Instant> {
+    DEADLINE.try_with(|deadline| *deadline).ok()
+}
+
+/// Returns time remaining until the deadline of the request being handled.
+pub fn remaining() -> Option<Duration> {
+    current().map(|deadline| deadline.saturating_duration_since(Instant::now()))
+}
+
+/// Returns true if deadline of the request being handled has passed.
+pub fn is_expired() -> bool {
+    matches!(current(), Some(deadline) if deadline <= Instant::now())
+}
+
+/// Runs a request handler within the request timeout.
+///
+/// Handler is skipped when the timeout is zero and cancelled once it elapses.
+/// It is used by transports handling requests outside of a channel loop.
+pub async fn handle<F, M>(timeout: Option<Duration>, handler: F) -> Result<Vec<M>>
+where
+    F: Future<Output = Result<Vec<M>>>,
+{
+    let timeout = match timeout {
+        Some(timeout) => timeout,
+        None => return handler.await,
+    };
+    if timeout == Duration::from_secs(0) {
+        tracing::warn!("request deadline exceeded, skipping handler");
+        return Ok(vec![]);
+    }
+    let deadline = Instant::now() + timeout;
+    match timeout_at(deadline, DEADLINE.scope(deadline, handler)).await {
+        Ok(result) => result,
+        Err(_) => {
+            tracing::warn!("request deadline exceeded, handler cancelled");
+            Ok(vec![])
+        }
+    }
+}
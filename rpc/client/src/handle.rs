@@ -11,20 +11,27 @@ use std::{
 };
 
 use serde::{de::DeserializeOwned, ser::Serialize};
-use tokio::{
-    sync::{mpsc::UnboundedSender, oneshot::channel},
-    time::timeout,
-};
+use tokio::{sync::oneshot::channel, time::timeout};
 
+use async_trait::async_trait;
 use tracing_attributes::instrument;
 
-use crate::handler::internal::{ClientMessage, ResponseReceiver};
+use crate::{
+    batch::BatchRequest,
+    deadline,
+    handler::internal::{ClientMessage, ResponseReceiver},
+    id::IdGenerator,
+    queue::{self, QueueConfig, QueueSender},
+    stream::{PartialParams, ResponseStream, PARTIAL_METHOD},
+    traits::Requester,
+};
 
 use net3_msg::{
     builder::{self, MessageBuilder},
     traits::Message,
-    types::Id,
+    types::{Id, Metadata, TIMEOUT_META},
 };
+use net3_rpc_conn::{Closing, Disconnect, Shutdown};
 use net3_rpc_error::{Error as CallError, Result};
 
 /// Handle reference.
@@ -65,15 +72,27 @@ pub(crate) struct InnerHandle<M: Message, U = ()> {
     /// Client ID.
     pub(crate) client_id: Option<u64>,
     /// Internal event sender.
-    pub(crate) events: UnboundedSender<U>,
+    pub(crate) events: QueueSender<U>,
     /// Sender of messages forwarded to network.
-    pub(crate) sender: UnboundedSender<ClientMessage<M>>,
+    pub(crate) sender: QueueSender<ClientMessage<M>>,
+    /// Sender of control messages which are never evicted from the queue.
+    pub(crate) control: QueueSender<ClientMessage<M>>,
     /// Atomic request counter for message ID.
     pub(crate) requests: Arc<AtomicU64>,
+    /// Request ID generation strategy.
+    pub(crate) id_generator: IdGenerator,
     /// Default request timeout used on a [`request`] call.
     ///
     /// [`request`]: struct.Handle.html#method.request
     pub(crate) request_timeout: Duration,
+    /// Encode request timeout in the request metadata.
+    pub(crate) propagate_deadlines: bool,
+    /// Sender of messages sent while a remote message is handled.
+    pub(crate) outbox: QueueSender<M>,
+    /// Sender of closing requests.
+    pub(crate) closing: QueueSender<Closing>,
+    /// Capacity and overflow policy of queues of partial results.
+    pub(crate) stream_queue: QueueConfig,
     /// Owned handle reference counter.
     /// It is decremented on a clone in `HandleRef`.
     pub(crate) instances: Arc<AtomicU64>,
@@ -85,18 +104,28 @@ impl<M: Message, U> Clone for InnerHandle<M, U> {
             client_id: self.client_id,
             events: self.events.clone(),
             sender: self.sender.clone(),
+            control: self.control.clone(),
             requests: self.requests.clone(),
+            id_generator: self.id_generator.clone(),
             request_timeout: self.request_timeout,
+            propagate_deadlines: self.propagate_deadlines,
+            outbox: self.outbox.clone(),
+            closing: self.closing.clone(),
+            stream_queue: self.stream_queue,
             instances: self.instances.clone(),
         }
     }
 }
 
-/// Network channel service client handle based on an [`UnboundedSender`].
+/// Network channel service client handle based on a [`QueueSender`].
 ///
 /// Designed as a base for strongly typed API clients.
 ///
-/// [`UnboundedSender`]: https://docs.rs/tokio/0.2/tokio/sync/mpsc/struct.UnboundedSender.html
+/// Non-blocking sends fail with `WouldBlock` when a bounded queue is full,
+/// asynchronous sends wait for space if the queue [`Overflow`] policy is `Wait`.
+///
+/// [`QueueSender`]: ../queue/struct.QueueSender.html
+/// [`Overflow`]: ../queue/enum.Overflow.html
 pub struct Handle<M: Message, U = ()> {
     /// Shared handle data.
     pub(crate) inner: Arc<InnerHandle<M, U>>,
@@ -112,12 +141,17 @@ impl<M: Message, U> Handle<M, U> {
     }
 
     /// Emits internal event.
+    ///
+    /// Fails with `WouldBlock` if the event queue is full.
     pub fn emit_internal(&self, event: U) -> std::io::Result<()> {
         log::trace!("Emit internal event");
-        self.inner
-            .events
-            .send(event)
-            .map_err(|_err| Error::from(ErrorKind::ConnectionReset))
+        Ok(self.inner.events.try_send(event)?)
+    }
+
+    /// Emits internal event, waiting for space in the event queue.
+    pub async fn emit_internal_async(&self, event: U) -> std::io::Result<()> {
+        log::trace!("Emit internal event");
+        Ok(self.inner.events.send(event).await?)
     }
 
     /// Sends a method call request to a network channel.
@@ -179,26 +213,178 @@ impl<M: Message, U> Handle<M, U> {
         params: Option<&V>,
         request_timeout: Duration,
     ) -> Result<Option<R>> {
-        let (msg_id, receiver) = self.send_request(method, params)?;
-        match timeout(request_timeout, receiver).await {
-            Ok(response) => {
-                match response.map_err(|_err| Error::from(ErrorKind::ConnectionReset))? {
-                    Ok(message) => Ok(message.read_optional()?),
-                    Err(err) => Err(CallError::Rpc(err)),
-                }
-            }
-            Err(_) => {
-                // Send cancelation requests to channel.
-                self.inner
-                    .sender
-                    .send(ClientMessage::Cancel(msg_id))
-                    .map_err(|_err| Error::from(ErrorKind::ConnectionReset))?;
-                Err(CallError::from(ErrorKind::TimedOut))
-            }
+        let (res, _) = self
+            .request_response(method, params, Metadata::new(), request_timeout)
+            .await?;
+        Ok(res)
+    }
+
+    /// Sends a method call request with metadata to a network channel.
+    ///
+    /// Returns response together with metadata of the response message.
+    /// Default timeout duration is used to await for the response.
+    #[instrument(skip(self, params, metadata))]
+    pub async fn request_with_metadata<V: Serialize, R: DeserializeOwned>(
+        &self,
+        method: &str,
+        params: Option<&V>,
+        metadata: Metadata,
+    ) -> Result<(R, Metadata)> {
+        match self
+            .request_opt_with_metadata(method, params, metadata)
+            .await?
+        {
+            (Some(res), metadata) => Ok((res, metadata)),
+            (None, _) => Err(Error::new(ErrorKind::InvalidData, "empty response").into()),
         }
     }
 
+    /// Sends a method call request with metadata to a network channel.
+    ///
+    /// Returns optional response together with metadata of the response message.
+    /// Default timeout duration is used to await for the response.
+    #[instrument(skip(self, params, metadata))]
+    pub async fn request_opt_with_metadata<V: Serialize, R: DeserializeOwned>(
+        &self,
+        method: &str,
+        params: Option<&V>,
+        metadata: Metadata,
+    ) -> Result<(Option<R>, Metadata)> {
+        self.request_response(method, params, metadata, self.inner.request_timeout)
+            .await
+    }
+
+    /// Sends a method call request and reads the response message.
+    ///
+    /// Returns optional response together with metadata of the response message.
+    async fn request_response<V: Serialize, R: DeserializeOwned>(
+        &self,
+        method: &str,
+        params: Option<&V>,
+        metadata: Metadata,
+        request_timeout: Duration,
+    ) -> Result<(Option<R>, Metadata)> {
+        let message = self
+            .request_message(method, params, metadata, request_timeout)
+            .await?;
+        Ok((message.read_optional()?, message.metadata().clone()))
+    }
+
+    /// Sends a method call request to a network channel.
+    ///
+    /// Receives a response message from oneshot sender asynchronously.
+    ///
+    /// Timeout is limited to the deadline of a request being handled, if any.
+    async fn request_message<V: Serialize>(
+        &self,
+        method: &str,
+        params: Option<&V>,
+        mut metadata: Metadata,
+        request_timeout: Duration,
+    ) -> Result<M> {
+        let request_timeout = self.call_timeout(&mut metadata, request_timeout);
+        let (msg_id, receiver) = self.send_request(method, params, metadata).await?;
+        receive_response(&self.inner.control, msg_id, receiver, request_timeout).await
+    }
+
+    /// Returns timeout of a call limited to the deadline of a request being handled.
+    ///
+    /// Timeout is inserted into the request `metadata` if deadlines are propagated.
+    pub(crate) fn call_timeout(
+        &self,
+        metadata: &mut Metadata,
+        request_timeout: Duration,
+    ) -> Duration {
+        let request_timeout = deadline::remaining()
+            .map_or(request_timeout, |remaining| remaining.min(request_timeout));
+        if self.inner.propagate_deadlines {
+            metadata.insert(
+                TIMEOUT_META.to_owned(),
+                request_timeout.as_millis().to_string(),
+            );
+        }
+        request_timeout
+    }
+
+    /// Returns identifier of the next request.
+    pub(crate) fn next_id(&self) -> Id {
+        // Increment request ID and get previous value.
+        let msg_id = self.inner.requests.fetch_add(1, Ordering::SeqCst);
+        // Create message ID using the configured strategy.
+        self.inner.id_generator.generate(msg_id)
+    }
+
+    /// Creates a batch of calls sent to a network channel in a single frame.
+    ///
+    /// Batches are sent one message per frame over codecs of plain messages,
+    /// use a codec of [`Batch`] messages to send them in a single frame.
+    ///
+    /// [`Batch`]: ../../net3_msg/batch/enum.Batch.html
+    pub fn batch(&self) -> BatchRequest<'_, M, U> {
+        BatchRequest::new(self)
+    }
+
+    /// Sends a streaming method call request to a network channel.
+    ///
+    /// Returns a stream of partial results sent by the peer with [`PARTIAL_METHOD`]
+    /// notifications, ended by the result of the final response.
+    /// Default timeout does not apply, dropping the stream cancels the request.
+    /// Partial results are queued as configured with `Builder::with_stream_queue`,
+    /// the final response is always delivered.
+    /// Fails with `WouldBlock` if the message queue is full.
+    ///
+    /// [`PARTIAL_METHOD`]: ../stream/constant.PARTIAL_METHOD.html
+    #[instrument(skip(self, params))]
+    pub fn request_stream<V: Serialize, R: DeserializeOwned>(
+        &self,
+        method: &str,
+        params: Option<&V>,
+    ) -> Result<ResponseStream<M, R>> {
+        let (partial_sender, partials) = queue::channel(self.inner.stream_queue);
+        let (sender, receiver) = channel();
+        let msg_id = self.next_id();
+        let message = builder::new_request::<M, V>(msg_id.clone(), method, params)?.build();
+        tracing::info!("sending to channel");
+        self.inner
+            .sender
+            .try_send(ClientMessage::Stream(
+                message,
+                partial_sender,
+                sender,
+                tracing::info_span!("send_stream"),
+            ))
+            .map_err(std::io::Error::from)?;
+        Ok(ResponseStream::new(
+            msg_id,
+            partials,
+            receiver,
+            self.inner.control.clone(),
+        ))
+    }
+
+    /// Sends a partial result of a streaming call request being handled.
+    ///
+    /// Partial results are sent to the peer before the final response.
+    /// Fails with `WouldBlock` if the message queue is full.
+    #[instrument(skip(self, value))]
+    pub fn send_partial<T: Serialize>(&self, request: &Id, value: &T) -> std::io::Result<()> {
+        let params = PartialParams {
+            id: request.clone(),
+            value,
+        };
+        let message = builder::new_event::<M, _>(PARTIAL_METHOD, Some(&params))?.build();
+        self.inner.outbox.try_send(message).map_err(|err| {
+            if !err.is_full() {
+                // Outbox is not polled for an end, close the connection on a disconnection.
+                let _ = self.inner.closing.try_send(Closing::Close);
+            }
+            err.into()
+        })
+    }
+
     /// Sends an event message to a network channel.
+    ///
+    /// Fails with `WouldBlock` if the message queue is full.
     #[instrument(skip(self, params))]
     pub fn send_notification<T: Serialize>(
         &self,
@@ -206,6 +392,24 @@ impl<M: Message, U> Handle<M, U> {
         params: Option<&T>,
     ) -> std::io::Result<()> {
         // let message = Message::new_event(method, params)?;
+        let message = builder::new_event::<M, T>(method, params)?.build();
+        tracing::info!("sending event");
+        self.inner.sender.try_send(ClientMessage::Request(
+            message,
+            None,
+            tracing::info_span!("send_notification"),
+        ))?;
+        Ok(())
+    }
+
+    /// Sends an event message to a network channel,
+    /// waiting for space in the message queue.
+    #[instrument(skip(self, params))]
+    pub async fn send_notification_async<T: Serialize>(
+        &self,
+        method: &str,
+        params: Option<&T>,
+    ) -> std::io::Result<()> {
         let message = builder::new_event::<M, T>(method, params)?.build();
         tracing::info!("sending event");
         self.inner
@@ -215,27 +419,29 @@ impl<M: Message, U> Handle<M, U> {
                 None,
                 tracing::info_span!("send_notification"),
             ))
-            .map_err(|_err| Error::from(ErrorKind::ConnectionReset))?;
+            .await?;
         Ok(())
     }
 
     /// Sends a method call request to a network channel.
     /// Does not await for response, instead returns a receiver handle.
     /// Includes `u64` request ID to provide ability to cancel requests.
-    #[instrument(skip(self, params))]
-    fn send_request<T: Serialize>(
+    ///
+    /// Waits for space in the message queue.
+    #[instrument(skip(self, params, metadata))]
+    async fn send_request<T: Serialize>(
         &self,
         method: &str,
         params: Option<&T>,
+        metadata: Metadata,
     ) -> Result<(Id, ResponseReceiver<M>)> {
         // Create a oneshot response channel.
         let (sender, receiver) = channel();
-        // Increment request ID and get previous value.
-        let msg_id = self.inner.requests.fetch_add(1, Ordering::SeqCst);
-        // Convert message ID to a string.
-        let msg_id = Id::Str(msg_id.to_string());
+        let msg_id = self.next_id();
         // Create a protocol message.
-        let message = builder::new_request::<M, T>(msg_id.clone(), method, params)?.build();
+        let message = builder::new_request::<M, T>(msg_id.clone(), method, params)?
+            .with_metadata(metadata)
+            .build();
         tracing::info!("sending to channel");
         // Send message to a client channel.
         self.inner
@@ -245,12 +451,24 @@ impl<M: Message, U> Handle<M, U> {
                 Some(sender),
                 tracing::info_span!("send"),
             ))
-            .map_err(|_| Error::from(ErrorKind::ConnectionReset))?;
+            .await
+            .map_err(Error::from)?;
         Ok((msg_id, receiver))
     }
 
     /// Sends a protocol message to a channel.
+    ///
+    /// Fails with `WouldBlock` if the message queue is full.
     pub fn send(&self, message: M) -> std::io::Result<()> {
+        Ok(self.inner.sender.try_send(ClientMessage::Request(
+            message,
+            None,
+            tracing::Span::current(),
+        ))?)
+    }
+
+    /// Sends a protocol message to a channel, waiting for space in the message queue.
+    pub async fn send_async(&self, message: M) -> std::io::Result<()> {
         Ok(self
             .inner
             .sender
@@ -259,16 +477,37 @@ impl<M: Message, U> Handle<M, U> {
                 None,
                 tracing::Span::current(),
             ))
-            .map_err(|_err| Error::from(ErrorKind::ConnectionReset))?)
+            .await?)
     }
 
-    /// Closes the connection.
-    pub(crate) fn close(&self) -> std::io::Result<()> {
-        Ok(self
-            .inner
-            .sender
-            .send(ClientMessage::Close)
-            .map_err(|_err| Error::from(ErrorKind::ConnectionReset))?)
+    /// Closes the connection immediately.
+    ///
+    /// Responses to requests in flight and queued messages are lost,
+    /// use [`shutdown`] to close the connection gracefully.
+    /// Connection loop returns [`Disconnect::Closed`], reconnecting clients
+    /// do not reconnect afterwards.
+    ///
+    /// [`shutdown`]: #method.shutdown
+    /// [`Disconnect::Closed`]: ../enum.Disconnect.html#variant.Closed
+    pub fn close(&self) -> std::io::Result<()> {
+        Ok(self.inner.closing.try_send(Closing::Close)?)
+    }
+
+    /// Closes the connection gracefully.
+    ///
+    /// Connection stops handling new remote requests, waits up to `timeout`
+    /// for responses to requests in flight, responses of the peer to pending
+    /// requests and queued messages, flushes and closes the channel.
+    /// Reconnecting clients do not reconnect afterwards.
+    ///
+    /// Returns reason of the disconnection. It should not be awaited by a handler
+    /// of the same connection while it handles a remote message.
+    pub async fn shutdown(&self, timeout: Duration) -> std::io::Result<Disconnect> {
+        let (shutdown, receiver) = Shutdown::new(timeout);
+        self.inner.closing.try_send(Closing::Shutdown(shutdown))?;
+        receiver
+            .await
+            .map_err(|_err| Error::from(ErrorKind::ConnectionReset))
     }
 
     fn unowned(mut self) -> Self {
@@ -280,11 +519,77 @@ impl<M: Message, U> Handle<M, U> {
     }
 }
 
+#[async_trait]
+impl<M: Message, U: Send> Requester for Handle<M, U> {
+    async fn request_opt<V, R>(&self, method: &str, params: Option<&V>) -> Result<Option<R>>
+    where
+        V: Serialize + Sync,
+        R: DeserializeOwned + Send,
+    {
+        Handle::request_opt(self, method, params).await
+    }
+}
+
+/// Receives a response to a request within the timeout.
+///
+/// Request is cancelled if the response is not received in time.
+pub(crate) async fn receive_response<M>(
+    control: &QueueSender<ClientMessage<M>>,
+    id: Id,
+    receiver: ResponseReceiver<M>,
+    request_timeout: Duration,
+) -> Result<M> {
+    let pending = PendingRequest {
+        control,
+        id: Some(id),
+    };
+    match timeout(request_timeout, receiver).await {
+        Ok(response) => {
+            pending.complete();
+            match response.map_err(|_err| Error::from(ErrorKind::ConnectionReset))? {
+                Ok(message) => Ok(message),
+                Err(err) => Err(CallError::Rpc(err)),
+            }
+        }
+        // Pending request is cancelled when dropped.
+        Err(_) => Err(CallError::from(ErrorKind::TimedOut)),
+    }
+}
+
+/// Request awaiting a response.
+///
+/// Cancels the request when dropped before the response is received,
+/// e.g. on a timeout or when the request future is dropped.
+struct PendingRequest<'a, M> {
+    control: &'a QueueSender<ClientMessage<M>>,
+    id: Option<Id>,
+}
+
+impl<M> PendingRequest<'_, M> {
+    /// Marks the request as answered.
+    fn complete(mut self) {
+        self.id = None;
+    }
+}
+
+impl<M> Drop for PendingRequest<'_, M> {
+    fn drop(&mut self) {
+        if let Some(id) = self.id.take() {
+            // Client loop is gone if sending fails, there is nothing to cancel.
+            let _ = self.control.try_send(ClientMessage::Cancel(id));
+        }
+    }
+}
+
 impl<M: Message, U> Drop for Handle<M, U> {
     fn drop(&mut self) {
         if self.is_owned {
             let instances = self.inner.instances.fetch_sub(1, Ordering::SeqCst);
             log::trace!("handle dropped; left={}", instances - 1);
+            if instances == 1 {
+                // Wake the client loop to close the connection.
+                let _ = self.inner.closing.try_send(Closing::Close);
+            }
         }
     }
 }
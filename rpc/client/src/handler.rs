@@ -16,20 +16,32 @@ use std::{
 use async_trait::async_trait;
 use futures::stream::Stream;
 use pin_project::pin_project;
-use tokio::sync::mpsc::UnboundedReceiver;
 
 use net3_msg::{
     prelude::*,
-    types::{Id, MessageKind},
+    types::{Id, InvalidReason, MessageKind},
 };
-use net3_rpc_conn::LoopHandler;
+use net3_rpc_conn::{
+    Cancellation, Closing, Closings, Handling, LoopHandler, Outbox, Overflow, QueueConfig,
+    DEFAULT_MAX_READ_AHEAD,
+};
+use serde::de::IgnoredAny;
 
-use crate::{handle::HandleRef, traits::Handler};
+use crate::{
+    deadline,
+    handle::HandleRef,
+    id::response_key,
+    queue::QueueReceiver,
+    stream::{PartialParams, PARTIAL_METHOD},
+    traits::Handler,
+};
 
 pub(crate) mod internal {
     use net3_msg::types::{Error, Id};
     use tokio::sync::oneshot::{Receiver, Sender};
 
+    use crate::queue::QueueSender;
+
     /// Network channel client response `Result` type.
     pub type Response<M> = Result<M, Error>;
 
@@ -39,53 +51,177 @@ pub(crate) mod internal {
     /// Response result oneshot receiver type.
     pub type ResponseReceiver<M> = Receiver<Response<M>>;
 
+    /// Sender of partial results of a streaming call.
+    pub type StreamSender<M> = QueueSender<M>;
+
     /// Client message with optional response sender and span.
     pub enum ClientMessage<M> {
-        /// Close request.
-        Close,
-
         /// Cancellation of a request ID.
-        /// Sent by [`Handle.request_timeout`] after time-out fires.
+        /// Sent on the control queue when a request times out or its future is dropped.
         Cancel(Id),
 
         /// Request to send a message to the channel.
         /// Includes optional [`ResponseSender`] and `u64` request ID.
         Request(M, Option<ResponseSender<M>>, tracing::Span),
+
+        /// Request of a streaming call.
+        /// Includes [`StreamSender`] of partial results and [`ResponseSender`]
+        /// of the final response.
+        Stream(M, StreamSender<M>, ResponseSender<M>, tracing::Span),
+
+        /// Batch of requests and notifications to send in a single frame.
+        /// Includes optional [`ResponseSender`] of each message.
+        Batch(Vec<(M, Option<ResponseSender<M>>)>, tracing::Span),
     }
 }
 
-use self::internal::{ClientMessage, ResponseSender};
+use self::internal::{ClientMessage, Response, ResponseSender, StreamSender};
 
 /// Response oneshot sender with span.
 type SpannedSender<M> = (ResponseSender<M>, tracing::Span);
 
+/// Senders of partial results and the final response of a streaming call.
+type StreamSenders<M> = (StreamSender<M>, ResponseSender<M>);
+
 /// Network channel client service handler.
 #[pin_project]
 pub(crate) struct ClientHandler<H: Handler> {
     #[pin]
     receiver: ClonedReceiver<ClientMessage<<H as Handler>::Message>>,
+    /// Control messages, received before other messages.
+    #[pin]
+    control: ClonedReceiver<ClientMessage<<H as Handler>::Message>>,
     handler: H,
     handle: HandleRef<<H as Handler>::Message, <H as Handler>::Event>,
+    /// Pending requests by normalized identifiers.
     requests: HashMap<Id, SpannedSender<<H as Handler>::Message>>,
-    pending_requests: usize,
+    /// Pending streaming calls by normalized identifiers.
+    streams: HashMap<Id, StreamSenders<<H as Handler>::Message>>,
     /// Counter of client instances.
     client_handles: Arc<AtomicU64>,
+    /// Remote cancellation of requests, if enabled.
+    cancellation: Option<Cancellation>,
+    /// Messages sent while a remote message is handled, until taken by the loop.
+    outbox: Option<ClonedReceiver<<H as Handler>::Message>>,
+    /// Limit of requests handled concurrently, if enabled.
+    max_concurrent_requests: Option<usize>,
+    /// Closing requests sent with handles, until taken by the loop.
+    closings: Option<ClonedReceiver<Closing>>,
+    /// Queue of frames read ahead while a remote message is handled.
+    read_ahead_queue: QueueConfig,
 }
 
 impl<H: Handler> ClientHandler<H> {
     pub fn new(
         rx: ClonedReceiver<ClientMessage<<H as Handler>::Message>>,
+        control: ClonedReceiver<ClientMessage<<H as Handler>::Message>>,
         handler: H,
         handle: HandleRef<<H as Handler>::Message, <H as Handler>::Event>,
-        client_handles: Arc<AtomicU64>,
+        cancellation: Option<Cancellation>,
+        outbox: ClonedReceiver<<H as Handler>::Message>,
+        closings: ClonedReceiver<Closing>,
     ) -> Self {
+        // Counter of owned handles is shared with the builder.
+        let client_handles = (*handle).inner.instances.clone();
         ClientHandler {
             receiver: rx,
+            control,
             requests: HashMap::default(),
+            streams: HashMap::default(),
             handler,
             handle,
-            pending_requests: 0,
             client_handles,
+            cancellation,
+            outbox: Some(outbox),
+            max_concurrent_requests: None,
+            closings: Some(closings),
+            read_ahead_queue: QueueConfig::bounded(DEFAULT_MAX_READ_AHEAD, Overflow::Wait),
+        }
+    }
+
+    /// Sets limit of requests handled concurrently.
+    pub fn with_concurrent_requests(mut self, limit: Option<usize>) -> Self {
+        assert!(
+            limit.is_none() || self.handler.fork().is_some(),
+            "handler has to implement `Handler::fork` to handle requests concurrently"
+        );
+        self.max_concurrent_requests = limit;
+        self
+    }
+
+    /// Sets queue of frames read ahead while a remote message is handled.
+    pub fn with_read_ahead_queue(mut self, config: QueueConfig) -> Self {
+        self.read_ahead_queue = config;
+        self
+    }
+}
+
+impl<H: Handler> ClientHandler<H> {
+    /// Handles a message that failed validation.
+    ///
+    /// Invalid requests are answered with an `InvalidRequest` error response.
+    /// Invalid responses are delivered to a pending request, if any.
+    /// They are never answered to avoid exchanging errors with the peer in a loop.
+    fn handle_invalid_message(
+        &mut self,
+        message: <H as Handler>::Message,
+        reason: InvalidReason,
+    ) -> Vec<<H as Handler>::Message> {
+        tracing::warn!(%reason, "received invalid message");
+        let is_response = message.error_kind().is_some()
+            || reason == InvalidReason::ResultAndError
+            || (message.method().is_none()
+                && reason != InvalidReason::MissingMethod
+                && reason != InvalidReason::UndefinedKind);
+        if !is_response {
+            return vec![builder::new_error_response(&message, reason.into()).build()];
+        }
+        if let Some((sender, _)) = self.requests.remove(&*response_key(message.id())) {
+            if sender.send(Err(reason.into())).is_err() {
+                tracing::error!("could not send value");
+            }
+        } else {
+            self.respond_stream(message.id(), Err(reason.into()));
+        }
+        vec![]
+    }
+
+    /// Delivers a final response to a pending streaming call.
+    ///
+    /// Returns false if there is no such call.
+    fn respond_stream(&mut self, id: &Id, response: Response<<H as Handler>::Message>) -> bool {
+        match self.streams.remove(&*response_key(id)) {
+            Some((_, sender)) => {
+                if sender.send(response).is_err() {
+                    tracing::debug!("streaming call receiver dropped");
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Delivers a partial result notification to a pending streaming call.
+    fn handle_partial(&mut self, message: <H as Handler>::Message) {
+        let id = match message.read::<PartialParams<IgnoredAny>>() {
+            Ok(partial) => partial.id,
+            Err(err) => {
+                tracing::warn!(%err, "invalid partial result");
+                return;
+            }
+        };
+        let key = response_key(&id);
+        match self.streams.get(&*key) {
+            Some((sender, _)) => match sender.try_send(message) {
+                Ok(()) => (),
+                Err(err) if err.is_full() => tracing::debug!("partial result queue is full"),
+                Err(_) => {
+                    // Receiver is dropped, cancellation is on its way,
+                    // or the queue overflowed and the call fails.
+                    self.streams.remove(&*key);
+                }
+            },
+            None => tracing::warn!("streaming call not found (possible receive after cancel)"),
         }
     }
 }
@@ -93,7 +229,8 @@ impl<H: Handler> ClientHandler<H> {
 #[async_trait]
 impl<H> LoopHandler for ClientHandler<H>
 where
-    H: Handler + Send,
+    H: Handler + Send + 'static,
+    <H as Handler>::Message: 'static,
 {
     type InternalEvent = <H as Handler>::Event;
     type RemoteMessage = <H as Handler>::Message;
@@ -107,45 +244,149 @@ where
         self.handler.handle_internal_event(event).await
     }
 
+    fn cancellation(&self) -> Option<Cancellation> {
+        self.cancellation.clone()
+    }
+
+    fn take_outbox(&mut self) -> Option<Outbox<Self::RemoteMessage>> {
+        self.outbox
+            .take()
+            .map(|outbox| Box::pin(outbox) as Outbox<Self::RemoteMessage>)
+    }
+
+    fn max_concurrent_requests(&self) -> Option<usize> {
+        self.max_concurrent_requests
+    }
+
+    fn read_ahead_queue(&self) -> QueueConfig {
+        self.read_ahead_queue
+    }
+
+    /// Handles a request or a notification with a forked handler.
+    ///
+    /// Responses, partial results and cancellations are delivered in place.
+    fn handle_concurrently(
+        &mut self,
+        message: Self::RemoteMessage,
+    ) -> std::result::Result<Handling<Self::RemoteMessage>, Self::RemoteMessage> {
+        if self.max_concurrent_requests.is_none() || message.validate().is_err() {
+            return Err(message);
+        }
+        let concurrent = match message.kind() {
+            MessageKind::Request => true,
+            MessageKind::Event => {
+                message.method() != Some(PARTIAL_METHOD)
+                    && self
+                        .cancellation
+                        .as_ref()
+                        .is_none_or(|cancellation| cancellation.cancelled_id(&message).is_none())
+            }
+            _ => false,
+        };
+        if !concurrent {
+            return Err(message);
+        }
+        let mut handler = match self.handler.fork() {
+            Some(handler) => handler,
+            None => return Err(message),
+        };
+        if message.kind() == MessageKind::Event {
+            return Ok(Box::pin(async move {
+                handler.handle_notification(message).await
+            }));
+        }
+        let cancellation = self.cancellation.clone();
+        let handling: Handling<Self::RemoteMessage> = Box::pin(async move {
+            let timeout = message.timeout();
+            let id = message.id().clone();
+            let handling = deadline::handle(timeout, handler.handle_request(message));
+            match cancellation {
+                Some(cancellation) => cancellation.run(id, handling).await,
+                None => handling.await,
+            }
+        });
+        Ok(handling)
+    }
+
+    /// Returns number of requests and streaming calls awaiting responses.
+    fn pending_requests(&self) -> usize {
+        self.requests.len() + self.streams.len()
+    }
+
+    fn take_closings(&mut self) -> Option<Closings> {
+        self.closings
+            .take()
+            .map(|closings| Box::pin(closings) as Closings)
+    }
+
     /// Handles event message.
     #[inline]
     async fn handle_remote_message(
         &mut self,
         message: Self::RemoteMessage,
     ) -> Result<Vec<Self::RemoteMessage>> {
+        if let Err(reason) = message.validate() {
+            return Ok(self.handle_invalid_message(message, reason));
+        }
         match message.kind() {
             MessageKind::Undefined => {
                 Err(Error::new(ErrorKind::InvalidData, "undefined message kind"))
             }
-            MessageKind::Event => self.handler.handle_notification(message).await,
-            MessageKind::Request => self.handler.handle_request(message).await,
+            MessageKind::Event if message.method() == Some(PARTIAL_METHOD) => {
+                self.handle_partial(message);
+                Ok(vec![])
+            }
+            MessageKind::Event => match self.cancellation.as_ref() {
+                Some(cancellation) => match cancellation.cancelled_id(&message) {
+                    Some(id) => {
+                        cancellation.cancel(&id);
+                        Ok(vec![])
+                    }
+                    None => self.handler.handle_notification(message).await,
+                },
+                None => self.handler.handle_notification(message).await,
+            },
+            MessageKind::Request => {
+                let timeout = message.timeout();
+                let id = message.id().clone();
+                let handling = deadline::handle(timeout, self.handler.handle_request(message));
+                match self.cancellation.as_ref() {
+                    Some(cancellation) => cancellation.run(id, handling).await,
+                    None => handling.await,
+                }
+            }
             MessageKind::Response => {
                 tracing::info!("handle_call_response");
-                if let Some((sender, span)) = self.requests.remove(message.id()) {
+                if let Some((sender, span)) = self.requests.remove(&*response_key(message.id())) {
                     let span = tracing::info_span!(parent: &span, "response");
                     let _enter = span.enter();
                     tracing::info!("received response");
                     if sender.send(Ok(message)).is_err() {
                         tracing::error!("could not send message");
                     }
-                } else {
+                } else if !self.respond_stream(&message.id().clone(), Ok(message)) {
                     tracing::warn!("response handler not found");
                 }
                 Ok(vec![])
             }
             MessageKind::ErrorResponse => {
                 tracing::info!("handle_call_error");
-                if let Some((sender, span)) = self.requests.remove(message.id()) {
+                if let Some((sender, span)) = self.requests.remove(&*response_key(message.id())) {
                     let span = tracing::info_span!(parent: &span, "response");
                     let _enter = span.enter();
                     tracing::info!("received error");
-                    self.pending_requests -= 1;
                     let err = message.into_error().expect("error");
                     if sender.send(Err(err)).is_err() {
                         tracing::error!("could not send value");
                     }
                 } else {
-                    tracing::warn!("response handler not found (possible receive after timeout)");
+                    let id = message.id().clone();
+                    let err = message.into_error().expect("error");
+                    if !self.respond_stream(&id, Err(err)) {
+                        tracing::warn!(
+                            "response handler not found (possible receive after timeout)"
+                        );
+                    }
                 }
                 Ok(vec![])
             }
@@ -154,47 +395,79 @@ where
 }
 
 impl<H: Handler + 'static> Stream for ClientHandler<H> {
-    type Item = Result<<H as Handler>::Message>;
+    type Item = Result<Batch<<H as Handler>::Message>>;
 
     #[inline]
     fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
-        let project = self.project();
-        match project.receiver.poll_next(cx) {
-            Poll::Ready(Some(ClientMessage::Close)) => Poll::Ready(Some(Err(Error::new(
-                ErrorKind::ConnectionAborted,
-                "requested close",
-            )))),
-            Poll::Ready(Some(ClientMessage::Cancel(request))) => {
-                // Remove pending request
-                project.requests.remove(&request);
-                // continue polling
-                cx.waker().wake_by_ref();
-                Poll::Pending
-            }
-            Poll::Ready(Some(ClientMessage::Request(message, sender, span))) => {
-                let _enter = span.enter();
-                if let Some(sender) = sender {
-                    // Insert response handler to requests map.
-                    project.requests.insert(
-                        message.id().clone(),
-                        (sender, tracing::info_span!(parent: &span, "request")),
-                    );
-                    *project.pending_requests += 1;
-                    tracing::info!("sending request");
-                } else {
-                    tracing::info!("sending notification");
+        let mut project = self.project();
+        // Cancellations of answered requests are skipped.
+        loop {
+            let message = match project.control.as_mut().poll_next(cx) {
+                Poll::Ready(Some(message)) => Poll::Ready(Some(message)),
+                // Control queue is unbounded, it does not end while the builder holds a sender.
+                _ => project.receiver.as_mut().poll_next(cx),
+            };
+            return match message {
+                Poll::Ready(Some(ClientMessage::Cancel(request))) => {
+                    // Remove pending request
+                    let key = response_key(&request);
+                    let pending = project.requests.remove(&*key).is_some()
+                        || project.streams.remove(&*key).is_some();
+                    // Let the peer know if the request was not answered yet.
+                    if let (true, Some(cancellation)) = (pending, project.cancellation.as_ref()) {
+                        return Poll::Ready(Some(
+                            cancellation.notification(request).map(Batch::Single),
+                        ));
+                    }
+                    continue;
                 }
-                Poll::Ready(Some(Ok(message)))
-            }
-            Poll::Ready(None) => Poll::Ready(None),
-            Poll::Pending => {
-                if project.client_handles.load(Ordering::SeqCst) == 0 {
-                    log::trace!("client handles are all dropped");
-                    Poll::Ready(None)
-                } else {
-                    Poll::Pending
+                Poll::Ready(Some(ClientMessage::Request(message, sender, span))) => {
+                    let _enter = span.enter();
+                    if let Some(sender) = sender {
+                        // Insert response handler to requests map.
+                        project.requests.insert(
+                            response_key(message.id()).into_owned(),
+                            (sender, tracing::info_span!(parent: &span, "request")),
+                        );
+                        tracing::info!("sending request");
+                    } else {
+                        tracing::info!("sending notification");
+                    }
+                    Poll::Ready(Some(Ok(Batch::Single(message))))
                 }
-            }
+                Poll::Ready(Some(ClientMessage::Stream(message, partials, sender, span))) => {
+                    let _enter = span.enter();
+                    project
+                        .streams
+                        .insert(response_key(message.id()).into_owned(), (partials, sender));
+                    tracing::info!("sending streaming request");
+                    Poll::Ready(Some(Ok(Batch::Single(message))))
+                }
+                Poll::Ready(Some(ClientMessage::Batch(batch, span))) => {
+                    let _enter = span.enter();
+                    let mut messages = Vec::with_capacity(batch.len());
+                    for (message, sender) in batch {
+                        if let Some(sender) = sender {
+                            project.requests.insert(
+                                response_key(message.id()).into_owned(),
+                                (sender, tracing::info_span!(parent: &span, "request")),
+                            );
+                        }
+                        messages.push(message);
+                    }
+                    tracing::info!(len = messages.len(), "sending batch");
+                    Poll::Ready(Some(Ok(Batch::Many(messages))))
+                }
+                Poll::Ready(None) => Poll::Ready(None),
+                Poll::Pending => {
+                    if project.client_handles.load(Ordering::SeqCst) == 0 {
+                        log::trace!("client handles are all dropped");
+                        Poll::Ready(None)
+                    } else {
+                        Poll::Pending
+                    }
+                }
+            };
         }
     }
 }
@@ -202,7 +475,10 @@ impl<H: Handler + 'static> Stream for ClientHandler<H> {
 impl<H: Handler> Debug for ClientHandler<H> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("ClientHandler")
-            .field("pending_requests", &self.pending_requests)
+            .field(
+                "pending_requests",
+                &(self.requests.len() + self.streams.len()),
+            )
             .finish()
     }
 }
@@ -217,7 +493,7 @@ impl<H: Handler> Debug for ClientHandler<H> {
 #[pin_project]
 pub(crate) struct ClonedReceiver<M> {
     #[pin]
-    inner: Arc<RefCell<UnboundedReceiver<M>>>,
+    inner: Arc<RefCell<QueueReceiver<M>>>,
 }
 
 unsafe impl<M> Send for ClonedReceiver<M> {}
@@ -240,8 +516,8 @@ impl<M> Clone for ClonedReceiver<M> {
     }
 }
 
-impl<M> From<UnboundedReceiver<M>> for ClonedReceiver<M> {
-    fn from(inner: UnboundedReceiver<M>) -> Self {
+impl<M> From<QueueReceiver<M>> for ClonedReceiver<M> {
+    fn from(inner: QueueReceiver<M>) -> Self {
         ClonedReceiver {
             inner: Arc::new(RefCell::new(inner)),
         }
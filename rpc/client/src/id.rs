@@ -0,0 +1,86 @@
+//! Request ID generation strategies.
+
+use std::{borrow::Cow, fmt, sync::Arc};
+
+use net3_msg::types::Id;
+
+/// Custom request ID generator function.
+///
+/// It receives a value of the client request counter.
+pub type IdGeneratorFn = Arc<dyn Fn(u64) -> Id + Send + Sync>;
+
+/// Request ID generation strategy set with [`with_id_generator`].
+///
+/// [`with_id_generator`]: ../builder/struct.Builder.html#method.with_id_generator
+#[derive(Clone, Default)]
+pub enum IdGenerator {
+    /// Numerical identifiers from a request counter.
+    Numeric,
+
+    /// String identifiers from a request counter.
+    ///
+    /// This is the default strategy.
+    #[default]
+    String,
+
+    /// Random UUID v4 string identifiers.
+    Uuid,
+
+    /// Identifiers created by a custom function.
+    Custom(IdGeneratorFn),
+}
+
+impl IdGenerator {
+    /// Creates a generator from a custom function.
+    pub fn custom<F>(func: F) -> Self
+    where
+        F: Fn(u64) -> Id + Send + Sync + 'static,
+    {
+        IdGenerator::Custom(Arc::new(func))
+    }
+
+    /// Creates a new request identifier from a value of the request counter.
+    #[inline]
+    pub fn generate(&self, counter: u64) -> Id {
+        match self {
+            IdGenerator::Numeric => Id::Num(counter),
+            IdGenerator::String => Id::Str(counter.to_string()),
+            IdGenerator::Uuid => Id::Str(uuid::Uuid::new_v4().to_string()),
+            IdGenerator::Custom(func) => func(counter),
+        }
+    }
+}
+
+impl fmt::Debug for IdGenerator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IdGenerator::Numeric => f.write_str("Numeric"),
+            IdGenerator::String => f.write_str("String"),
+            IdGenerator::Uuid => f.write_str("Uuid"),
+            IdGenerator::Custom(_) => f.write_str("Custom"),
+        }
+    }
+}
+
+/// Returns identifier used to match responses to requests.
+///
+/// Peers can echo numerical identifiers as strings and the other way around,
+/// so strings holding a canonical unsigned integer are matched as numbers.
+#[inline]
+pub fn response_key(id: &Id) -> Cow<'_, Id> {
+    match id {
+        Id::Str(value) if is_canonical_number(value) => match value.parse() {
+            Ok(num) => Cow::Owned(Id::Num(num)),
+            Err(_) => Cow::Borrowed(id),
+        },
+        id => Cow::Borrowed(id),
+    }
+}
+
+/// Returns true if string is an unsigned integer without leading zeros.
+#[inline]
+fn is_canonical_number(value: &str) -> bool {
+    !value.is_empty()
+        && value.bytes().all(|byte| byte.is_ascii_digit())
+        && (value.len() == 1 || !value.starts_with('0'))
+}
@@ -1,36 +1,67 @@
 //! Network channel client implementing request-reply and notifications.
 //!
-//! Client implements request to reply mapping using only string IDs at the moment.
-//! String IDs are most frequently the only supported way of calling methods.
+//! Client implements request to reply mapping using string IDs by default.
+//! Other request ID strategies can be set using [`IdGenerator`].
 //!
 //! Clients are created using [`Builder`] and can be spawned in the background using [`spawn`] method.
 //!
 //! Interface to the client is provided by [`Handle`] structure.
 //!
-//! Client [`Handle`] sends requests and notifications to [`UnboundedSender`] channel.
+//! Client [`Handle`] sends requests and notifications to a message queue.
+//! Queues are unbounded by default, see [`queue`] module for bounded queues and overflow policies.
 //! Requests IDs are registered and received responses are send to requestee using oneshot [`Sender`].
 //!
+//! Request deadlines can be propagated to the server, see [`deadline`] module.
+//! Streaming calls yielding partial results are described in [`stream`] module.
+//! Several calls can be sent in a single frame, see [`batch`] module.
+//!
 //! This library is intended to provide only a low-level access to network channel service.
 //!
 //! All APIs in this library are highly experimental and are subject to change.
 //!
 //! [`Handle`]: handle/struct.Handle.html
+//! [`IdGenerator`]: id/enum.IdGenerator.html
+//! [`batch`]: batch/index.html
+//! [`deadline`]: deadline/index.html
+//! [`queue`]: queue/index.html
+//! [`stream`]: stream/index.html
 //! [`spawn`]: builder/struct.Builder.html#method.spawn
 //! [`Channel`]: ../channel/struct.Channel.html
-//! [`UnboundedSender`]: https://docs.rs/tokio/0.2/tokio/sync/mpsc/struct.UnboundedSender.html
 //! [`Sender`]: https://docs.rs/tokio/0.2/tokio/sync/oneshot/struct.Sender.html
 
+#[macro_use]
+extern crate serde_derive;
+
+pub mod batch;
 pub mod builder;
 pub mod common;
+pub mod deadline;
 pub mod handle;
 pub(crate) mod handler;
+pub mod id;
 pub mod notifications;
+pub mod queue;
+pub mod stream;
 pub mod traits;
 
+pub use self::batch::{BatchCall, BatchRequest};
 pub use self::builder::Builder as ClientBuilder;
 pub use self::builder::*;
 pub use self::handle::*;
+pub use self::id::IdGenerator;
 pub use self::notifications::*;
+pub use self::queue::{Overflow, QueueConfig, QueueReceiver, QueueSender, SendError};
+pub use self::stream::{PartialParams, ResponseStream, PARTIAL_METHOD};
 pub use self::traits::*;
 
+pub use net3_codec_limits::{LimitError, LimitedCodec, Limits};
+pub use net3_rpc_conn::cancel::{CancelParams, DEFAULT_METHOD as DEFAULT_CANCEL_METHOD};
+pub use net3_rpc_conn::Disconnect;
 pub use net3_rpc_error::*;
+
+/// Used by code generated with `#[rpc]` attribute.
+#[doc(hidden)]
+pub use serde;
+
+#[cfg(test)]
+mod tests;
@@ -6,9 +6,12 @@ use std::{
 };
 
 use async_trait::async_trait;
-use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender};
 
-use crate::{handle::Handle, traits::Handler};
+use crate::{
+    handle::Handle,
+    queue::{QueueReceiver, QueueSender, SendError},
+    traits::Handler,
+};
 
 use net3_msg::traits::Message;
 
@@ -17,11 +20,11 @@ pub struct Notifications<M: Message, T = M, U = ()> {
     /// Client handle.
     pub handle: Handle<M, U>,
     /// Notifications receiver.
-    pub receiver: UnboundedReceiver<T>,
+    pub receiver: QueueReceiver<T>,
 }
 
 impl<M: Message, T> Deref for Notifications<M, T> {
-    type Target = UnboundedReceiver<T>;
+    type Target = QueueReceiver<T>;
 
     fn deref(&self) -> &Self::Target {
         &self.receiver
@@ -35,7 +38,13 @@ impl<M: Message, T> DerefMut for Notifications<M, T> {
 }
 
 /// Notification handler for messages.
-pub struct NotificationHandler<M, T = M>(pub UnboundedSender<T>, pub std::marker::PhantomData<M>);
+///
+/// Notifications are delivered according to the queue [`Overflow`] policy,
+/// notifications rejected by a full queue are dropped.
+/// Connection is closed when the receiver is dropped or disconnected.
+///
+/// [`Overflow`]: ../queue/enum.Overflow.html
+pub struct NotificationHandler<M, T = M>(pub QueueSender<T>, pub std::marker::PhantomData<M>);
 
 #[async_trait]
 impl<M, T> Handler for NotificationHandler<M, T>
@@ -47,10 +56,14 @@ where
     type Message = M;
 
     async fn handle_notification(&mut self, message: Self::Message) -> Result<Vec<Self::Message>> {
-        self.0
-            .send(T::from(message))
-            .map(|_| vec![])
-            .map_err(|_| std::io::ErrorKind::BrokenPipe.into())
+        match self.0.send(T::from(message)).await {
+            Ok(()) => Ok(vec![]),
+            Err(SendError::Full(_)) => {
+                log::warn!("Notification queue is full, dropping notification");
+                Ok(vec![])
+            }
+            Err(SendError::Closed(_)) => Err(std::io::ErrorKind::BrokenPipe.into()),
+        }
     }
 
     async fn handle_request(&mut self, _message: Self::Message) -> Result<Vec<Self::Message>> {
@@ -65,11 +78,11 @@ where
     }
 }
 
-impl<M, T> From<UnboundedSender<T>> for NotificationHandler<M, T>
+impl<M, T> From<QueueSender<T>> for NotificationHandler<M, T>
 where
     T: From<M>,
 {
-    fn from(sink: UnboundedSender<T>) -> Self {
+    fn from(sink: QueueSender<T>) -> Self {
         NotificationHandler(sink, std::marker::PhantomData)
     }
 }
@@ -0,0 +1,365 @@
+//! Bounded queues with overflow policies.
+//!
+//! Outbound messages, internal events and notifications are delivered through
+//! queues which are unbounded by default. Bounded queues apply an [`Overflow`]
+//! policy when a value is sent to a full queue:
+//!
+//! * [`Wait`] – asynchronous sends wait for space, non-blocking sends fail with [`Full`],
+//! * [`Error`] – sends fail with [`Full`],
+//! * [`DropOldest`] – the oldest queued value is dropped to make space,
+//! * [`Disconnect`] – queued values are dropped and the send fails with [`Closed`].
+//!   Receiver observes the disconnection as an end of the queue, the queue is not reopened.
+//!
+//! Errors are converted into IO errors of `WouldBlock` and `ConnectionReset` kinds.
+//!
+//! Queues are built on `tokio` channels. Queues with the [`Wait`] and [`Error`] policies
+//! are bounded channels, other queues are unbounded channels with a limit of queued values.
+//!
+//! [`Overflow`]: enum.Overflow.html
+//! [`Wait`]: enum.Overflow.html#variant.Wait
+//! [`Error`]: enum.Overflow.html#variant.Error
+//! [`DropOldest`]: enum.Overflow.html#variant.DropOldest
+//! [`Disconnect`]: enum.Overflow.html#variant.Disconnect
+//! [`Full`]: enum.SendError.html#variant.Full
+//! [`Closed`]: enum.SendError.html#variant.Closed
+
+use std::{
+    fmt,
+    io::{Error, ErrorKind},
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc,
+    },
+    task::{Context, Poll},
+};
+
+use futures::{future::poll_fn, stream::Stream, task::AtomicWaker};
+use tokio::sync::mpsc;
+
+pub use net3_rpc_conn::{Overflow, QueueConfig};
+
+/// Error returned by a queue send.
+///
+/// Contains the value that was not sent.
+pub enum SendError<T> {
+    /// Queue is full.
+    Full(T),
+
+    /// Receiver was dropped or disconnected.
+    Closed(T),
+}
+
+impl<T> SendError<T> {
+    /// Returns the value that was not sent.
+    pub fn into_inner(self) -> T {
+        match self {
+            SendError::Full(value) | SendError::Closed(value) => value,
+        }
+    }
+
+    /// Returns `true` if the queue was full.
+    pub fn is_full(&self) -> bool {
+        matches!(self, SendError::Full(_))
+    }
+}
+
+impl<T> fmt::Debug for SendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SendError::Full(_) => f.write_str("Full(..)"),
+            SendError::Closed(_) => f.write_str("Closed(..)"),
+        }
+    }
+}
+
+impl<T> fmt::Display for SendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SendError::Full(_) => f.write_str("queue is full"),
+            SendError::Closed(_) => f.write_str("queue is closed"),
+        }
+    }
+}
+
+impl<T> std::error::Error for SendError<T> {}
+
+impl<T> From<SendError<T>> for Error {
+    fn from(err: SendError<T>) -> Self {
+        match err {
+            SendError::Full(_) => Error::new(ErrorKind::WouldBlock, "queue is full"),
+            SendError::Closed(_) => Error::from(ErrorKind::ConnectionReset),
+        }
+    }
+}
+
+/// Creates a queue with a capacity and an overflow policy.
+pub fn channel<T>(config: QueueConfig) -> (QueueSender<T>, QueueReceiver<T>) {
+    let shared = Arc::new(Shared {
+        config,
+        len: AtomicUsize::new(0),
+        evicted: AtomicUsize::new(0),
+        disconnected: AtomicBool::new(false),
+        closed: AtomicBool::new(false),
+        receiver: AtomicWaker::new(),
+    });
+    let (sender, receiver) = match (config.capacity(), config.overflow()) {
+        (Some(capacity), Overflow::Wait) | (Some(capacity), Overflow::Error) => {
+            let (sender, receiver) = mpsc::channel(capacity);
+            (Sender::Bounded(sender), Receiver::Bounded(receiver))
+        }
+        _ => {
+            let (sender, receiver) = mpsc::unbounded_channel();
+            (Sender::Unbounded(sender), Receiver::Unbounded(receiver))
+        }
+    };
+    let sender = QueueSender {
+        sender,
+        shared: shared.clone(),
+    };
+    let receiver = QueueReceiver { receiver, shared };
+    (sender, receiver)
+}
+
+/// Overflow state shared by both halves of a queue.
+struct Shared {
+    config: QueueConfig,
+    /// Number of queued values, not including evicted ones.
+    len: AtomicUsize,
+    /// Number of oldest queued values to drop on receive.
+    evicted: AtomicUsize,
+    /// Queue overflowed with the `Disconnect` policy.
+    disconnected: AtomicBool,
+    /// Receiver was dropped or closed.
+    closed: AtomicBool,
+    /// Receiver woken on a disconnection.
+    receiver: AtomicWaker,
+}
+
+enum Sender<T> {
+    Bounded(mpsc::Sender<T>),
+    Unbounded(mpsc::UnboundedSender<T>),
+}
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Self {
+        match self {
+            Sender::Bounded(sender) => Sender::Bounded(sender.clone()),
+            Sender::Unbounded(sender) => Sender::Unbounded(sender.clone()),
+        }
+    }
+}
+
+enum Receiver<T> {
+    Bounded(mpsc::Receiver<T>),
+    Unbounded(mpsc::UnboundedReceiver<T>),
+}
+
+impl<T> Receiver<T> {
+    fn poll_recv(&mut self, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        match self {
+            Receiver::Bounded(receiver) => receiver.poll_recv(cx),
+            Receiver::Unbounded(receiver) => receiver.poll_recv(cx),
+        }
+    }
+
+    fn try_recv(&mut self) -> Option<T> {
+        match self {
+            Receiver::Bounded(receiver) => receiver.try_recv().ok(),
+            Receiver::Unbounded(receiver) => receiver.try_recv().ok(),
+        }
+    }
+
+    fn close(&mut self) {
+        match self {
+            Receiver::Bounded(receiver) => receiver.close(),
+            Receiver::Unbounded(receiver) => receiver.close(),
+        }
+    }
+}
+
+/// Sending half of a queue.
+pub struct QueueSender<T> {
+    sender: Sender<T>,
+    shared: Arc<Shared>,
+}
+
+impl<T> QueueSender<T> {
+    /// Sends a value without waiting, applying the overflow policy if the queue is full.
+    ///
+    /// Fails with [`Full`] if the queue is full and its policy is [`Wait`] or [`Error`].
+    ///
+    /// [`Full`]: enum.SendError.html#variant.Full
+    /// [`Wait`]: enum.Overflow.html#variant.Wait
+    /// [`Error`]: enum.Overflow.html#variant.Error
+    pub fn try_send(&self, value: T) -> Result<(), SendError<T>> {
+        if self.is_closed() {
+            return Err(SendError::Closed(value));
+        }
+        let sender = match &self.sender {
+            // Bounded channel applies the capacity on its own.
+            Sender::Bounded(sender) => {
+                self.shared.len.fetch_add(1, Ordering::SeqCst);
+                return sender.clone().try_send(value).map_err(|err| {
+                    self.shared.len.fetch_sub(1, Ordering::SeqCst);
+                    match err {
+                        mpsc::error::TrySendError::Full(value) => SendError::Full(value),
+                        mpsc::error::TrySendError::Closed(value) => SendError::Closed(value),
+                    }
+                });
+            }
+            Sender::Unbounded(sender) => sender,
+        };
+        let counter = if self.shared.config.is_full(self.len()) {
+            if self.shared.config.overflow() == Overflow::Disconnect {
+                log::debug!("Queue overflow, disconnecting");
+                self.shared.disconnected.store(true, Ordering::SeqCst);
+                self.shared.receiver.wake();
+                return Err(SendError::Closed(value));
+            }
+            // Receiver drops the oldest value in place of this one.
+            &self.shared.evicted
+        } else {
+            &self.shared.len
+        };
+        counter.fetch_add(1, Ordering::SeqCst);
+        sender.send(value).map_err(|err| {
+            // Queue was closed meanwhile, the value is not queued.
+            counter.fetch_sub(1, Ordering::SeqCst);
+            SendError::Closed(err.0)
+        })
+    }
+
+    /// Sends a value, waiting for space if the queue is full and its policy is [`Wait`].
+    ///
+    /// Other policies are applied as in [`try_send`].
+    ///
+    /// [`Wait`]: enum.Overflow.html#variant.Wait
+    /// [`try_send`]: #method.try_send
+    pub async fn send(&self, value: T) -> Result<(), SendError<T>> {
+        let mut sender = match (&self.sender, self.shared.config.overflow()) {
+            (Sender::Bounded(sender), Overflow::Wait) => sender.clone(),
+            _ => return self.try_send(value),
+        };
+        if self.is_closed() {
+            return Err(SendError::Closed(value));
+        }
+        self.shared.len.fetch_add(1, Ordering::SeqCst);
+        sender.send(value).await.map_err(|err| {
+            self.shared.len.fetch_sub(1, Ordering::SeqCst);
+            SendError::Closed(err.0)
+        })
+    }
+
+    /// Returns number of queued values.
+    pub fn len(&self) -> usize {
+        self.shared.len.load(Ordering::SeqCst)
+    }
+
+    /// Returns `true` if there are no queued values.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns `true` if the receiver was dropped or disconnected.
+    pub fn is_closed(&self) -> bool {
+        self.shared.closed.load(Ordering::SeqCst) || self.shared.disconnected.load(Ordering::SeqCst)
+    }
+
+    /// Returns queue capacity and overflow policy.
+    pub fn config(&self) -> QueueConfig {
+        self.shared.config
+    }
+}
+
+impl<T> Clone for QueueSender<T> {
+    fn clone(&self) -> Self {
+        QueueSender {
+            sender: self.sender.clone(),
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+impl<T> fmt::Debug for QueueSender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("QueueSender")
+            .field("config", &self.shared.config)
+            .finish()
+    }
+}
+
+/// Receiving half of a queue.
+pub struct QueueReceiver<T> {
+    receiver: Receiver<T>,
+    shared: Arc<Shared>,
+}
+
+impl<T> QueueReceiver<T> {
+    /// Receives the next value.
+    ///
+    /// Returns `None` when all senders are dropped or the queue was disconnected.
+    pub async fn recv(&mut self) -> Option<T> {
+        poll_fn(|cx| self.poll_recv(cx)).await
+    }
+
+    /// Polls to receive the next value.
+    pub fn poll_recv(&mut self, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        self.shared.receiver.register(cx.waker());
+        if self.shared.disconnected.load(Ordering::SeqCst)
+            && !self.shared.closed.swap(true, Ordering::SeqCst)
+        {
+            // Disconnection is terminal, queued values are dropped.
+            self.receiver.close();
+            let mut dropped = 0;
+            while self.receiver.try_recv().is_some() {
+                dropped += 1;
+            }
+            self.shared.len.fetch_sub(dropped, Ordering::SeqCst);
+            return Poll::Ready(None);
+        }
+        loop {
+            let value = match self.receiver.poll_recv(cx) {
+                Poll::Ready(Some(value)) => value,
+                poll => return poll,
+            };
+            let evicted = self.shared.evicted.load(Ordering::SeqCst);
+            if evicted > 0 {
+                // Value was evicted by a sender with the `DropOldest` policy.
+                self.shared.evicted.fetch_sub(1, Ordering::SeqCst);
+                continue;
+            }
+            self.shared.len.fetch_sub(1, Ordering::SeqCst);
+            return Poll::Ready(Some(value));
+        }
+    }
+
+    /// Closes the queue, queued values can still be received.
+    pub fn close(&mut self) {
+        self.shared.closed.store(true, Ordering::SeqCst);
+        self.receiver.close();
+    }
+}
+
+impl<T> Stream for QueueReceiver<T> {
+    type Item = T;
+
+    #[inline]
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.get_mut().poll_recv(cx)
+    }
+}
+
+impl<T> Drop for QueueReceiver<T> {
+    fn drop(&mut self) {
+        self.shared.closed.store(true, Ordering::SeqCst);
+    }
+}
+
+impl<T> fmt::Debug for QueueReceiver<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("QueueReceiver")
+            .field("config", &self.shared.config)
+            .finish()
+    }
+}
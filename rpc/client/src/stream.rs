@@ -0,0 +1,144 @@
+//! Streaming calls.
+//!
+//! Peer handling a streaming call sends partial results as notifications
+//! with [`PARTIAL_METHOD`] method name, correlated by the request identifier:
+//!
+//! ```json
+//! {"jsonrpc": "2.0", "method": "$/partialResult", "params": {"id": 1, "value": [1, 2]}}
+//! ```
+//!
+//! The call is ended by a regular response or an error response.
+//!
+//! [`PARTIAL_METHOD`]: constant.PARTIAL_METHOD.html
+
+use std::{
+    future::Future,
+    marker::PhantomData,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use futures::{ready, stream::Stream};
+use serde::de::DeserializeOwned;
+
+use net3_msg::{traits::Message, types::Id};
+use net3_rpc_error::{Error as CallError, Result};
+
+use crate::{
+    handler::internal::{ClientMessage, Response, ResponseReceiver},
+    queue::{QueueReceiver, QueueSender},
+};
+
+/// Method name of partial result notifications.
+pub const PARTIAL_METHOD: &str = "$/partialResult";
+
+/// Partial result notification parameters.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PartialParams<T> {
+    /// Identifier of the streaming call request.
+    pub id: Id,
+
+    /// Partial result.
+    pub value: T,
+}
+
+/// Stream of results of a streaming call.
+///
+/// Yields partial results followed by the result of the final response, if any.
+/// Error response ends the stream with an error.
+/// Dropping the stream before it ends cancels the request.
+pub struct ResponseStream<M: Message, R> {
+    /// Receiver of partial results.
+    partials: QueueReceiver<M>,
+    /// Receiver of the final response.
+    receiver: ResponseReceiver<M>,
+    /// Final response received before the last partial results.
+    ///
+    /// Boxed to keep the stream `Unpin` for any message type.
+    response: Option<Box<Response<M>>>,
+    /// Sender of a request cancellation.
+    control: QueueSender<ClientMessage<M>>,
+    /// Request identifier, `None` when the stream has ended.
+    id: Option<Id>,
+    _marker: PhantomData<fn() -> R>,
+}
+
+impl<M: Message, R> ResponseStream<M, R> {
+    pub(crate) fn new(
+        id: Id,
+        partials: QueueReceiver<M>,
+        receiver: ResponseReceiver<M>,
+        control: QueueSender<ClientMessage<M>>,
+    ) -> Self {
+        ResponseStream {
+            partials,
+            receiver,
+            response: None,
+            control,
+            id: Some(id),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns identifier of the streaming call request.
+    pub fn id(&self) -> Option<&Id> {
+        self.id.as_ref()
+    }
+}
+
+impl<M: Message, R: DeserializeOwned> Stream for ResponseStream<M, R> {
+    type Item = Result<R>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        if this.id.is_none() {
+            return Poll::Ready(None);
+        }
+        // Partial results precede the final response.
+        if let Poll::Ready(Some(message)) = this.partials.poll_recv(cx) {
+            return Poll::Ready(Some(read_partial(message)));
+        }
+        let response = match this.response.take() {
+            Some(response) => *response,
+            None => match ready!(Pin::new(&mut this.receiver).poll(cx)) {
+                Ok(response) => {
+                    // Partial results could be queued meanwhile.
+                    if let Poll::Ready(Some(message)) = this.partials.poll_recv(cx) {
+                        this.response = Some(Box::new(response));
+                        return Poll::Ready(Some(read_partial(message)));
+                    }
+                    response
+                }
+                Err(_) => {
+                    this.id = None;
+                    return Poll::Ready(Some(Err(std::io::ErrorKind::ConnectionReset.into())));
+                }
+            },
+        };
+        this.id = None;
+        let message = match response {
+            Ok(message) => message,
+            Err(err) => return Poll::Ready(Some(Err(CallError::Rpc(err)))),
+        };
+        match message.read_optional::<R>() {
+            Ok(Some(result)) => Poll::Ready(Some(Ok(result))),
+            Ok(None) => Poll::Ready(None),
+            Err(err) => Poll::Ready(Some(Err(err.into()))),
+        }
+    }
+}
+
+impl<M: Message, R> Drop for ResponseStream<M, R> {
+    fn drop(&mut self) {
+        if let Some(id) = self.id.take() {
+            // Client loop is gone if sending fails, there is nothing to cancel.
+            let _ = self.control.try_send(ClientMessage::Cancel(id));
+        }
+    }
+}
+
+/// Reads result of a partial result notification.
+fn read_partial<M: Message, R: DeserializeOwned>(message: M) -> Result<R> {
+    let partial = message.read::<PartialParams<R>>()?;
+    Ok(partial.value)
+}
@@ -0,0 +1,870 @@
+use net3_msg::types::Id;
+
+use crate::id::{response_key, IdGenerator};
+
+#[test]
+fn id_generators() {
+    assert_eq!(IdGenerator::Numeric.generate(7), Id::Num(7));
+    assert_eq!(IdGenerator::String.generate(7), Id::Str("7".to_owned()));
+    assert_eq!(
+        IdGenerator::custom(|n| Id::Str(format!("req-{}", n))).generate(7),
+        Id::Str("req-7".to_owned())
+    );
+    match IdGenerator::Uuid.generate(7) {
+        Id::Str(id) => assert_eq!(id.len(), 36),
+        id => panic!("unexpected id: {:?}", id),
+    }
+}
+
+#[test]
+fn response_keys() {
+    let num = Id::Num(12);
+    assert_eq!(*response_key(&num), num);
+    assert_eq!(*response_key(&Id::Str("12".to_owned())), num);
+    for id in &["012", "+12", "", "req-12", "99999999999999999999"] {
+        let id = Id::Str(id.to_string());
+        assert_eq!(*response_key(&id), id);
+    }
+}
+
+#[tokio::test]
+async fn request_deadlines() {
+    use std::time::Duration;
+
+    use crate::deadline;
+
+    let handled = deadline::handle(None, async { Ok(vec![deadline::current()]) }).await;
+    assert_eq!(handled.unwrap(), vec![None]);
+
+    let handled = deadline::handle(Some(Duration::from_secs(60)), async {
+        assert!(!deadline::is_expired());
+        Ok(vec![deadline::remaining().unwrap()])
+    })
+    .await;
+    assert!(handled.unwrap()[0] > Duration::from_secs(59));
+
+    let skipped = deadline::handle::<_, ()>(Some(Duration::from_secs(0)), async {
+        panic!("handler should be skipped")
+    })
+    .await;
+    assert_eq!(skipped.unwrap(), Vec::<()>::new());
+
+    let cancelled = deadline::handle(Some(Duration::from_millis(10)), async {
+        tokio::time::delay_for(Duration::from_secs(60)).await;
+        Ok(vec![()])
+    })
+    .await;
+    assert_eq!(cancelled.unwrap(), Vec::<()>::new());
+}
+
+#[test]
+fn request_timeout_metadata() {
+    use std::time::Duration;
+
+    use net3_msg::{compact::Message, prelude::*};
+
+    let request =
+        Message::new_request(Id::Num(1), "test").with_timeout(Duration::from_millis(1500));
+    assert_eq!(request.meta(types::TIMEOUT_META), Some("1500"));
+    assert_eq!(request.timeout(), Some(Duration::from_millis(1500)));
+    assert_eq!(Message::new_request(Id::Num(1), "test").timeout(), None);
+}
+
+#[tokio::test]
+async fn queue_overflow() {
+    use std::{io::ErrorKind, time::Duration};
+
+    use crate::queue::{channel, Overflow, QueueConfig, SendError};
+
+    let (sender, mut receiver) = channel(QueueConfig::bounded(2, Overflow::Error));
+    sender.try_send(1).unwrap();
+    sender.send(2).await.unwrap();
+    match sender.try_send(3) {
+        Err(SendError::Full(3)) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+    assert_eq!(receiver.recv().await, Some(1));
+    sender.try_send(3).unwrap();
+    assert_eq!(receiver.recv().await, Some(2));
+    assert_eq!(receiver.recv().await, Some(3));
+
+    let (sender, mut receiver) = channel(QueueConfig::bounded(2, Overflow::DropOldest));
+    for value in 1..=3 {
+        sender.try_send(value).unwrap();
+    }
+    assert_eq!(receiver.recv().await, Some(2));
+    assert_eq!(receiver.recv().await, Some(3));
+
+    let (sender, mut receiver) = channel(QueueConfig::bounded(1, Overflow::Disconnect));
+    sender.try_send(1).unwrap();
+    assert!(matches!(sender.try_send(2), Err(SendError::Closed(2))));
+    assert!(sender.is_closed());
+    // Disconnection drops queued values and the queue is not reopened.
+    assert_eq!(receiver.recv().await, None);
+    assert!(sender.is_empty());
+    assert!(matches!(sender.try_send(3), Err(SendError::Closed(3))));
+    assert!(matches!(sender.send(4).await, Err(SendError::Closed(4))));
+    assert_eq!(receiver.recv().await, None);
+    assert!(sender.is_empty());
+
+    let (sender, mut receiver) = channel(QueueConfig::bounded(1, Overflow::Wait));
+    sender.try_send(1).unwrap();
+    let err = std::io::Error::from(sender.try_send(2).unwrap_err());
+    assert_eq!(err.kind(), ErrorKind::WouldBlock);
+    let waiting = tokio::spawn(async move {
+        sender.send(2).await.unwrap();
+        sender
+    });
+    tokio::time::delay_for(Duration::from_millis(10)).await;
+    assert_eq!(receiver.recv().await, Some(1));
+    let sender = waiting.await.unwrap();
+    assert_eq!(receiver.recv().await, Some(2));
+    drop(sender);
+    assert_eq!(receiver.recv().await, None);
+
+    let (sender, receiver) = channel::<u8>(QueueConfig::unbounded());
+    drop(receiver);
+    assert!(matches!(sender.send(1).await, Err(SendError::Closed(1))));
+}
+
+#[tokio::test]
+async fn invalid_frames_answered() {
+    use net3_msg::prelude::*;
+    use net3_proto_jsonrpc::Message;
+    use serde_json::Value;
+    use tokio::{
+        io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+        net::TcpListener,
+    };
+
+    use crate::{
+        common::{FromBuilder, NoopHandler},
+        ClientBuilder,
+    };
+
+    type Codec = net3_codec_json_lines::Codec<Message>;
+
+    let mut listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap().to_string();
+    let client =
+        ClientBuilder::<Codec, FromBuilder<NoopHandler<Message>>>::from_addr(&addr).background();
+    let mut stream = BufReader::new(listener.accept().await.unwrap().0);
+
+    stream
+        .get_mut()
+        .write_all(b"\x00garbage\n{\"jsonrpc\":\n[1]\n")
+        .await
+        .unwrap();
+    let mut line = String::new();
+    for code in &[-32700, -32700, -32600] {
+        line.clear();
+        stream.read_line(&mut line).await.unwrap();
+        let response: Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(response["id"], Value::Null);
+        assert_eq!(response["error"]["code"], *code);
+    }
+
+    stream
+        .get_mut()
+        .write_all(b"{\"jsonrpc\":\"1.5\",\"id\":2,\"method\":\"test\"}\n")
+        .await
+        .unwrap();
+    line.clear();
+    stream.read_line(&mut line).await.unwrap();
+    assert_eq!(
+        line,
+        "{\"jsonrpc\":\"2.0\",\"id\":2,\"error\":{\"code\":-32600,\"message\":\"unsupported protocol version\"}}\n"
+    );
+
+    // Connection is kept open.
+    let request = tokio::spawn(async move { client.request::<_, u32>("test", Some(&1)).await });
+    line.clear();
+    stream.read_line(&mut line).await.unwrap();
+    let request_message: Message = serde_json::from_str(&line).unwrap();
+    let response = builder::new_response(&request_message)
+        .with_data(&7u32)
+        .unwrap()
+        .build();
+    let body = format!("{}\n", serde_json::to_string(&response).unwrap());
+    stream.get_mut().write_all(body.as_bytes()).await.unwrap();
+    assert_eq!(request.await.unwrap().unwrap(), 7);
+}
+
+/// Accepts a connection on a local port and passes it to `start`.
+///
+/// Returns address of the listener.
+async fn serve_one<F>(start: F) -> String
+where
+    F: FnOnce(tokio::net::TcpStream) + Send + 'static,
+{
+    let mut listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap().to_string();
+    tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        start(stream);
+    });
+    addr
+}
+
+/// Handler of requests that never finish in time.
+#[derive(Clone)]
+struct SlowHandler {
+    dropped: std::sync::Arc<std::sync::atomic::AtomicBool>,
+}
+
+/// Sets a flag when dropped.
+struct DropFlag(std::sync::Arc<std::sync::atomic::AtomicBool>);
+
+impl Drop for DropFlag {
+    fn drop(&mut self) {
+        self.0.store(true, std::sync::atomic::Ordering::SeqCst);
+    }
+}
+
+#[async_trait::async_trait]
+impl crate::Handler for SlowHandler {
+    type Event = ();
+    type Message = net3_msg::compact::Message;
+
+    async fn handle_request(
+        &mut self,
+        message: Self::Message,
+    ) -> std::io::Result<Vec<Self::Message>> {
+        use net3_msg::prelude::*;
+
+        let _flag = DropFlag(self.dropped.clone());
+        tokio::time::delay_for(std::time::Duration::from_secs(60)).await;
+        Ok(vec![builder::new_response(&message).build()])
+    }
+}
+
+#[tokio::test]
+async fn remote_cancellation() {
+    use std::{
+        sync::{atomic::Ordering, Arc},
+        time::Duration,
+    };
+
+    use net3_msg::compact::Message;
+
+    use crate::{
+        common::{CloneBuilder, FromBuilder, NoopHandler},
+        ClientBuilder, Error, DEFAULT_CANCEL_METHOD,
+    };
+
+    type Codec = net3_codec_json_lines::Codec<Message>;
+
+    let dropped = Arc::new(Default::default());
+    let handler = SlowHandler {
+        dropped: Arc::clone(&dropped),
+    };
+    let addr = serve_one(move |stream| {
+        ClientBuilder::<Codec, _>::from(CloneBuilder(handler))
+            .with_cancel_method(DEFAULT_CANCEL_METHOD)
+            .with_stream(stream)
+            .unwrap()
+            .spawn();
+    })
+    .await;
+
+    let client = ClientBuilder::<Codec, FromBuilder<NoopHandler<Message>>>::from_addr(&addr)
+        .with_call_timeout(Duration::from_millis(200))
+        .with_cancel_method(DEFAULT_CANCEL_METHOD)
+        .background();
+    match client.request::<_, ()>("slow", Some(&())).await {
+        Err(Error::Io(err)) => assert_eq!(err.kind(), std::io::ErrorKind::TimedOut),
+        result => panic!("unexpected result: {:?}", result),
+    }
+    for _ in 0..100 {
+        if dropped.load(Ordering::SeqCst) {
+            return;
+        }
+        tokio::time::delay_for(Duration::from_millis(10)).await;
+    }
+    panic!("peer handler was not cancelled");
+}
+
+/// Handler of streaming requests sending three partial results.
+struct CountingHandler {
+    handle: crate::Handle<net3_msg::compact::Message, ()>,
+}
+
+#[async_trait::async_trait]
+impl crate::Handler for CountingHandler {
+    type Event = ();
+    type Message = net3_msg::compact::Message;
+
+    async fn handle_request(
+        &mut self,
+        message: Self::Message,
+    ) -> std::io::Result<Vec<Self::Message>> {
+        use net3_msg::prelude::*;
+
+        for value in 1..=3u32 {
+            self.handle.send_partial(message.id(), &value)?;
+        }
+        Ok(vec![builder::new_response(&message)
+            .with_data(&4u32)?
+            .build()])
+    }
+}
+
+struct CountingBuilder;
+
+#[async_trait::async_trait]
+impl crate::HandlerBuilder for CountingBuilder {
+    type Handler = CountingHandler;
+
+    async fn build_handler(
+        &mut self,
+        handle: &crate::ClientHandle<CountingHandler>,
+    ) -> CountingHandler {
+        CountingHandler {
+            handle: handle.clone(),
+        }
+    }
+}
+
+#[tokio::test]
+async fn streaming_request() {
+    use futures::stream::TryStreamExt;
+
+    use net3_msg::compact::Message;
+
+    use crate::{
+        common::{FromBuilder, NoopHandler},
+        ClientBuilder,
+    };
+
+    type Codec = net3_codec_json_lines::Codec<Message>;
+
+    let addr = serve_one(|stream| {
+        ClientBuilder::<Codec, _>::from(CountingBuilder)
+            .with_stream(stream)
+            .unwrap()
+            .spawn();
+    })
+    .await;
+
+    let client =
+        ClientBuilder::<Codec, FromBuilder<NoopHandler<Message>>>::from_addr(&addr).background();
+    let values: Vec<_> = client
+        .request_stream::<_, u32>("count", Some(&()))
+        .unwrap()
+        .try_collect()
+        .await
+        .unwrap();
+    assert_eq!(values, vec![1, 2, 3, 4]);
+}
+
+/// Handler of requests responding with request parameters.
+struct EchoHandler<M>(std::marker::PhantomData<M>);
+
+impl<M> Clone for EchoHandler<M> {
+    fn clone(&self) -> Self {
+        EchoHandler(std::marker::PhantomData)
+    }
+}
+
+impl<M> Default for EchoHandler<M> {
+    fn default() -> Self {
+        EchoHandler(std::marker::PhantomData)
+    }
+}
+
+#[async_trait::async_trait]
+impl<M: net3_msg::traits::Message + 'static> crate::Handler for EchoHandler<M> {
+    type Event = ();
+    type Message = M;
+
+    async fn handle_request(&mut self, message: M) -> std::io::Result<Vec<M>> {
+        use net3_msg::prelude::*;
+
+        let params = message.read::<u32>()?;
+        Ok(vec![builder::new_response(&message)
+            .with_data(&params)?
+            .build()])
+    }
+}
+
+/// Starts a peer of [`EchoHandler`] accepting batches and returns its address.
+async fn start_echo_peer<M: net3_msg::traits::Message + Default + 'static>() -> String {
+    use net3_msg::batch::Batch;
+
+    use crate::{common::CloneBuilder, ClientBuilder};
+
+    serve_one(|stream| {
+        ClientBuilder::<net3_codec_json_lines::Codec<Batch<M>>, _>::from(CloneBuilder(
+            EchoHandler::<M>::default(),
+        ))
+        .with_stream(stream)
+        .unwrap()
+        .spawn();
+    })
+    .await
+}
+
+#[tokio::test]
+async fn batch_request() {
+    use net3_msg::{batch::Batch, compact::Message};
+
+    use crate::{
+        common::{FromBuilder, NoopHandler},
+        ClientBuilder,
+    };
+
+    type BatchCodec = net3_codec_json_lines::Codec<Batch<Message>>;
+
+    let addr = start_echo_peer::<Message>().await;
+    let client = ClientBuilder::<BatchCodec, FromBuilder<NoopHandler<Message>>>::from_addr(&addr)
+        .background();
+    let mut batch = client.batch();
+    let first = batch.request::<_, u32>("echo", Some(&1u32)).unwrap();
+    batch.notification("log", Some(&())).unwrap();
+    let second = batch.request::<_, u32>("echo", Some(&2u32)).unwrap();
+    assert_eq!(batch.len(), 3);
+    batch.send().unwrap();
+    assert_eq!(second.response().await.unwrap(), 2);
+    assert_eq!(first.response().await.unwrap(), 1);
+}
+
+#[tokio::test]
+async fn batch_response_frame() {
+    use tokio::{
+        io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+        net::TcpStream,
+    };
+
+    use net3_msg::{batch::Batch, compact::Message, prelude::*};
+
+    let addr = start_echo_peer::<Message>().await;
+    let mut stream = BufReader::new(TcpStream::connect(&addr).await.unwrap());
+    let batch = Batch::Many(vec![
+        builder::new_request::<Message, _>(Id::Num(1), "echo", Some(&1u32))
+            .unwrap()
+            .build(),
+        builder::new_event::<Message, _>("log", Some(&()))
+            .unwrap()
+            .build(),
+        builder::new_request::<Message, _>(Id::Num(2), "echo", Some(&2u32))
+            .unwrap()
+            .build(),
+    ]);
+    let body = format!("{}\n[]\n", serde_json::to_string(&batch).unwrap());
+    stream.get_mut().write_all(body.as_bytes()).await.unwrap();
+
+    // Responses to a batch are sent in a single frame.
+    let mut line = String::new();
+    stream.read_line(&mut line).await.unwrap();
+    let responses = match serde_json::from_str::<Batch<Message>>(&line).unwrap() {
+        Batch::Many(responses) => responses,
+        batch => panic!("unexpected frame: {:?}", batch),
+    };
+    assert_eq!(responses.len(), 2);
+    assert_eq!(responses[0].id(), &Id::Num(1));
+    assert_eq!(responses[0].read::<u32>().unwrap(), 1);
+    assert_eq!(responses[1].id(), &Id::Num(2));
+    assert_eq!(responses[1].read::<u32>().unwrap(), 2);
+
+    // Empty batch is answered with a single error response.
+    line.clear();
+    stream.read_line(&mut line).await.unwrap();
+    match serde_json::from_str::<Batch<Message>>(&line).unwrap() {
+        Batch::Single(response) => {
+            assert_eq!(response.error_kind(), Some(&ErrorKind::InvalidRequest))
+        }
+        batch => panic!("unexpected frame: {:?}", batch),
+    }
+}
+
+#[tokio::test]
+async fn batch_invalid_elements() {
+    use serde_json::{json, Value};
+    use tokio::{
+        io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+        net::TcpStream,
+    };
+
+    use net3_proto_jsonrpc::Message;
+
+    let addr = start_echo_peer::<Message>().await;
+    let mut stream = BufReader::new(TcpStream::connect(&addr).await.unwrap());
+    let body = concat!(
+        r#"[1,{"jsonrpc":"2.0","id":1,"method":"echo","params":7},"#,
+        r#"{"jsonrpc":"2.0","id":2,"method":"echo","params":"x"},"#,
+        r#"{"jsonrpc":"2.0","id":3,"method":"echo","other":1}]"#,
+        "\n"
+    );
+    stream.get_mut().write_all(body.as_bytes()).await.unwrap();
+
+    // Every invalid element is answered with its own error.
+    let mut line = String::new();
+    stream.read_line(&mut line).await.unwrap();
+    let responses: Vec<Value> = serde_json::from_str(&line).unwrap();
+    assert_eq!(responses.len(), 4);
+    assert_eq!(responses[0]["id"], Value::Null);
+    assert_eq!(responses[0]["error"]["code"], json!(-32600));
+    assert_eq!(
+        responses[1],
+        json!({"jsonrpc": "2.0", "id": 1, "result": 7})
+    );
+    assert_eq!(responses[2]["id"], json!(2));
+    assert_eq!(responses[2]["error"]["code"], json!(-32600));
+    assert_eq!(responses[3]["id"], Value::Null);
+    assert_eq!(responses[3]["error"]["code"], json!(-32600));
+
+    // Single requests which fail are answered alike, the connection stays open.
+    let body = concat!(
+        r#"{"jsonrpc":"2.0","id":4,"method":"echo","params":"x"}"#,
+        "\n",
+        r#"{"jsonrpc":"2.0","id":5,"method":"echo","params":5}"#,
+        "\n"
+    );
+    stream.get_mut().write_all(body.as_bytes()).await.unwrap();
+    let mut line = String::new();
+    stream.read_line(&mut line).await.unwrap();
+    let response: Value = serde_json::from_str(&line).unwrap();
+    assert_eq!(response["id"], json!(4));
+    assert_eq!(response["error"]["code"], json!(-32600));
+    let mut line = String::new();
+    stream.read_line(&mut line).await.unwrap();
+    let response: Value = serde_json::from_str(&line).unwrap();
+    assert_eq!(response, json!({"jsonrpc": "2.0", "id": 5, "result": 5}));
+}
+
+/// Handler of requests responding after a delay in milliseconds given in parameters.
+#[derive(Clone)]
+struct DelayHandler;
+
+#[async_trait::async_trait]
+impl crate::Handler for DelayHandler {
+    type Event = ();
+    type Message = net3_msg::compact::Message;
+
+    async fn handle_request(
+        &mut self,
+        message: Self::Message,
+    ) -> std::io::Result<Vec<Self::Message>> {
+        use net3_msg::prelude::*;
+
+        let delay = message.read::<u64>()?;
+        tokio::time::delay_for(std::time::Duration::from_millis(delay)).await;
+        Ok(vec![builder::new_response(&message)
+            .with_data(&delay)?
+            .build()])
+    }
+
+    fn fork(&self) -> Option<Self> {
+        Some(self.clone())
+    }
+}
+
+/// Handler of requests and notifications waiting for each other at a barrier.
+#[derive(Clone)]
+struct BarrierHandler(std::sync::Arc<tokio::sync::Barrier>);
+
+#[async_trait::async_trait]
+impl crate::Handler for BarrierHandler {
+    type Event = ();
+    type Message = net3_msg::compact::Message;
+
+    async fn handle_notification(
+        &mut self,
+        _message: Self::Message,
+    ) -> std::io::Result<Vec<Self::Message>> {
+        self.0.wait().await;
+        Ok(vec![])
+    }
+
+    async fn handle_request(
+        &mut self,
+        message: Self::Message,
+    ) -> std::io::Result<Vec<Self::Message>> {
+        use net3_msg::prelude::*;
+
+        self.0.wait().await;
+        Ok(vec![builder::new_response(&message).build()])
+    }
+
+    fn fork(&self) -> Option<Self> {
+        Some(self.clone())
+    }
+}
+
+#[tokio::test]
+async fn concurrent_requests() {
+    use std::{sync::Arc, time::Duration};
+
+    use tokio::{
+        io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+        net::TcpStream,
+        sync::Barrier,
+        time::timeout,
+    };
+
+    use net3_msg::{batch::Batch, compact::Message, prelude::*};
+
+    use crate::{common::CloneBuilder, ClientBuilder};
+
+    type Codec = net3_codec_json_lines::Codec<Batch<Message>>;
+
+    // Handlers are released only once all three frames are handled at once.
+    let barrier = Arc::new(Barrier::new(3));
+    let addr = serve_one(move |stream| {
+        ClientBuilder::<Codec, _>::from(CloneBuilder(BarrierHandler(barrier)))
+            .with_concurrent_requests(3)
+            .with_stream(stream)
+            .unwrap()
+            .spawn();
+    })
+    .await;
+
+    let mut stream = BufReader::new(TcpStream::connect(&addr).await.unwrap());
+    let frames = vec![
+        Batch::Many(vec![builder::new_request::<Message, _>(
+            Id::Num(1),
+            "wait",
+            Some(&()),
+        )
+        .unwrap()
+        .build()]),
+        Batch::Single(
+            builder::new_event::<Message, _>("wait", Some(&()))
+                .unwrap()
+                .build(),
+        ),
+        Batch::Single(
+            builder::new_request::<Message, _>(Id::Num(2), "wait", Some(&()))
+                .unwrap()
+                .build(),
+        ),
+    ];
+    let mut body = String::new();
+    for frame in &frames {
+        body.push_str(&serde_json::to_string(frame).unwrap());
+        body.push('\n');
+    }
+    stream.get_mut().write_all(body.as_bytes()).await.unwrap();
+
+    let mut responses = Vec::new();
+    for _ in 0..2 {
+        let mut line = String::new();
+        timeout(Duration::from_secs(5), stream.read_line(&mut line))
+            .await
+            .expect("frames are handled concurrently")
+            .unwrap();
+        responses.push(serde_json::from_str::<Batch<Message>>(&line).unwrap());
+    }
+    responses.sort_by_key(|batch| matches!(batch, Batch::Single(_)));
+    match &responses[..] {
+        [Batch::Many(batch), Batch::Single(response)] => {
+            assert_eq!(batch.len(), 1);
+            assert_eq!(batch[0].id(), &Id::Num(1));
+            assert_eq!(response.id(), &Id::Num(2));
+        }
+        responses => panic!("unexpected frames: {:?}", responses),
+    }
+}
+
+#[tokio::test]
+async fn graceful_shutdown() {
+    use std::time::Duration;
+
+    use tokio::{
+        io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+        net::TcpListener,
+    };
+
+    use net3_msg::{compact::Message, prelude::*};
+
+    use crate::{common::CloneBuilder, ClientBuilder, Disconnect};
+
+    type Codec = net3_codec_json_lines::Codec<Message>;
+
+    let mut listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap().to_string();
+    // Requests are handled concurrently or in place.
+    for (delay, expected, limit) in &[
+        (200u64, Disconnect::Drained, Some(2)),
+        (10_000, Disconnect::TimedOut, Some(2)),
+        (200, Disconnect::Drained, None),
+        (10_000, Disconnect::TimedOut, None),
+    ] {
+        let mut builder =
+            ClientBuilder::<Codec, _>::from(CloneBuilder(DelayHandler)).with_reconnect(&addr);
+        if let Some(limit) = limit {
+            builder = builder.with_concurrent_requests(*limit);
+        }
+        let client = builder.background();
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut stream = BufReader::new(stream);
+        let request = builder::new_request::<Message, _>(Id::Num(1), "delay", Some(delay))
+            .unwrap()
+            .build();
+        let body = format!("{}\n", serde_json::to_string(&request).unwrap());
+        stream.get_mut().write_all(body.as_bytes()).await.unwrap();
+        tokio::time::delay_for(Duration::from_millis(100)).await;
+        client.send_notification("log", Some(&())).unwrap();
+
+        let reason = client.shutdown(Duration::from_millis(500)).await.unwrap();
+        assert_eq!(&reason, expected);
+
+        // Queued notification is sent, the response only if it was in time.
+        // Notification follows the response of a request handled in place.
+        let mut lines = Vec::new();
+        loop {
+            let mut line = String::new();
+            if stream.read_line(&mut line).await.unwrap() == 0 {
+                break;
+            }
+            lines.push(serde_json::from_str::<Message>(&line).unwrap());
+        }
+        assert!(lines.iter().any(|line| line.method() == Some("log")));
+        match expected {
+            Disconnect::Drained => {
+                assert_eq!(lines.len(), 2);
+                assert!(lines.iter().any(|line| line.id() == &Id::Num(1)));
+            }
+            _ => assert_eq!(lines.len(), 1),
+        }
+    }
+}
+
+#[tokio::test]
+async fn shutdown_awaits_pending_requests() {
+    use std::time::Duration;
+
+    use tokio::{
+        io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+        net::TcpListener,
+    };
+
+    use net3_msg::{compact::Message, prelude::*};
+
+    use crate::{common::CloneBuilder, ClientBuilder, Disconnect};
+
+    type Codec = net3_codec_json_lines::Codec<Message>;
+
+    let mut listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap().to_string();
+    let client = ClientBuilder::<Codec, _>::from(CloneBuilder(DelayHandler))
+        .with_reconnect(&addr)
+        .background();
+    let (stream, _) = listener.accept().await.unwrap();
+    let mut stream = BufReader::new(stream);
+    let client_ = client.clone();
+    let response =
+        tokio::spawn(async move { client_.request::<_, u64>("delay", Some(&1u64)).await });
+    let mut line = String::new();
+    stream.read_line(&mut line).await.unwrap();
+    let request = serde_json::from_str::<Message>(&line).unwrap();
+
+    // Shutdown waits for the response to the request sent to the peer.
+    let shutdown = tokio::spawn(async move { client.shutdown(Duration::from_secs(5)).await });
+    tokio::time::delay_for(Duration::from_millis(100)).await;
+    let reply = builder::new_response(&request)
+        .with_data(&1u64)
+        .unwrap()
+        .build();
+    let body = format!("{}\n", serde_json::to_string(&reply).unwrap());
+    stream.get_mut().write_all(body.as_bytes()).await.unwrap();
+
+    assert_eq!(response.await.unwrap().unwrap(), 1);
+    assert_eq!(shutdown.await.unwrap().unwrap(), Disconnect::Drained);
+}
+
+#[tokio::test]
+async fn close_reason() {
+    use tokio::{io::AsyncReadExt, net::TcpListener};
+
+    use net3_msg::compact::Message;
+
+    use crate::{common::CloneBuilder, ClientBuilder, Disconnect};
+
+    type Codec = net3_codec_json_lines::Codec<Message>;
+
+    let mut listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap().to_string();
+    let builder = ClientBuilder::<Codec, _>::from(CloneBuilder(DelayHandler)).with_reconnect(&addr);
+    let client = builder.handle();
+    let task = builder.spawn();
+    let (mut stream, _) = listener.accept().await.unwrap();
+
+    // Closed connection is not made again.
+    client.close().unwrap();
+    assert_eq!(task.await.unwrap().unwrap(), Disconnect::Closed);
+    let mut buf = Vec::new();
+    assert_eq!(stream.read_to_end(&mut buf).await.unwrap(), 0);
+}
+
+#[tokio::test]
+async fn bounded_message_queue() {
+    use std::io::ErrorKind;
+
+    use net3_msg::{batch::Batch, compact::Message};
+
+    use crate::{
+        common::{FromBuilder, NoopHandler},
+        ClientBuilder, Overflow, QueueConfig,
+    };
+
+    type BatchCodec = net3_codec_json_lines::Codec<Batch<Message>>;
+
+    let addr = start_echo_peer::<Message>().await;
+    let builder = ClientBuilder::<BatchCodec, FromBuilder<NoopHandler<Message>>>::from_addr(&addr)
+        .with_message_queue(QueueConfig::bounded(1, Overflow::Wait));
+    let client = builder.handle();
+    client.send_notification("log", Some(&())).unwrap();
+    let err = client.send_notification("log", Some(&())).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::WouldBlock);
+
+    // Request waits for space until the client loop sends the queued notification.
+    let response = tokio::spawn(async move { client.request::<_, u32>("echo", Some(&1u32)).await });
+    builder.spawn();
+    assert_eq!(response.await.unwrap().unwrap(), 1);
+}
+
+#[tokio::test]
+async fn control_messages_not_evicted() {
+    use futures::FutureExt;
+
+    use net3_msg::compact::Message;
+
+    use crate::{
+        common::{FromBuilder, NoopHandler},
+        ClientBuilder, Disconnect, Overflow, QueueConfig,
+    };
+
+    type Codec = net3_codec_json_lines::Codec<Message>;
+
+    let addr = start_echo_peer::<Message>().await;
+    let builder = ClientBuilder::<Codec, FromBuilder<NoopHandler<Message>>>::from_addr(&addr)
+        .with_message_queue(QueueConfig::bounded(1, Overflow::DropOldest));
+    let client = builder.handle();
+    // Dropped request queues a cancellation which is not evicted by notifications.
+    let request = client.request::<_, u32>("echo", Some(&1u32));
+    assert!(request.now_or_never().is_none());
+    client.close().unwrap();
+    for _ in 0..3 {
+        client.send_notification("log", Some(&())).unwrap();
+    }
+    assert_eq!(builder.spawn().await.unwrap().unwrap(), Disconnect::Closed);
+}
+
+#[test]
+#[should_panic(expected = "queues have to be configured before creating client handles")]
+fn message_queue_after_handles() {
+    use net3_msg::compact::Message;
+
+    use crate::{
+        common::{FromBuilder, NoopHandler},
+        ClientBuilder, Overflow, QueueConfig,
+    };
+
+    type Codec = net3_codec_json_lines::Codec<Message>;
+
+    let builder =
+        ClientBuilder::<Codec, FromBuilder<NoopHandler<Message>>>::from_addr("127.0.0.1:0");
+    let _client = builder.handle();
+    let _ = builder.with_message_queue(QueueConfig::bounded(1, Overflow::Wait));
+}
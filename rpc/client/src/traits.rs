@@ -1,11 +1,15 @@
 //! Client traits.
 
-use std::{fmt::Debug, io::Result};
+use std::{
+    fmt::Debug,
+    io::{Error, ErrorKind, Result},
+};
 
 // re-export
 pub use tokio_util::codec::{Decoder, Encoder};
 
 use async_trait::async_trait;
+use serde::{de::DeserializeOwned, ser::Serialize};
 
 use crate::{builder::ClientHandle, handle::Handle};
 
@@ -61,4 +65,53 @@ pub trait Handler {
     async fn handle_internal_event(&mut self, _event: Self::Event) -> Result<Vec<Self::Message>> {
         Ok(vec![])
     }
+
+    /// Creates a handler of a single request or notification handled concurrently with others.
+    ///
+    /// It has to return a handler if the limit is set with [`with_concurrent_requests`],
+    /// connections of handlers which cannot be forked panic when they start.
+    /// It is usually a clone of the handler with shared state.
+    ///
+    /// [`with_concurrent_requests`]: ../builder/struct.Builder.html#method.with_concurrent_requests
+    fn fork(&self) -> Option<Self>
+    where
+        Self: Sized,
+    {
+        None
+    }
+}
+
+/// Request path of a client shared by transports.
+///
+/// Implemented by [`Handle`] and clients of other transports,
+/// clients of `#[rpc]` traits are implemented for every `Requester`.
+///
+/// [`Handle`]: ../../handle/struct.Handle.html
+#[async_trait]
+pub trait Requester {
+    /// Sends a method call request.
+    ///
+    /// Default timeout duration of the client is used to await for the response.
+    async fn request_opt<V, R>(
+        &self,
+        method: &str,
+        params: Option<&V>,
+    ) -> net3_rpc_error::Result<Option<R>>
+    where
+        V: Serialize + Sync,
+        R: DeserializeOwned + Send;
+
+    /// Sends a method call request, it is an error if the response is empty.
+    ///
+    /// Default timeout duration of the client is used to await for the response.
+    async fn request<V, R>(&self, method: &str, params: Option<&V>) -> net3_rpc_error::Result<R>
+    where
+        V: Serialize + Sync,
+        R: DeserializeOwned + Send,
+    {
+        match self.request_opt(method, params).await? {
+            Some(res) => Ok(res),
+            None => Err(Error::new(ErrorKind::InvalidData, "empty response").into()),
+        }
+    }
 }
@@ -0,0 +1,114 @@
+//! Remote cancellation of in-flight requests.
+//!
+//! Peer cancels a request by sending a notification with a configured method
+//! name and the request identifier in parameters, e.g. for [`DEFAULT_METHOD`]:
+//!
+//! ```json
+//! {"jsonrpc": "2.0", "method": "$/cancelRequest", "params": {"id": 1}}
+//! ```
+//!
+//! [`DEFAULT_METHOD`]: constant.DEFAULT_METHOD.html
+
+use std::{
+    collections::HashMap,
+    future::Future,
+    io::Result,
+    sync::{Arc, Mutex},
+};
+
+use futures::future::{AbortHandle, Abortable};
+
+use net3_msg::{
+    builder::{self, MessageBuilder},
+    traits::Message,
+    types::{Id, MessageKind},
+};
+
+/// Default method name of a cancellation notification.
+pub const DEFAULT_METHOD: &str = "$/cancelRequest";
+
+/// Cancellation notification parameters.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CancelParams {
+    /// Identifier of the cancelled request.
+    pub id: Id,
+}
+
+/// Registry of request handlers that can be cancelled by the peer.
+///
+/// Clones share the same registry.
+#[derive(Debug, Clone)]
+pub struct Cancellation {
+    method: Arc<str>,
+    handlers: Arc<Mutex<HashMap<Id, AbortHandle>>>,
+}
+
+impl Cancellation {
+    /// Creates a registry using given cancellation method name.
+    pub fn new(method: &str) -> Self {
+        Cancellation {
+            method: method.into(),
+            handlers: Default::default(),
+        }
+    }
+
+    /// Returns method name of a cancellation notification.
+    #[inline]
+    pub fn method(&self) -> &str {
+        &self.method
+    }
+
+    /// Creates a cancellation notification of a request.
+    pub fn notification<M: Message>(&self, id: Id) -> Result<M> {
+        Ok(builder::new_event::<M, _>(&self.method, Some(&CancelParams { id }))?.build())
+    }
+
+    /// Returns identifier of a request cancelled by the message.
+    ///
+    /// Returns `None` if the message is not a cancellation notification.
+    pub fn cancelled_id<M: Message>(&self, message: &M) -> Option<Id> {
+        if message.kind() != MessageKind::Event || message.method() != Some(&self.method) {
+            return None;
+        }
+        match message.read::<CancelParams>() {
+            Ok(params) => Some(params.id),
+            Err(err) => {
+                log::debug!("Invalid cancellation parameters: {}", err);
+                None
+            }
+        }
+    }
+
+    /// Aborts handler of a request.
+    ///
+    /// Returns true if the handler was in-flight.
+    pub fn cancel(&self, id: &Id) -> bool {
+        match self.handlers.lock().unwrap().remove(id) {
+            Some(handle) => {
+                handle.abort();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Runs request handler until it is finished or cancelled.
+    ///
+    /// Cancelled handler produces no response.
+    pub async fn run<F, M>(&self, id: Id, handler: F) -> Result<Vec<M>>
+    where
+        F: Future<Output = Result<Vec<M>>>,
+    {
+        let (handle, registration) = AbortHandle::new_pair();
+        self.handlers.lock().unwrap().insert(id.clone(), handle);
+        let result = Abortable::new(handler, registration).await;
+        self.handlers.lock().unwrap().remove(&id);
+        match result {
+            Ok(result) => result,
+            Err(_) => {
+                log::debug!("Request {} cancelled by peer", id);
+                Ok(vec![])
+            }
+        }
+    }
+}
@@ -1,6 +1,28 @@
 //! net3 channel connection loop message handler
 
-use std::{fmt::Debug, io::Result};
+#[macro_use]
+extern crate serde_derive;
+
+use std::{fmt::Debug, future::Future, io::Result, pin::Pin};
+
+use futures::stream::Stream;
+
+pub mod cancel;
+pub mod queue;
+pub mod shutdown;
+
+pub use self::cancel::Cancellation;
+pub use self::queue::{Overflow, QueueConfig};
+pub use self::shutdown::{Closing, Closings, Disconnect, Shutdown};
+
+/// Stream of messages sent to the peer by the handler.
+pub type Outbox<M> = Pin<Box<dyn Stream<Item = M> + Send>>;
+
+/// Handling of a request running concurrently with the connection loop.
+pub type Handling<M> = Pin<Box<dyn Future<Output = Result<Vec<M>>> + Send>>;
+
+/// Default limit of frames read ahead while a remote message is handled.
+pub const DEFAULT_MAX_READ_AHEAD: usize = 64;
 
 /// Network [`Channel`] message handler trait.
 ///
@@ -19,4 +41,66 @@ pub trait LoopHandler {
         &mut self,
         _event: Self::InternalEvent,
     ) -> Result<Vec<Self::RemoteMessage>>;
+
+    /// Returns registry of request handlers cancelled by the peer.
+    ///
+    /// Connection loop applies cancellations received while a message is handled.
+    fn cancellation(&self) -> Option<Cancellation> {
+        None
+    }
+
+    /// Takes stream of messages sent to the peer, also while a remote message is handled.
+    ///
+    /// Connection loop takes the outbox once when it is started.
+    fn take_outbox(&mut self) -> Option<Outbox<Self::RemoteMessage>> {
+        None
+    }
+
+    /// Returns queue of frames read ahead while a remote message is handled.
+    ///
+    /// Frames are read ahead to receive cancellations, other messages are queued.
+    /// Channel is not read while the queue is full and its policy is [`Overflow::Wait`],
+    /// other policies are applied to frames read while the queue is full.
+    /// Queue holds up to [`DEFAULT_MAX_READ_AHEAD`] frames by default.
+    ///
+    /// [`Overflow::Wait`]: queue/enum.Overflow.html#variant.Wait
+    /// [`DEFAULT_MAX_READ_AHEAD`]: constant.DEFAULT_MAX_READ_AHEAD.html
+    fn read_ahead_queue(&self) -> QueueConfig {
+        QueueConfig::bounded(DEFAULT_MAX_READ_AHEAD, Overflow::Wait)
+    }
+
+    /// Returns limit of requests handled concurrently.
+    ///
+    /// Remote messages are handled one by one by default.
+    fn max_concurrent_requests(&self) -> Option<usize> {
+        None
+    }
+
+    /// Starts handling of a remote request or notification concurrently with the connection loop.
+    ///
+    /// Returns the message back if it has to be handled by [`handle_remote_message`],
+    /// like responses and cancellations delivered in place.
+    ///
+    /// [`handle_remote_message`]: #tymethod.handle_remote_message
+    fn handle_concurrently(
+        &mut self,
+        message: Self::RemoteMessage,
+    ) -> std::result::Result<Handling<Self::RemoteMessage>, Self::RemoteMessage> {
+        Err(message)
+    }
+
+    /// Returns amount of requests sent to the peer awaiting responses.
+    ///
+    /// Graceful shutdown waits for their responses.
+    fn pending_requests(&self) -> usize {
+        0
+    }
+
+    /// Takes stream of closing requests, also received while a remote message is handled.
+    ///
+    /// Connection loop takes the stream once when it is started.
+    /// Connection is also closed when the handler stream ends.
+    fn take_closings(&mut self) -> Option<Closings> {
+        None
+    }
 }
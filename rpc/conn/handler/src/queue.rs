@@ -0,0 +1,74 @@
+//! Queue capacity and overflow policies.
+//!
+//! Queues are unbounded by default. Bounded queues apply an [`Overflow`]
+//! policy when a value is sent to a full queue.
+//!
+//! [`Overflow`]: enum.Overflow.html
+
+/// Policy applied when a value is sent to a full queue.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Overflow {
+    /// Waits for space in the queue.
+    Wait,
+
+    /// Rejects the value.
+    Error,
+
+    /// Drops the oldest queued value.
+    DropOldest,
+
+    /// Drops queued values and disconnects the receiver.
+    Disconnect,
+}
+
+/// Queue capacity and overflow policy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QueueConfig {
+    capacity: Option<usize>,
+    overflow: Overflow,
+}
+
+impl QueueConfig {
+    /// Creates a configuration of an unbounded queue.
+    #[inline]
+    pub fn unbounded() -> Self {
+        QueueConfig {
+            capacity: None,
+            overflow: Overflow::Wait,
+        }
+    }
+
+    /// Creates a configuration of a queue holding up to `capacity` values.
+    #[inline]
+    pub fn bounded(capacity: usize, overflow: Overflow) -> Self {
+        QueueConfig {
+            capacity: Some(capacity.max(1)),
+            overflow,
+        }
+    }
+
+    /// Returns queue capacity, `None` if the queue is unbounded.
+    #[inline]
+    pub fn capacity(&self) -> Option<usize> {
+        self.capacity
+    }
+
+    /// Returns policy applied when the queue is full.
+    #[inline]
+    pub fn overflow(&self) -> Overflow {
+        self.overflow
+    }
+
+    /// Returns `true` if a queue of `len` values is full.
+    #[inline]
+    pub fn is_full(&self, len: usize) -> bool {
+        self.capacity.is_some_and(|capacity| len >= capacity)
+    }
+}
+
+impl Default for QueueConfig {
+    #[inline]
+    fn default() -> Self {
+        QueueConfig::unbounded()
+    }
+}
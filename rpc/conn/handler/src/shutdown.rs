@@ -0,0 +1,83 @@
+//! Closing of a connection.
+//!
+//! Connection is closed immediately with [`Closing::Close`] or gracefully
+//! with a [`Shutdown`]. On a graceful shutdown the connection loop stops
+//! handling new remote requests and notifications, waits for the message handled
+//! in place, requests in flight, responses to requests sent to the peer and
+//! messages in the handler outbox within the shutdown timeout,
+//! flushes the channel and closes it.
+//!
+//! [`Closing::Close`]: enum.Closing.html#variant.Close
+//! [`Shutdown`]: struct.Shutdown.html
+
+use std::{
+    io::ErrorKind,
+    pin::Pin,
+    time::{Duration, Instant},
+};
+
+use futures::{
+    channel::oneshot::{channel, Receiver, Sender},
+    stream::Stream,
+};
+
+/// Stream of closing requests of a connection handler.
+pub type Closings = Pin<Box<dyn Stream<Item = Closing> + Send>>;
+
+/// Reason of a connection closed by the connection handler.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Disconnect {
+    /// Responses to requests in flight and queued messages were sent.
+    Drained,
+
+    /// Shutdown timed out, requests still in flight were dropped.
+    TimedOut,
+
+    /// Connection was closed immediately or all client handles were dropped.
+    Closed,
+
+    /// Connection failed during the shutdown.
+    Failed(ErrorKind),
+}
+
+/// Closing of a connection requested by a connection handler.
+#[derive(Debug)]
+pub enum Closing {
+    /// Closes the connection immediately.
+    Close,
+
+    /// Closes the connection gracefully.
+    Shutdown(Shutdown),
+}
+
+/// Graceful shutdown requested by a connection handler.
+#[derive(Debug)]
+pub struct Shutdown {
+    deadline: Instant,
+    sender: Sender<Disconnect>,
+}
+
+impl Shutdown {
+    /// Creates a shutdown with a timeout and a receiver of the disconnect reason.
+    ///
+    /// Timeout starts when the shutdown is created.
+    pub fn new(timeout: Duration) -> (Self, Receiver<Disconnect>) {
+        let (sender, receiver) = channel();
+        let deadline = Instant::now() + timeout;
+        (Shutdown { deadline, sender }, receiver)
+    }
+
+    /// Returns deadline of the message handled in place, requests in flight
+    /// and queued messages.
+    #[inline]
+    pub fn deadline(&self) -> Instant {
+        self.deadline
+    }
+
+    /// Completes the shutdown with a disconnect reason.
+    pub fn complete(self, reason: Disconnect) {
+        log::trace!("Connection closed: {:?}", reason);
+        // Requester is not waiting for the reason if sending fails.
+        let _ = self.sender.send(reason);
+    }
+}
@@ -8,68 +8,212 @@
 #![recursion_limit = "512"]
 
 use std::{
+    collections::VecDeque,
     fmt::Debug,
+    future::Future,
     io::{Error, ErrorKind, Result},
     marker::Unpin,
+    pin::Pin,
 };
 
 use futures::{
-    select,
+    future::{self, FutureExt},
+    pin_mut, select,
     sink::{Sink, SinkExt},
-    stream::{Stream, StreamExt},
+    stream::{Fuse, FusedStream, FuturesUnordered, Stream, StreamExt},
 };
 use futures_option::OptionExt as _;
+use tokio::time::{delay_until, timeout_at, Instant};
 
-pub use net3_rpc_conn_handler::LoopHandler;
+pub use net3_rpc_conn_handler::{
+    cancel, queue, shutdown, Cancellation, Closing, Closings, Disconnect, Handling, LoopHandler,
+    Outbox, Overflow, QueueConfig, Shutdown, DEFAULT_MAX_READ_AHEAD,
+};
+
+use net3_msg::{
+    batch::{Batch, Frame},
+    builder::{self, MessageBuilder, MessageBuilderExt},
+    traits::Message,
+    types::{self, Id, MessageKind},
+};
+
+/// Handling of a remote frame running concurrently with the loop.
+///
+/// Resolves to frames of responses sent back to the peer.
+type InFlight<M> = Pin<Box<dyn Future<Output = Result<Vec<Batch<M>>>> + Send>>;
+
+/// Closing requests of a connection handler.
+struct Closer {
+    /// Stream of closing requests taken from the handler.
+    requests: Option<Fuse<Closings>>,
+    /// Closing requested while a remote message is handled in place.
+    pending: Option<Closing>,
+}
 
-use net3_msg::traits::Message;
+impl Closer {
+    /// Receives a closing request, it never completes while one is pending.
+    async fn next(&mut self) -> Closing {
+        match self.requests.as_mut() {
+            Some(requests) if self.pending.is_none() && !requests.is_terminated() => {
+                match requests.next().await {
+                    Some(closing) => closing,
+                    None => future::pending().await,
+                }
+            }
+            _ => future::pending().await,
+        }
+    }
+
+    /// Returns deadline of a pending shutdown.
+    fn deadline(&self) -> Option<Instant> {
+        match &self.pending {
+            Some(Closing::Shutdown(shutdown)) => Some(Instant::from_std(shutdown.deadline())),
+            _ => None,
+        }
+    }
+
+    /// Returns true if the connection has to be closed immediately.
+    fn is_closed(&self) -> bool {
+        matches!(self.pending, Some(Closing::Close))
+    }
+}
 
 /// Starts channel message handler loop.
 ///
+/// Channel frames carry single messages or batches, see [`Frame`].
+/// Messages of a received batch are handled in order and their responses
+/// are sent back in a single batch.
+///
+/// Requests and notifications, single or in batches, are handled concurrently
+/// if the handler supports it, see [`LoopHandler::max_concurrent_requests`].
+/// Every received frame takes a place within the limit, responses are sent
+/// as frames complete and frames are not received while the limit is reached.
+///
+/// Frames which could not be decoded are answered with an error response
+/// if the codec consumed them, see [`is_recoverable`]. Malformed frames are
+/// answered with `ParseError`, other invalid frames with `InvalidRequest`.
+///
+/// Requests which fail to be handled are answered with error responses.
+///
 /// Loop will return on connection or [`LoopHandler`] error.
+/// It returns reason of a disconnection requested by the handler,
+/// [`Disconnect::Closed`] after [`Closing::Close`] or when the handler stream ends,
+/// otherwise reason of a graceful [`Shutdown`]. Closing requests are received
+/// also while a message is handled in place, see [`LoopHandler::take_closings`].
 ///
+/// [`Frame`]: ../net3_msg/batch/trait.Frame.html
 /// [`LoopHandler`]: trait.LoopHandler.html
+/// [`Shutdown`]: shutdown/struct.Shutdown.html
+/// [`Disconnect::Closed`]: shutdown/enum.Disconnect.html#variant.Closed
+/// [`Closing::Close`]: shutdown/enum.Closing.html#variant.Close
+/// [`is_recoverable`]: ../net3_codec_limits/fn.is_recoverable.html
+/// [`LoopHandler::max_concurrent_requests`]: trait.LoopHandler.html#method.max_concurrent_requests
+/// [`LoopHandler::take_closings`]: trait.LoopHandler.html#method.take_closings
 #[inline]
-pub async fn start_loop<C, H, M, E>(channel: C, handler: H, events: Option<E>) -> Result<()>
+pub async fn start_loop<C, H, M, E, F>(
+    channel: C,
+    handler: H,
+    events: Option<E>,
+) -> Result<Disconnect>
 where
     M: Message + 'static,
-    C: Sink<M, Error = Error> + Stream<Item = Result<M>> + Unpin,
+    F: Frame<M>,
+    C: Sink<F, Error = Error> + Stream<Item = Result<F>> + Unpin,
     E: Stream<Item = <H as LoopHandler>::InternalEvent> + Unpin,
-    H: LoopHandler<RemoteMessage = M> + Stream<Item = Result<M>> + Unpin + 'static,
+    H: LoopHandler<RemoteMessage = M> + Stream<Item = Result<Batch<M>>> + Unpin + 'static,
     H: Send,
     <H as LoopHandler>::InternalEvent: Sized + Send + Sync + Clone + Debug,
 {
     let mut handler = handler.fuse();
     let mut channel = channel.fuse();
     let mut events = events.map(|stream| stream.fuse());
+    let mut outbox = handler.get_mut().take_outbox().map(|stream| stream.fuse());
+    let mut closer = Closer {
+        requests: handler
+            .get_mut()
+            .take_closings()
+            .map(|stream| stream.fuse()),
+        pending: None,
+    };
+    // Messages received while handling a remote message.
+    let mut backlog = VecDeque::new();
+    let max_concurrent = handler.get_ref().max_concurrent_requests();
+    // Frames handled concurrently with the loop.
+    let mut in_flight: FuturesUnordered<InFlight<M>> = FuturesUnordered::new();
 
     loop {
+        match closer.pending.take() {
+            Some(Closing::Close) => {
+                log::trace!(
+                    "Connection closed with {} requests in flight.",
+                    in_flight.len()
+                );
+                return Ok(Disconnect::Closed);
+            }
+            Some(Closing::Shutdown(shutdown)) => {
+                return shutdown_loop(
+                    &mut handler,
+                    &mut channel,
+                    &mut outbox,
+                    &mut backlog,
+                    &mut in_flight,
+                    shutdown,
+                )
+                .await
+            }
+            None => (),
+        }
+        let accepting = max_concurrent.is_none_or(|limit| in_flight.len() < limit);
+        if accepting {
+            if let Some(batch) = backlog.pop_front() {
+                let batch = match batch {
+                    Ok(batch) => batch,
+                    Err(err) => {
+                        let response = invalid_frame_response(err)?;
+                        send_batch(&mut channel, Batch::Single(response)).await?;
+                        continue;
+                    }
+                };
+                handle_remote_batch(
+                    &mut handler,
+                    &mut channel,
+                    &mut outbox,
+                    &mut backlog,
+                    &mut in_flight,
+                    &mut closer,
+                    batch,
+                )
+                .await?;
+                continue;
+            }
+        }
         select! {
-            message = handler.next() => match message {
-                Some(Ok(message)) => {
-                    channel
-                        .get_mut()
-                        .send(message)
-                        .await?;
-                },
+            batch = handler.next() => match batch {
+                Some(Ok(batch)) => send_batch(&mut channel, batch).await?,
                 Some(Err(err)) => return Err(err),
                 None => {
-                    log::trace!("Connection aborted because handler sender is dropped.");
-                    return Err(ErrorKind::ConnectionAborted.into())
+                    log::trace!("Connection closed because handler stream ended.");
+                    return Ok(Disconnect::Closed)
                 },
             },
-            message = channel.next() => match message {
-                Some(Ok(message)) => {
-                    // Let the `handler` handle the message.
-                    let messages = handler.get_mut().handle_remote_message(message).await?;
-                    for message in messages {
-                        channel
-                            .get_mut()
-                            .send(message)
-                            .await?;
-                    }
+            closing = closer.next().fuse() => closer.pending = Some(closing),
+            frame = next_if(accepting, &mut channel).fuse() => match frame {
+                Some(Ok(frame)) => {
+                    handle_remote_batch(
+                        &mut handler,
+                        &mut channel,
+                        &mut outbox,
+                        &mut backlog,
+                        &mut in_flight,
+                        &mut closer,
+                        frame.into_batch(),
+                    )
+                    .await?;
+                },
+                Some(Err(err)) => {
+                    let response = invalid_frame_response(err)?;
+                    send_batch(&mut channel, Batch::Single(response)).await?;
                 },
-                Some(Err(err)) => return Err(err),
                 None => {
                     log::trace!("Channel stream was closed.");
                     return Err(ErrorKind::ConnectionReset.into())
@@ -78,20 +222,609 @@ where
             event = events.next() => match event {
                 Some(event) => {
                     let messages = handler.get_mut().handle_internal_event(event).await?;
-                    for message in messages {
-                        channel
-                            .get_mut()
-                            .send(message)
-                            .await?;
-                    }
+                    send_all(&mut channel, messages).await?;
                 }
                 None => {
                     log::trace!("Connection aborted because event sender is dropped.");
                     return Err(ErrorKind::ConnectionAborted.into())
                 },
             },
+            message = outbox.next() => send_outbox(&mut channel, message).await?,
+            batches = in_flight.select_next_some() => send_batches(&mut channel, batches?).await?,
             complete => break,
         }
     }
+    Ok(Disconnect::Closed)
+}
+
+/// Lets the `handler` handle remote messages and sends responses to the channel.
+///
+/// Responses to a batch are sent in a single batch.
+/// Empty batch is answered with an `InvalidRequest` error response.
+/// Requests which fail to be handled are answered with error responses,
+/// other messages of a batch are handled as usual.
+///
+/// Messages the handler handles concurrently are pushed to `in_flight`,
+/// a batch with any of them is pushed as a whole, others are handled in place.
+/// Batch handled in place is dropped if the connection is closed meanwhile.
+async fn handle_remote_batch<C, H, M, F>(
+    handler: &mut Fuse<H>,
+    channel: &mut Fuse<C>,
+    outbox: &mut Option<Fuse<Outbox<M>>>,
+    backlog: &mut VecDeque<Result<Batch<M>>>,
+    in_flight: &mut FuturesUnordered<InFlight<M>>,
+    closer: &mut Closer,
+    batch: Batch<M>,
+) -> Result<()>
+where
+    M: Message + 'static,
+    F: Frame<M>,
+    C: Sink<F, Error = Error> + Stream<Item = Result<F>> + Unpin,
+    H: LoopHandler<RemoteMessage = M> + Stream<Item = Result<Batch<M>>> + Unpin,
+{
+    match batch {
+        Batch::Single(message) => {
+            let request = as_request(&message);
+            match handler.get_mut().handle_concurrently(message) {
+                Ok(handling) => {
+                    let handling = handling.map(|result| {
+                        let messages = answer_errors(request, result);
+                        Ok(messages.into_iter().map(Batch::Single).collect())
+                    });
+                    in_flight.push(Box::pin(handling));
+                    Ok(())
+                }
+                Err(message) => {
+                    let result =
+                        handle_remote_message(handler, channel, outbox, backlog, closer, message)
+                            .await;
+                    send_all(channel, answer_errors(request, result)).await
+                }
+            }
+        }
+        Batch::Many(messages) if messages.is_empty() => {
+            log::trace!("Received an empty batch.");
+            let request = <M as MessageBuilderExt>::Builder::new(MessageKind::Request).build();
+            let error = types::Error::new(
+                types::ErrorKind::InvalidRequest,
+                Some("empty batch".to_owned()),
+            );
+            let response = builder::new_error_response(&request, error).build();
+            send_batch(channel, Batch::Single(response)).await
+        }
+        Batch::Many(messages) => {
+            let mut handlings = Vec::with_capacity(messages.len());
+            let mut concurrent = false;
+            for message in messages {
+                let request = as_request(&message);
+                let handling = match handler.get_mut().handle_concurrently(message) {
+                    Ok(handling) => {
+                        concurrent = true;
+                        handling
+                    }
+                    Err(message) => {
+                        let result = handle_remote_message(
+                            handler, channel, outbox, backlog, closer, message,
+                        )
+                        .await;
+                        if closer.is_closed() {
+                            return Ok(());
+                        }
+                        Box::pin(future::ready(result)) as Handling<M>
+                    }
+                };
+                handlings.push((request, handling));
+            }
+            let handling = async move {
+                let mut responses = Vec::new();
+                for (request, handling) in handlings {
+                    responses.extend(answer_errors(request, handling.await));
+                }
+                if responses.is_empty() {
+                    return Ok(vec![]);
+                }
+                Ok::<_, Error>(vec![Batch::Many(responses)])
+            };
+            if concurrent {
+                in_flight.push(Box::pin(handling));
+                return Ok(());
+            }
+            send_batches(channel, handling.await?).await
+        }
+    }
+}
+
+/// Returns a copy of the message if it is a request.
+fn as_request<M: Message>(message: &M) -> Option<M> {
+    match message.kind() {
+        MessageKind::Request => Some(message.clone()),
+        _ => None,
+    }
+}
+
+/// Returns responses of a handled message or an error response to a request
+/// which failed to be handled.
+///
+/// Requests which could not be read are answered with an `InvalidRequest` error,
+/// other failures with an `InternalError`. Errors of other messages are dropped.
+fn answer_errors<M: Message>(request: Option<M>, result: Result<Vec<M>>) -> Vec<M> {
+    let err = match result {
+        Ok(messages) => return messages,
+        Err(err) => err,
+    };
+    let request = match request {
+        Some(request) => request,
+        None => {
+            log::trace!("Failed to handle a remote message: {}", err);
+            return vec![];
+        }
+    };
+    log::trace!("Failed to handle a remote request: {}", err);
+    let kind = match err.kind() {
+        ErrorKind::InvalidData => types::ErrorKind::InvalidRequest,
+        _ => types::ErrorKind::InternalError,
+    };
+    let error = types::Error::new(kind, Some(err.to_string()));
+    vec![builder::new_error_response(&request, error).build()]
+}
+
+/// Lets the `handler` handle a remote message and returns its responses.
+///
+/// Messages from the handler [`Outbox`] are sent while the message is handled.
+/// If the handler supports [`Cancellation`], the channel is read as well.
+/// Cancellations are applied immediately, other messages are queued in the `backlog`
+/// configured with [`LoopHandler::read_ahead_queue`].
+///
+/// Closing requests are received meanwhile. Handling is dropped when the connection
+/// is closed immediately or when a graceful shutdown times out.
+///
+/// [`LoopHandler::read_ahead_queue`]: trait.LoopHandler.html#method.read_ahead_queue
+/// [`Outbox`]: type.Outbox.html
+/// [`Cancellation`]: cancel/struct.Cancellation.html
+async fn handle_remote_message<C, H, M, F>(
+    handler: &mut Fuse<H>,
+    channel: &mut Fuse<C>,
+    outbox: &mut Option<Fuse<Outbox<M>>>,
+    backlog: &mut VecDeque<Result<Batch<M>>>,
+    closer: &mut Closer,
+    message: M,
+) -> Result<Vec<M>>
+where
+    M: Message + 'static,
+    F: Frame<M>,
+    C: Sink<F, Error = Error> + Stream<Item = Result<F>> + Unpin,
+    H: LoopHandler<RemoteMessage = M> + Stream<Item = Result<Batch<M>>> + Unpin,
+{
+    let cancellation = handler.get_ref().cancellation();
+    let read_ahead_queue = handler.get_ref().read_ahead_queue();
+    if cancellation.is_none() && outbox.is_none() && closer.requests.is_none() {
+        return handler.get_mut().handle_remote_message(message).await;
+    }
+    let handling = handler.get_mut().handle_remote_message(message).fuse();
+    pin_mut!(handling);
+    let messages = loop {
+        // Channel is not read ahead unless cancellations have to be received.
+        let read_ahead = cancellation.is_some()
+            && !channel.is_terminated()
+            && (read_ahead_queue.overflow() != Overflow::Wait
+                || !read_ahead_queue.is_full(backlog.len()));
+        let deadline = closer.deadline();
+        select! {
+            messages = handling => break messages?,
+            message = outbox.next() => send_outbox(channel, message).await?,
+            frame = next_if(read_ahead, channel).fuse() => match frame {
+                Some(frame) => {
+                    let batch = frame.map(|frame| frame.into_batch());
+                    let responses =
+                        receive_ahead(cancellation.as_ref(), read_ahead_queue, backlog, batch)?;
+                    send_all(channel, responses).await?;
+                }
+                None => {
+                    log::trace!("Channel stream was closed.");
+                    backlog.push_back(Err(ErrorKind::ConnectionReset.into()));
+                }
+            },
+            closing = closer.next().fuse() => {
+                closer.pending = Some(closing);
+                if closer.is_closed() {
+                    log::trace!("Connection closed while a remote message was handled.");
+                    return Ok(vec![]);
+                }
+            },
+            _ = sleep_until(deadline).fuse() => {
+                log::trace!("Shutdown timed out while a remote message was handled.");
+                return Ok(vec![]);
+            },
+        }
+    };
+    // Messages sent by the handler precede its responses.
+    while let Some(Some(message)) = outbox.next().now_or_never() {
+        send_outbox(channel, Some(message)).await?;
+    }
+    Ok(messages)
+}
+
+/// Closes the connection gracefully.
+///
+/// Sends responses to requests in flight and messages of the handler outbox,
+/// awaits responses to requests sent to the peer within the shutdown timeout,
+/// sends queued messages, then flushes and closes the channel. Timeout includes
+/// time spent on a message handled in place when the shutdown was requested.
+/// Completes the shutdown with a reason of the disconnection and returns it.
+async fn shutdown_loop<C, H, M, F>(
+    handler: &mut Fuse<H>,
+    channel: &mut Fuse<C>,
+    outbox: &mut Option<Fuse<Outbox<M>>>,
+    backlog: &mut VecDeque<Result<Batch<M>>>,
+    in_flight: &mut FuturesUnordered<InFlight<M>>,
+    shutdown: Shutdown,
+) -> Result<Disconnect>
+where
+    M: Message + 'static,
+    F: Frame<M>,
+    C: Sink<F, Error = Error> + Stream<Item = Result<F>> + Unpin,
+    H: LoopHandler<RemoteMessage = M> + Stream<Item = Result<Batch<M>>> + Unpin,
+{
+    log::trace!(
+        "Closing connection with {} requests in flight and {} pending.",
+        in_flight.len(),
+        handler.get_ref().pending_requests()
+    );
+    let deadline = Instant::from_std(shutdown.deadline());
+    let reason = if deadline <= Instant::now() {
+        Disconnect::TimedOut
+    } else {
+        let drained = timeout_at(
+            deadline,
+            drain(handler, channel, outbox, backlog, in_flight),
+        );
+        match drained.await {
+            Ok(Ok(())) => Disconnect::Drained,
+            Ok(Err(err)) => {
+                shutdown.complete(Disconnect::Failed(err.kind()));
+                return Err(err);
+            }
+            Err(_) => Disconnect::TimedOut,
+        }
+    };
+    if let Err(err) = flush_queued(handler, channel, outbox).await {
+        shutdown.complete(Disconnect::Failed(err.kind()));
+        return Err(err);
+    }
+    match channel.get_mut().close().await {
+        Ok(()) => {
+            shutdown.complete(reason);
+            Ok(reason)
+        }
+        Err(err) => {
+            shutdown.complete(Disconnect::Failed(err.kind()));
+            Err(err)
+        }
+    }
+}
+
+/// Sends responses to requests in flight and messages of the handler outbox
+/// until requests sent to the peer are answered.
+///
+/// Messages sent with client handles are still sent. Remote responses and
+/// cancellations, also those read ahead into the `backlog`, are handled,
+/// other remote messages are dropped as the connection is closing.
+async fn drain<C, H, M, F>(
+    handler: &mut Fuse<H>,
+    channel: &mut Fuse<C>,
+    outbox: &mut Option<Fuse<Outbox<M>>>,
+    backlog: &mut VecDeque<Result<Batch<M>>>,
+    in_flight: &mut FuturesUnordered<InFlight<M>>,
+) -> Result<()>
+where
+    M: Message + 'static,
+    F: Frame<M>,
+    C: Sink<F, Error = Error> + Stream<Item = Result<F>> + Unpin,
+    H: LoopHandler<RemoteMessage = M> + Stream<Item = Result<Batch<M>>> + Unpin,
+{
+    let cancellation = handler.get_ref().cancellation();
+    while let Some(batch) = backlog.pop_front() {
+        match batch {
+            Ok(batch) => drain_batch(handler, channel, cancellation.as_ref(), batch).await?,
+            Err(err) if net3_codec_limits::is_recoverable(&err) => {
+                log::trace!("Dropped invalid frame received while closing: {}", err);
+            }
+            Err(err) => return Err(err),
+        }
+    }
+    while !in_flight.is_empty() || handler.get_ref().pending_requests() > 0 {
+        select! {
+            batches = in_flight.select_next_some() => send_batches(channel, batches?).await?,
+            batch = handler.next() => match batch {
+                Some(Ok(batch)) => send_batch(channel, batch).await?,
+                Some(Err(err)) => return Err(err),
+                None => log::trace!("Handler stream ended while closing."),
+            },
+            message = outbox.next() => send_outbox(channel, message).await?,
+            frame = channel.next() => match frame {
+                Some(Ok(frame)) => {
+                    drain_batch(handler, channel, cancellation.as_ref(), frame.into_batch()).await?
+                }
+                Some(Err(err)) if net3_codec_limits::is_recoverable(&err) => {
+                    log::trace!("Dropped invalid frame received while closing: {}", err);
+                }
+                Some(Err(err)) => return Err(err),
+                None => return Err(ErrorKind::ConnectionReset.into()),
+            },
+            complete => break,
+        }
+    }
+    Ok(())
+}
+
+/// Sends messages queued by the handler and in its outbox without waiting for more.
+async fn flush_queued<C, H, M, F>(
+    handler: &mut Fuse<H>,
+    channel: &mut Fuse<C>,
+    outbox: &mut Option<Fuse<Outbox<M>>>,
+) -> Result<()>
+where
+    M: Message + 'static,
+    F: Frame<M>,
+    C: Sink<F, Error = Error> + Unpin,
+    H: Stream<Item = Result<Batch<M>>> + Unpin,
+{
+    while let Some(Some(batch)) = handler.next().now_or_never() {
+        send_batch(channel, batch?).await?;
+    }
+    while let Some(Some(message)) = outbox.next().now_or_never() {
+        send_outbox(channel, Some(message)).await?;
+    }
+    Ok(())
+}
+
+/// Handles remote responses and cancellations received while closing.
+///
+/// Other remote messages are dropped.
+async fn drain_batch<C, H, M, F>(
+    handler: &mut Fuse<H>,
+    channel: &mut Fuse<C>,
+    cancellation: Option<&Cancellation>,
+    batch: Batch<M>,
+) -> Result<()>
+where
+    M: Message + 'static,
+    F: Frame<M>,
+    C: Sink<F, Error = Error> + Unpin,
+    H: LoopHandler<RemoteMessage = M> + Unpin,
+{
+    for message in batch.into_vec() {
+        match message.kind() {
+            MessageKind::Response | MessageKind::ErrorResponse => {
+                let messages = handler.get_mut().handle_remote_message(message).await?;
+                send_all(channel, messages).await?;
+            }
+            _ => {
+                let cancelled = cancellation.and_then(|cancellation| {
+                    let id = cancellation.cancelled_id(&message)?;
+                    Some(cancellation.cancel(&id))
+                });
+                if cancelled.is_none() {
+                    log::trace!("Dropped remote message received while closing.");
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Applies cancellations received while handling a message and queues other messages.
+///
+/// Returns error responses to requests rejected by the queue [`Overflow`] policy.
+///
+/// [`Overflow`]: queue/enum.Overflow.html
+fn receive_ahead<M: Message>(
+    cancellation: Option<&Cancellation>,
+    config: QueueConfig,
+    backlog: &mut VecDeque<Result<Batch<M>>>,
+    batch: Result<Batch<M>>,
+) -> Result<Vec<M>> {
+    let batch = match (batch, cancellation) {
+        (Ok(batch), Some(cancellation)) => match cancel_batch(cancellation, backlog, batch) {
+            Some(batch) => Ok(batch),
+            None => return Ok(vec![]),
+        },
+        (batch, _) => batch,
+    };
+    if !config.is_full(backlog.len()) {
+        backlog.push_back(batch);
+        return Ok(vec![]);
+    }
+    match config.overflow() {
+        // Channel is not read while the queue is full.
+        Overflow::Wait => backlog.push_back(batch),
+        Overflow::DropOldest => {
+            log::debug!("Read-ahead queue is full, dropped the oldest frame.");
+            backlog.pop_front();
+            backlog.push_back(batch);
+        }
+        Overflow::Error => {
+            log::debug!("Read-ahead queue is full, rejected a frame.");
+            let error = types::Error::new(
+                types::ErrorKind::InternalError,
+                Some("read-ahead queue is full".to_owned()),
+            );
+            let responses = batch
+                .map(Batch::into_vec)
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|message| message.kind() == MessageKind::Request)
+                .map(|request| builder::new_error_response(&request, error.clone()).build())
+                .collect();
+            return Ok(responses);
+        }
+        Overflow::Disconnect => {
+            log::debug!("Read-ahead queue is full, aborting connection.");
+            return Err(Error::new(
+                ErrorKind::ConnectionAborted,
+                "read-ahead queue overflow",
+            ));
+        }
+    }
+    Ok(vec![])
+}
+
+/// Applies cancellations of a batch, returns rest of the batch if any.
+///
+/// Cancellations are taken out of batches, rest of a batch is queued as a batch.
+fn cancel_batch<M: Message>(
+    cancellation: &Cancellation,
+    backlog: &mut VecDeque<Result<Batch<M>>>,
+    batch: Batch<M>,
+) -> Option<Batch<M>> {
+    match batch {
+        Batch::Single(message) => cancel_ahead(cancellation, backlog, message).map(Batch::Single),
+        Batch::Many(messages) if messages.is_empty() => Some(Batch::Many(messages)),
+        Batch::Many(messages) => {
+            let messages: Vec<M> = messages
+                .into_iter()
+                .filter_map(|message| cancel_ahead(cancellation, backlog, message))
+                .collect();
+            if messages.is_empty() {
+                return None;
+            }
+            Some(Batch::Many(messages))
+        }
+    }
+}
+
+/// Applies a cancellation, returns other messages back.
+fn cancel_ahead<M: Message>(
+    cancellation: &Cancellation,
+    backlog: &mut VecDeque<Result<Batch<M>>>,
+    message: M,
+) -> Option<M> {
+    let id = match cancellation.cancelled_id(&message) {
+        Some(id) => id,
+        None => return Some(message),
+    };
+    if !cancellation.cancel(&id) {
+        // Drop cancelled request if it was not handled yet.
+        remove_request(backlog, &id);
+    }
+    None
+}
+
+/// Removes a queued request with given identifier, also out of queued batches.
+fn remove_request<M: Message>(backlog: &mut VecDeque<Result<Batch<M>>>, id: &Id) {
+    *backlog = backlog
+        .drain(..)
+        .filter_map(|queued| match queued {
+            Ok(Batch::Single(message)) if is_request(&message, id) => None,
+            Ok(Batch::Many(messages)) if !messages.is_empty() => {
+                let messages: Vec<M> = messages
+                    .into_iter()
+                    .filter(|message| !is_request(message, id))
+                    .collect();
+                if messages.is_empty() {
+                    None
+                } else {
+                    Some(Ok(Batch::Many(messages)))
+                }
+            }
+            queued => Some(queued),
+        })
+        .collect();
+}
+
+/// Sends messages to the channel, each in a separate frame.
+async fn send_all<C, M, F>(channel: &mut Fuse<C>, messages: Vec<M>) -> Result<()>
+where
+    F: Frame<M>,
+    C: Sink<F, Error = Error> + Unpin,
+{
+    for message in messages {
+        send_batch(channel, Batch::Single(message)).await?;
+    }
+    Ok(())
+}
+
+/// Sends a message of the handler outbox to the channel.
+///
+/// Disconnection of the outbox queue aborts the connection.
+async fn send_outbox<C, M, F>(channel: &mut Fuse<C>, message: Option<M>) -> Result<()>
+where
+    F: Frame<M>,
+    C: Sink<F, Error = Error> + Unpin,
+{
+    match message {
+        Some(message) => send_batch(channel, Batch::Single(message)).await,
+        None => {
+            log::trace!("Connection aborted because handler outbox is disconnected.");
+            Err(ErrorKind::ConnectionAborted.into())
+        }
+    }
+}
+
+/// Sends batches of messages to the channel, each in separate frames.
+async fn send_batches<C, M, F>(channel: &mut Fuse<C>, batches: Vec<Batch<M>>) -> Result<()>
+where
+    F: Frame<M>,
+    C: Sink<F, Error = Error> + Unpin,
+{
+    for batch in batches {
+        send_batch(channel, batch).await?;
+    }
     Ok(())
 }
+
+/// Sends messages to the channel in frames.
+async fn send_batch<C, M, F>(channel: &mut Fuse<C>, batch: Batch<M>) -> Result<()>
+where
+    F: Frame<M>,
+    C: Sink<F, Error = Error> + Unpin,
+{
+    match F::from_batch(batch) {
+        Batch::Single(frame) => channel.get_mut().send(frame).await,
+        Batch::Many(frames) => {
+            for frame in frames {
+                channel.get_mut().send(frame).await?;
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Completes at the `deadline`, never completes without one.
+async fn sleep_until(deadline: Option<Instant>) {
+    match deadline {
+        Some(deadline) => delay_until(deadline).await,
+        None => future::pending().await,
+    }
+}
+
+/// Returns next item of the stream if `enabled`, otherwise never completes.
+async fn next_if<S: Stream + Unpin>(enabled: bool, stream: &mut S) -> Option<S::Item> {
+    if enabled {
+        stream.next().await
+    } else {
+        future::pending().await
+    }
+}
+
+/// Returns true if a message is a request with given identifier.
+fn is_request<M: Message>(message: &M, id: &Id) -> bool {
+    message.kind() == MessageKind::Request && message.id() == id
+}
+
+/// Returns an error response to a frame which could not be decoded.
+///
+/// Errors of frames not consumed by the codec are returned, connection is closed.
+fn invalid_frame_response<M: Message>(err: Error) -> Result<M> {
+    if !net3_codec_limits::is_recoverable(&err) {
+        return Err(err);
+    }
+    log::trace!("Received an invalid frame: {}", err);
+    let request = <M as MessageBuilderExt>::Builder::new(MessageKind::Request).build();
+    let kind = match net3_codec_limits::InvalidFrame::from_io(&err) {
+        Some(frame) if frame.is_malformed() => types::ErrorKind::ParseError,
+        _ => types::ErrorKind::InvalidRequest,
+    };
+    let error = types::Error::new(kind, Some(err.to_string()));
+    Ok(builder::new_error_response(&request, error).build())
+}
@@ -3,14 +3,55 @@ use syn::{punctuated::Punctuated, *};
 
 use darling::FromMeta;
 
+/// Encoding of method call parameters.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ParamsStyle {
+    /// Single argument is sent as the parameters.
+    Single,
+    /// Arguments are sent as an array.
+    Positional,
+    /// Arguments are sent as an object keyed by argument names.
+    Named,
+}
+
+impl FromMeta for ParamsStyle {
+    fn from_string(value: &str) -> darling::Result<Self> {
+        match value {
+            "positional" => Ok(ParamsStyle::Positional),
+            "named" => Ok(ParamsStyle::Named),
+            value => Err(darling::Error::unknown_value(value)),
+        }
+    }
+}
+
+#[derive(Default, Debug, FromMeta)]
+pub struct TraitAttr {
+    #[darling(default)]
+    pub params: Option<ParamsStyle>,
+    #[darling(default)]
+    pub openrpc: bool,
+}
+
+/// Error returned by a method described in OpenRPC document.
+#[derive(Debug, FromMeta)]
+pub struct ErrorAttr {
+    pub code: i64,
+    pub message: String,
+}
+
 #[derive(Default, Debug, FromMeta)]
 pub struct MethodAttr {
     #[darling(default)]
     pub name: Option<String>,
+    #[darling(default)]
+    pub params: Option<ParamsStyle>,
+    #[darling(default, multiple, rename = "error")]
+    pub errors: Vec<ErrorAttr>,
 }
 
 pub struct MethodDef {
     pub attr: MethodAttr,
+    pub style: ParamsStyle,
     pub item: TraitItemMethod,
     pub definition: TokenStream2,
     pub attributes: Vec<Attribute>,
@@ -46,6 +87,84 @@ impl MethodDef {
             .map(|arg| expect_arg_name(*arg))
             .collect::<Punctuated<_, Token![,]>>()
     }
+
+    pub fn input_types(&self) -> Vec<&Type> {
+        self.inputs()
+            .iter()
+            .map(|arg| expect_arg_type(arg))
+            .collect()
+    }
+
+    /// Returns types of decoded arguments, references are passed to the method.
+    ///
+    /// Arguments of `&str` and `&[T]` types are decoded as `String` and `Vec<T>`.
+    pub fn param_types(&self) -> Vec<Type> {
+        self.input_types()
+            .into_iter()
+            .map(|ty| match ty {
+                Type::Reference(reference) => owned_type(&reference.elem),
+                ty => ty.clone(),
+            })
+            .collect()
+    }
+
+    /// Returns `T` of a `Result<T>` return type.
+    pub fn result_type(&self) -> Option<&Type> {
+        let path = match &self.signature().output {
+            ReturnType::Type(_, ty) => match ty.as_ref() {
+                Type::Path(path) => path,
+                _ => return None,
+            },
+            ReturnType::Default => return None,
+        };
+        match &path.path.segments.last()?.arguments {
+            PathArguments::AngleBracketed(arguments) => {
+                arguments.args.iter().find_map(|argument| match argument {
+                    GenericArgument::Type(ty) => Some(ty),
+                    _ => None,
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns documentation of the method.
+    pub fn docs(&self) -> Option<String> {
+        parse_docs(&self.attributes)
+    }
+}
+
+/// Returns documentation from doc comment attributes.
+pub fn parse_docs(attrs: &[Attribute]) -> Option<String> {
+    let lines = attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident("doc"))
+        .filter_map(|attr| match attr.parse_meta() {
+            Ok(Meta::NameValue(MetaNameValue {
+                lit: Lit::Str(doc), ..
+            })) => Some(doc.value().trim().to_owned()),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    let docs = lines.join("\n");
+    let docs = docs.trim();
+    if docs.is_empty() {
+        None
+    } else {
+        Some(docs.to_owned())
+    }
+}
+
+/// Returns parameters style of a method.
+///
+/// Methods with a single argument send it as the parameters unless a style is set.
+/// Other methods use positional parameters by default.
+pub fn params_style(trait_attr: &TraitAttr, attr: &MethodAttr, inputs: usize) -> ParamsStyle {
+    match attr.params.or(trait_attr.params) {
+        Some(style) => style,
+        None if inputs == 1 => ParamsStyle::Single,
+        None => ParamsStyle::Positional,
+    }
 }
 
 pub fn parse_method_attrs(
@@ -82,6 +201,27 @@ pub fn parse_method_attrs(
     Ok((attr, forward))
 }
 
+/// Returns an owned type of a referenced type.
+fn owned_type(ty: &Type) -> Type {
+    match ty {
+        Type::Path(path) if path.qself.is_none() && path.path.is_ident("str") => {
+            parse_quote! { String }
+        }
+        Type::Slice(slice) => {
+            let elem = &slice.elem;
+            parse_quote! { Vec<#elem> }
+        }
+        ty => ty.clone(),
+    }
+}
+
+fn expect_arg_type(arg: &FnArg) -> &Type {
+    match arg {
+        FnArg::Typed(ty) => ty.ty.as_ref(),
+        _ => unimplemented!(),
+    }
+}
+
 fn expect_arg_name(arg: &FnArg) -> Ident {
     match arg {
         FnArg::Typed(ty) => match ty.pat.as_ref() {
@@ -20,7 +20,10 @@ fn filter_method_trait_item(item: &TraitItem) -> Option<TraitItemMethod> {
     }
 }
 
-fn map_method_trait_item(method: TraitItemMethod) -> std::result::Result<MethodDef, TokenStream2> {
+fn map_method_trait_item(
+    trait_attr: &TraitAttr,
+    method: TraitItemMethod,
+) -> std::result::Result<MethodDef, TokenStream2> {
     let (attr, attributes) = match parse_method_attrs(&method.attrs) {
         Ok(res) => res,
         Err(err) => return Err(err),
@@ -32,21 +35,202 @@ fn map_method_trait_item(method: TraitItemMethod) -> std::result::Result<MethodD
         #(#attributes)*
         async fn #name (#inputs) #output;
     };
+    // Skip `self` argument
+    let style = params_style(trait_attr, &attr, inputs.len().saturating_sub(1));
     Ok(MethodDef {
         attr,
+        style,
         item: method.clone(),
         definition,
         attributes,
     })
 }
 
+/// Creates statements binding `__params` to parameters of a method call
+/// and an expression of the parameters to send.
+fn make_params(def: &MethodDef) -> (TokenStream2, TokenStream2) {
+    let input_names = def.input_names();
+    match def.style {
+        ParamsStyle::Single => (quote! {}, quote! { Some(& #input_names ) }),
+        ParamsStyle::Positional if input_names.is_empty() => (
+            quote! { let __params: [(); 0] = []; },
+            quote! { Some(&__params) },
+        ),
+        ParamsStyle::Positional => (
+            quote! { let __params = ( #input_names , ); },
+            quote! { Some(&__params) },
+        ),
+        ParamsStyle::Named => {
+            let len = input_names.len();
+            let keys = input_names.iter().map(|name| name.to_string());
+            let fields = (0..len).map(Index::from);
+            let generics = (0..len)
+                .map(|i| Ident::new(&format!("__P{}", i), Span::call_site()))
+                .collect::<Vec<_>>();
+            let statements = quote! {
+                struct __Params<#(#generics),*>(#(#generics),*);
+
+                impl<#(#generics: net3_rpc_client::serde::Serialize),*> net3_rpc_client::serde::Serialize for __Params<#(#generics),*> {
+                    #[allow(unused_mut)]
+                    fn serialize<__S: net3_rpc_client::serde::Serializer>(
+                        &self,
+                        __serializer: __S,
+                    ) -> std::result::Result<__S::Ok, __S::Error> {
+                        use net3_rpc_client::serde::ser::SerializeMap;
+                        let mut __map = __serializer.serialize_map(Some(#len))?;
+                        #(__map.serialize_entry(#keys, &self.#fields)?;)*
+                        __map.end()
+                    }
+                }
+
+                let __params = __Params( #input_names );
+            };
+            (statements, quote! { Some(&__params) })
+        }
+    }
+}
+
+/// Creates statements reading `params` structure of method call arguments
+/// from a `message`, it is an error if parameters are not of the method style.
+fn make_params_reader(def: &MethodDef) -> TokenStream2 {
+    let input_names = def.input_names();
+    let names = input_names.iter().collect::<Vec<_>>();
+    let keys = names
+        .iter()
+        .map(|name| name.to_string())
+        .collect::<Vec<_>>();
+    let types = def.param_types();
+    let len = names.len();
+    let indices = 0..len;
+    let visitor = match def.style {
+        ParamsStyle::Named => quote! {
+            fn visit_map<__A: net3_rpc_client::serde::de::MapAccess<'de>>(
+                self,
+                mut __map: __A,
+            ) -> std::result::Result<__Params, __A::Error> {
+                const FIELDS: &[&str] = &[#(#keys),*];
+                #(let mut #names = None;)*
+                while let Some(__key) = __map.next_key::<String>()? {
+                    match __key.as_str() {
+                        #(#keys => {
+                            if #names.is_some() {
+                                return Err(net3_rpc_client::serde::de::Error::duplicate_field(#keys));
+                            }
+                            #names = Some(__map.next_value()?);
+                        })*
+                        __key => return Err(net3_rpc_client::serde::de::Error::unknown_field(__key, FIELDS)),
+                    }
+                }
+                Ok(__Params {
+                    #(#names: #names.ok_or_else(|| net3_rpc_client::serde::de::Error::missing_field(#keys))?,)*
+                })
+            }
+        },
+        _ => quote! {
+            fn visit_seq<__A: net3_rpc_client::serde::de::SeqAccess<'de>>(
+                self,
+                mut __seq: __A,
+            ) -> std::result::Result<__Params, __A::Error> {
+                #(let #names = __seq
+                    .next_element()?
+                    .ok_or_else(|| net3_rpc_client::serde::de::Error::invalid_length(#indices, &self))?;)*
+                if __seq.next_element::<net3_rpc_client::serde::de::IgnoredAny>()?.is_some() {
+                    return Err(net3_rpc_client::serde::de::Error::invalid_length(#len + 1, &self));
+                }
+                Ok(__Params { #(#names),* })
+            }
+        },
+    };
+    let (expecting, deserialize) = match def.style {
+        ParamsStyle::Named => ("named parameters", quote! { deserialize_map }),
+        _ => ("positional parameters", quote! { deserialize_seq }),
+    };
+    let missing = if len == 0 {
+        quote! { Ok(__Params {}) }
+    } else {
+        quote! { Err(std::io::ErrorKind::InvalidInput.into()) }
+    };
+    quote! {
+        struct __Params {
+            #(#names: #types,)*
+        }
+
+        impl<'de> net3_rpc_client::serde::Deserialize<'de> for __Params {
+            fn deserialize<__D: net3_rpc_client::serde::Deserializer<'de>>(
+                __deserializer: __D,
+            ) -> std::result::Result<Self, __D::Error> {
+                struct __Visitor;
+
+                impl<'de> net3_rpc_client::serde::de::Visitor<'de> for __Visitor {
+                    type Value = __Params;
+
+                    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                        formatter.write_str(#expecting)
+                    }
+
+                    #visitor
+                }
+
+                __deserializer.#deserialize(__Visitor)
+            }
+        }
+
+        let params: std::io::Result<__Params> = message.read_optional().and_then(|params| match params {
+            Some(params) => Ok(params),
+            None => #missing,
+        });
+    }
+}
+/// Returns an argument passing a decoded parameter to a method.
+///
+/// Parameters are decoded as owned values, references to them are passed
+/// to methods taking references.
+fn pass_param(ty: &Type, param: TokenStream2) -> TokenStream2 {
+    match ty {
+        Type::Reference(TypeReference {
+            mutability: Some(_),
+            ..
+        }) => quote! { &mut #param },
+        Type::Reference(_) => quote! { &#param },
+        _ => param,
+    }
+}
+
 fn make_method_message_handler(def: &MethodDef) -> TokenStream2 {
     let name = def.name();
     let method_name = def.method_name();
+    let (reader, args) = match def.style {
+        ParamsStyle::Single => {
+            let ty = &def.param_types()[0];
+            let reader = quote! { let params = message.read::<#ty>(); };
+            let arg = pass_param(def.input_types()[0], quote! { params });
+            (reader, arg)
+        }
+        _ => {
+            let args = def
+                .input_types()
+                .into_iter()
+                .zip(def.input_names())
+                .map(|(ty, name)| pass_param(ty, quote! { params.#name }));
+            let reader = make_params_reader(def);
+            (reader, quote! { #(#args),* })
+        }
+    };
     quote! {
         #method_name => {
-            let params = message.read()?;
-            match self. #name (&params).await {
+            #reader
+            #[allow(unused_mut)]
+            let mut params = match params {
+                Ok(params) => params,
+                Err(err) => {
+                    let err = net3_msg::types::Error::new(
+                        net3_msg::types::ErrorKind::InvalidParams,
+                        Some(err.to_string()),
+                    );
+                    return Ok(builder::new_error_response::<M>(&message, err).build());
+                }
+            };
+            match self. #name (#args).await {
                 Ok(response) => {
                     Ok(builder::new_response::<M>(&message).with_data(&response)?.build())
                 },
@@ -66,15 +250,16 @@ fn make_request_method(def: &MethodDef) -> TokenStream2 {
     let method_name = def.method_name();
     // Get inputs without `self`
     let inputs = def.inputs();
-    // Get input names
-    let input_names = def.input_names();
+    // Get parameters to send
+    let (statements, params) = make_params(def);
     quote! {
         fn #name <M> (#inputs) -> std::io::Result<M>
         where
             M: net3_msg::builder::MessageBuilderExt<Builder = M> + Send + Sync + 'static,
             M: net3_msg::builder::MessageBuilder<M>,
         {
-            net3_msg::builder::new_request::<M, _>(net3_msg::types::Id::Null, #method_name, Some(#input_names))
+            #statements
+            net3_msg::builder::new_request::<M, _>(net3_msg::types::Id::Null, #method_name, #params)
         }
     }
 }
@@ -85,21 +270,110 @@ fn make_method_client(def: &MethodDef) -> TokenStream2 {
     let inputs = &sig.inputs;
     let output = &sig.output;
     let method_name = def.method_name();
-    let input_names = def.input_names();
+    let (statements, params) = make_params(def);
     quote! {
         async fn #name (#inputs) #output {
-            self.request( #method_name , Some( #input_names )).await
+            #statements
+            self.request( #method_name , #params ).await
         }
     }
 }
 
-pub fn make_rpc(item: ItemTrait) -> Result<TokenStream2> {
+/// Creates a function returning OpenRPC document of the trait.
+fn make_openrpc(item: &ItemTrait, methods: &[MethodDef]) -> Result<TokenStream2> {
+    let title = item.ident.to_string();
+    let description = parse_docs(&item.attrs).map(|docs| {
+        quote! { generator.set_description(#docs); }
+    });
+    let methods = methods
+        .iter()
+        .map(|def| {
+            let method_name = def.method_name();
+            let names = def
+                .input_names()
+                .iter()
+                .map(Ident::to_string)
+                .collect::<Vec<_>>();
+            let types = def.param_types();
+            let result = def.result_type().ok_or_else(|| {
+                Error::new_spanned(&def.signature().output, "expected `Result<T>` return type")
+            })?;
+            let description = match def.docs() {
+                Some(docs) => quote! { Some(#docs.to_owned()) },
+                None => quote! { None },
+            };
+            // Single argument is sent as the parameters, it is described by its schema
+            let params = match def.style {
+                ParamsStyle::Single => {
+                    let ty = &types[0];
+                    let name = &names[0];
+                    quote! {
+                        let (params, param_structure) = generator.value_params::<#ty>(#name);
+                    }
+                }
+                ParamsStyle::Positional => quote! {
+                    let params = vec![#(generator.content::<#types>(#names)),*];
+                    let param_structure = Some(net3_rpc_openrpc::ParamStructure::ByPosition);
+                },
+                ParamsStyle::Named => quote! {
+                    let params = vec![#(generator.content::<#types>(#names)),*];
+                    let param_structure = Some(net3_rpc_openrpc::ParamStructure::ByName);
+                },
+            };
+            // Derived handlers answer with `InvalidParams` on mismatch
+            let mut errors = Vec::new();
+            if !names.is_empty() {
+                errors.push(quote! { net3_rpc_openrpc::Error::invalid_params() });
+            }
+            errors.extend(def.attr.errors.iter().map(|error| {
+                let code = error.code;
+                let message = &error.message;
+                quote! {
+                    net3_rpc_openrpc::Error {
+                        code: #code,
+                        message: #message.to_owned(),
+                    }
+                }
+            }));
+            Ok(quote! {
+                #params
+                let result = generator.content::<#result>("result");
+                generator.add_method(net3_rpc_openrpc::Method {
+                    name: #method_name.to_owned(),
+                    description: #description,
+                    params,
+                    result,
+                    errors: vec![#(#errors),*],
+                    param_structure,
+                });
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(quote! {
+        /// Returns OpenRPC document of the service.
+        ///
+        /// Document is generated once and cached.
+        fn openrpc() -> &'static net3_rpc_openrpc::OpenRpc {
+            static DOCUMENT: std::sync::OnceLock<net3_rpc_openrpc::OpenRpc> =
+                std::sync::OnceLock::new();
+            DOCUMENT.get_or_init(|| {
+                let mut generator =
+                    net3_rpc_openrpc::Generator::new(#title, env!("CARGO_PKG_VERSION"));
+                #description
+                #(#methods)*
+                generator.build()
+            })
+        }
+    })
+}
+
+pub fn make_rpc(attr: TraitAttr, item: ItemTrait) -> Result<TokenStream2> {
     // Filter RPC methods
     let methods = item
         .items
         .iter()
         .filter_map(filter_method_trait_item)
-        .map(map_method_trait_item)
+        .map(|method| map_method_trait_item(&attr, method))
         .collect::<std::result::Result<Vec<_>, TokenStream2>>();
     let methods = match methods {
         Ok(methods) => methods,
@@ -140,6 +414,23 @@ pub fn make_rpc(item: ItemTrait) -> Result<TokenStream2> {
     // Create client methods
     let methods_client = methods.iter().map(make_method_client).collect::<Vec<_>>();
 
+    // Create OpenRPC document and discovery method handler
+    let (openrpc, discover_handler, discover_method) = if attr.openrpc {
+        let openrpc = match make_openrpc(&item, &methods) {
+            Ok(openrpc) => openrpc,
+            Err(err) => return Ok(err.to_compile_error()),
+        };
+        let discover_handler = quote! {
+            net3_rpc_openrpc::DISCOVER_METHOD => {
+                Ok(builder::new_response::<M>(&message).with_data(Self::openrpc())?.build())
+            },
+        };
+        let discover_method = quote! { net3_rpc_openrpc::DISCOVER_METHOD, };
+        (openrpc, discover_handler, discover_method)
+    } else {
+        (quote! {}, quote! {}, quote! {})
+    };
+
     let vis = &item.vis;
     let unsafety = &item.unsafety;
     let trait_token = &item.trait_token;
@@ -154,10 +445,12 @@ pub fn make_rpc(item: ItemTrait) -> Result<TokenStream2> {
             #(#fwd_trait_items)*
             #(#definitions)*
             #(#method_requests)*
+            #openrpc
 
             fn methods() -> &'static [&'static str] {
                 &[
                     #(#methods_slice)*
+                    #discover_method
                 ]
             }
 
@@ -170,6 +463,7 @@ pub fn make_rpc(item: ItemTrait) -> Result<TokenStream2> {
                 let method = message.method().expect("method name");
                 match method {
                     #(#method_handlers)*
+                    #discover_handler
                     method => Ok(builder::new_error_response::<M>(
                         &message,
                         net3_msg::types::ErrorKind::MethodNotFound.into(),
@@ -180,7 +474,10 @@ pub fn make_rpc(item: ItemTrait) -> Result<TokenStream2> {
         }
 
         #[async_trait::async_trait]
-        impl<M: net3_msg::traits::Message> #rpc_trait_name for net3_rpc_client::Handle<M> {
+        impl<__R> #rpc_trait_name for __R
+        where
+            __R: net3_rpc_client::Requester + Send + Sync,
+        {
             #(#methods_client)*
         }
 
@@ -1,11 +1,32 @@
 mod impls;
 
+use darling::FromMeta;
 use proc_macro::TokenStream;
 
+/// Derives a message handler and a client of an RPC trait.
+///
+/// Client is implemented for every `net3_rpc_client::Requester`,
+/// like a client `Handle` or a client of the HTTP transport.
+///
+/// Parameters of methods with multiple arguments are sent as an array by default.
+/// It can be changed with `#[rpc(params = "positional")]` or `#[rpc(params = "named")]`
+/// on the trait or a method, requests are answered with `InvalidParams` error
+/// if the parameters are of a different shape.
+/// Arguments of `&str` and `&[T]` types are read as `String` and `Vec<T>`.
+///
+/// Traits with `#[rpc(openrpc)]` describe their methods in an OpenRPC document
+/// returned by `openrpc()` and `rpc.discover` method, it requires `net3_rpc_openrpc`
+/// dependency and `JsonSchema` implementations of parameter and result types.
+/// Method errors are described with `#[rpc(error(code = 1, message = "..."))]`.
 #[proc_macro_attribute]
-pub fn rpc(_attr: TokenStream, item: TokenStream) -> TokenStream {
+pub fn rpc(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let attr = syn::parse_macro_input!(attr as syn::AttributeArgs);
+    let attr = match self::impls::method::TraitAttr::from_list(&attr) {
+        Ok(attr) => attr,
+        Err(err) => return err.write_errors().into(),
+    };
     let item = syn::parse_macro_input!(item as syn::ItemTrait);
-    self::impls::make_rpc(item)
+    self::impls::make_rpc(attr, item)
         .unwrap_or_else(|e| e.to_compile_error())
         .into()
 }
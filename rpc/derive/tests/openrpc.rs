@@ -0,0 +1,102 @@
+#[macro_use]
+extern crate serde_derive;
+
+use net3_msg::{compact::Message, prelude::*};
+use net3_rpc_derive::rpc;
+use net3_rpc_error::Result;
+
+#[derive(Deserialize, Serialize, schemars::JsonSchema)]
+struct Worker {
+    name: String,
+}
+
+#[derive(Deserialize, Serialize, schemars::JsonSchema)]
+struct Job {
+    id: String,
+    target: String,
+}
+
+/// Mining pool service.
+#[rpc(openrpc)]
+trait Pool {
+    /// Returns current job of a worker.
+    #[rpc(name = "getjob", error(code = 24, message = "Unauthorized worker"))]
+    async fn get_job(&mut self, worker: &Worker) -> Result<Job>;
+
+    #[rpc(params = "named")]
+    async fn submit(&mut self, job_id: String, nonce: u64) -> Result<bool>;
+}
+
+struct PoolHandler;
+
+#[async_trait::async_trait]
+impl Pool for PoolHandler {
+    async fn get_job(&mut self, worker: &Worker) -> Result<Job> {
+        Ok(Job {
+            id: worker.name.clone(),
+            target: "ff".to_owned(),
+        })
+    }
+
+    async fn submit(&mut self, _job_id: String, _nonce: u64) -> Result<bool> {
+        Ok(true)
+    }
+}
+
+#[test]
+fn openrpc_document() {
+    use serde_json::json;
+
+    let document = serde_json::to_value(PoolHandler::openrpc()).unwrap();
+    assert_eq!(document["info"]["title"], json!("Pool"));
+    assert_eq!(
+        document["info"]["description"],
+        json!("Mining pool service.")
+    );
+    assert_eq!(
+        document["methods"][0],
+        json!({
+            "name": "getjob",
+            "description": "Returns current job of a worker.",
+            "params": [{
+                "name": "name",
+                "schema": {"type": "string"},
+                "required": true,
+            }],
+            "result": {
+                "name": "result",
+                "schema": {"$ref": "#/components/schemas/Job"},
+                "required": true,
+            },
+            "errors": [
+                {"code": -32602, "message": "Invalid params"},
+                {"code": 24, "message": "Unauthorized worker"},
+            ],
+            "paramStructure": "by-name",
+        })
+    );
+    let submit = &document["methods"][1];
+    assert_eq!(submit["name"], json!("submit"));
+    assert_eq!(submit["paramStructure"], json!("by-name"));
+    assert_eq!(submit["params"][0]["schema"], json!({"type": "string"}));
+    assert_eq!(submit["params"][1]["name"], json!("nonce"));
+    assert_eq!(submit["result"]["schema"], json!({"type": "boolean"}));
+    let schemas = document["components"]["schemas"].as_object().unwrap();
+    assert!(schemas.contains_key("Worker") && schemas.contains_key("Job"));
+    assert!(PoolHandler::methods().contains(&net3_rpc_openrpc::DISCOVER_METHOD));
+    // Document is generated once.
+    assert!(std::ptr::eq(PoolHandler::openrpc(), PoolHandler::openrpc()));
+}
+
+#[tokio::test]
+async fn openrpc_discover() {
+    let request =
+        builder::new_request::<Message, ()>(Id::Num(1), net3_rpc_openrpc::DISCOVER_METHOD, None)
+            .unwrap()
+            .build();
+    let response = PoolHandler.handle_message(request).await.unwrap();
+    assert_eq!(
+        &response.read::<net3_rpc_openrpc::OpenRpc>().unwrap(),
+        PoolHandler::openrpc()
+    );
+}
@@ -0,0 +1,179 @@
+#[macro_use]
+extern crate serde_derive;
+
+use net3_msg::{compact::Message, prelude::*};
+use net3_rpc_derive::rpc;
+use net3_rpc_error::Result;
+
+#[derive(Debug, Deserialize, Serialize)]
+struct MyMessage {
+    test: String,
+}
+
+/// Login RPC trait.
+#[rpc]
+trait Login {
+    #[rpc]
+    async fn login(&mut self, msg: &MyMessage) -> Result<MyMessage>;
+}
+
+struct LoginHandler;
+
+#[async_trait::async_trait]
+impl Login for LoginHandler {
+    async fn login(&mut self, msg: &MyMessage) -> Result<MyMessage> {
+        Ok(MyMessage {
+            test: format!("very {}", msg.test),
+        })
+    }
+}
+
+#[tokio::test]
+async fn invalid_params_response() {
+    let request = builder::new_request::<Message, _>(Id::Num(1), "login", Some(&1))
+        .unwrap()
+        .build();
+    let response = LoginHandler.handle_message(request).await.unwrap();
+    assert_eq!(response.kind(), MessageKind::ErrorResponse);
+    assert_eq!(response.error_kind(), Some(&ErrorKind::InvalidParams));
+}
+
+#[tokio::test]
+async fn method_response() {
+    let params = MyMessage {
+        test: "good".to_owned(),
+    };
+    let request = builder::new_request::<Message, _>(Id::Num(1), "login", Some(&params))
+        .unwrap()
+        .build();
+    let response = LoginHandler.handle_message(request).await.unwrap();
+    assert_eq!(response.kind(), MessageKind::Response);
+    assert_eq!(response.read::<MyMessage>().unwrap().test, "very good");
+}
+
+/// RPC trait with borrowed arguments.
+#[rpc]
+trait Words {
+    #[rpc]
+    async fn upper(&mut self, word: &str) -> Result<String>;
+
+    #[rpc]
+    async fn repeat(&mut self, word: &str, counts: &[usize]) -> Result<Vec<String>>;
+}
+
+struct WordsHandler;
+
+#[async_trait::async_trait]
+impl Words for WordsHandler {
+    async fn upper(&mut self, word: &str) -> Result<String> {
+        Ok(word.to_uppercase())
+    }
+
+    async fn repeat(&mut self, word: &str, counts: &[usize]) -> Result<Vec<String>> {
+        Ok(counts.iter().map(|count| word.repeat(*count)).collect())
+    }
+}
+
+#[tokio::test]
+async fn borrowed_params() {
+    use serde_json::json;
+
+    let request = WordsHandler::upper_request::<Message>("abc").unwrap();
+    assert_eq!(serde_json::to_value(request.data()).unwrap(), json!("abc"));
+    let response = WordsHandler.handle_message(request).await.unwrap();
+    assert_eq!(response.read::<String>().unwrap(), "ABC");
+
+    let request = WordsHandler::repeat_request::<Message>("ab", &[1, 2]).unwrap();
+    assert_eq!(
+        serde_json::to_value(request.data()).unwrap(),
+        json!(["ab", [1, 2]])
+    );
+    let response = WordsHandler.handle_message(request).await.unwrap();
+    assert_eq!(response.read::<Vec<String>>().unwrap(), vec!["ab", "abab"]);
+}
+
+/// Arithmetic RPC trait with multiple arguments.
+#[rpc(params = "named")]
+trait Calc {
+    #[rpc]
+    async fn add(&mut self, a: &i64, b: &i64) -> Result<i64>;
+
+    #[rpc(params = "positional")]
+    async fn sub(&mut self, a: i64, b: i64) -> Result<i64>;
+
+    #[rpc(params = "positional")]
+    async fn zero(&mut self) -> Result<i64>;
+}
+
+struct CalcHandler;
+
+#[async_trait::async_trait]
+impl Calc for CalcHandler {
+    async fn add(&mut self, a: &i64, b: &i64) -> Result<i64> {
+        Ok(a + b)
+    }
+
+    async fn sub(&mut self, a: i64, b: i64) -> Result<i64> {
+        Ok(a - b)
+    }
+
+    async fn zero(&mut self) -> Result<i64> {
+        Ok(0)
+    }
+}
+
+#[test]
+fn multiple_params_request() {
+    use serde_json::json;
+
+    let request = CalcHandler::add_request::<Message>(&1, &2).unwrap();
+    assert_eq!(
+        serde_json::to_value(request.data()).unwrap(),
+        json!({"a": 1, "b": 2})
+    );
+    let request = CalcHandler::sub_request::<Message>(5, 3).unwrap();
+    assert_eq!(serde_json::to_value(request.data()).unwrap(), json!([5, 3]));
+    let request = CalcHandler::zero_request::<Message>().unwrap();
+    assert_eq!(serde_json::to_value(request.data()).unwrap(), json!([]));
+}
+
+#[tokio::test]
+async fn multiple_params_response() {
+    let request = CalcHandler::add_request::<Message>(&1, &2).unwrap();
+    let response = CalcHandler.handle_message(request).await.unwrap();
+    assert_eq!(response.read::<i64>().unwrap(), 3);
+    let request = CalcHandler::sub_request::<Message>(5, 3).unwrap();
+    let response = CalcHandler.handle_message(request).await.unwrap();
+    assert_eq!(response.read::<i64>().unwrap(), 2);
+    let request = builder::new_request::<Message, ()>(Id::Num(1), "zero", None)
+        .unwrap()
+        .build();
+    let response = CalcHandler.handle_message(request).await.unwrap();
+    assert_eq!(response.read::<i64>().unwrap(), 0);
+}
+
+#[tokio::test]
+async fn multiple_params_mismatch() {
+    let requests = vec![
+        ("add", serde_json::json!([1, 2])),
+        ("add", serde_json::json!({"a": 1})),
+        ("add", serde_json::json!({"a": 1, "b": 2, "c": 3})),
+        ("sub", serde_json::json!({"a": 5, "b": 3})),
+        ("sub", serde_json::json!([5])),
+        ("sub", serde_json::json!([5, 3, 1])),
+        ("zero", serde_json::json!([1])),
+    ];
+    for (method, params) in requests {
+        let request = builder::new_request::<Message, _>(Id::Num(1), method, Some(&params))
+            .unwrap()
+            .build();
+        let response = CalcHandler.handle_message(request).await.unwrap();
+        assert_eq!(
+            response.error_kind(),
+            Some(&ErrorKind::InvalidParams),
+            "{} {}",
+            method,
+            params
+        );
+    }
+}
@@ -51,6 +51,10 @@ impl Handler for MyHandler {
             }
         }
     }
+
+    fn fork(&self) -> Option<Self> {
+        Some(self.clone())
+    }
 }
 
 #[tokio::main]
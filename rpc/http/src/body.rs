@@ -0,0 +1,34 @@
+//! HTTP message bodies.
+
+use std::io::{Error, ErrorKind, Result};
+
+use bytes::{Bytes, BytesMut};
+use hyper::{body::HttpBody, Body};
+
+use net3_msg::{frame, prelude::Batch};
+use net3_proto_jsonrpc::Message;
+use net3_rpc_client::Limits;
+
+/// Reads a body up to the frame length limit.
+///
+/// Returns [`LimitError`] converted into an IO error if the body is too large.
+///
+/// [`LimitError`]: ../../net3_codec_limits/enum.LimitError.html
+pub(crate) async fn read_body(mut body: Body, limits: &Limits) -> Result<Bytes> {
+    let length = body.size_hint().lower() as usize;
+    limits.check_frame_length(length)?;
+    let mut buffer = BytesMut::with_capacity(length);
+    while let Some(chunk) = body.data().await {
+        let chunk = chunk.map_err(Error::other)?;
+        limits.check_frame_length(buffer.len() + chunk.len())?;
+        buffer.extend_from_slice(&chunk);
+    }
+    Ok(buffer.freeze())
+}
+
+/// Decodes a body checked against the limits.
+pub(crate) fn decode(body: &Bytes, limits: &Limits) -> Result<Batch<Message>> {
+    net3_codec_json_lines::check_limits(body, limits)?;
+    frame::scope(body, || serde_json::from_slice(body))
+        .map_err(|err| Error::new(ErrorKind::InvalidData, err))
+}
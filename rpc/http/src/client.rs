@@ -0,0 +1,526 @@
+//! JSON-RPC over HTTP client.
+//!
+//! Every call is sent in a `POST` request, batches of calls are sent in a single one.
+//! Connections are pooled and reused between calls.
+//!
+//! [`HttpClient`] implements [`Requester`], clients of `#[rpc]` traits
+//! work the same as over a client [`Handle`]. Streaming calls are not supported.
+//!
+//! [`HttpClient`]: struct.HttpClient.html
+//! [`Requester`]: ../../net3_rpc_client/traits/trait.Requester.html
+//! [`Handle`]: ../../net3_rpc_client/handle/struct.Handle.html
+
+use std::{
+    collections::HashMap,
+    io::{Error, ErrorKind},
+    marker::PhantomData,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use hyper::{client::HttpConnector, header, Body, Client, Method, Request, StatusCode, Uri};
+use serde::{de::DeserializeOwned, ser::Serialize};
+use tokio::{
+    sync::oneshot::{channel, Receiver, Sender},
+    time::timeout,
+};
+
+use net3_msg::{prelude::*, types::TIMEOUT_META};
+use net3_proto_jsonrpc::Message;
+use net3_rpc_client::{deadline, id::response_key, IdGenerator, Limits, Requester};
+use net3_rpc_error::{Error as CallError, Result};
+
+use crate::body::{decode, read_body};
+
+/// Response result of a call in a batch.
+type Response = std::result::Result<Message, CallError>;
+
+/// JSON-RPC over HTTP [`HttpClient`] builder.
+///
+/// [`HttpClient`]: struct.HttpClient.html
+pub struct HttpClientBuilder {
+    request_timeout: Duration,
+    id_generator: IdGenerator,
+    propagate_deadlines: bool,
+    limits: Limits,
+    pool_idle_timeout: Option<Duration>,
+    pool_max_idle_per_host: usize,
+}
+
+impl HttpClientBuilder {
+    /// Sets default timeout on [`request_opt`] call.
+    ///
+    /// Default request timeout is set to 3 seconds.
+    ///
+    /// [`request_opt`]: struct.HttpClient.html#method.request_opt
+    pub fn with_call_timeout(mut self, timeout: Duration) -> Self {
+        self.request_timeout = timeout;
+        self
+    }
+
+    /// Sets request ID generation strategy.
+    ///
+    /// Default strategy creates string identifiers from a request counter.
+    pub fn with_id_generator(mut self, generator: IdGenerator) -> Self {
+        self.id_generator = generator;
+        self
+    }
+
+    /// Encodes request timeout in the metadata of sent requests.
+    ///
+    /// Server handles the request only until the timeout elapses,
+    /// see [`deadline`] module. Disabled by default.
+    ///
+    /// [`deadline`]: ../../net3_rpc_client/deadline/index.html
+    pub fn with_deadline_propagation(mut self) -> Self {
+        self.propagate_deadlines = true;
+        self
+    }
+
+    /// Sets resource limits applied to every response body.
+    ///
+    /// Default limits are defined in [`Limits`].
+    ///
+    /// [`Limits`]: ../../net3_codec_limits/struct.Limits.html
+    pub fn with_limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Sets timeout of idle pooled connections, `None` keeps them open.
+    ///
+    /// Default idle timeout is set to 90 seconds.
+    pub fn with_pool_idle_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.pool_idle_timeout = timeout;
+        self
+    }
+
+    /// Sets maximum amount of idle pooled connections to a host.
+    ///
+    /// Amount of idle connections is not limited by default.
+    pub fn with_pool_max_idle_per_host(mut self, max: usize) -> Self {
+        self.pool_max_idle_per_host = max;
+        self
+    }
+
+    /// Creates a client of the endpoint at `url`.
+    pub fn build(self, url: &str) -> std::io::Result<HttpClient> {
+        let uri = url
+            .parse::<Uri>()
+            .map_err(|err| Error::new(ErrorKind::InvalidInput, err))?;
+        let client = Client::builder()
+            .pool_idle_timeout(self.pool_idle_timeout)
+            .pool_max_idle_per_host(self.pool_max_idle_per_host)
+            .build_http();
+        Ok(HttpClient {
+            inner: Arc::new(InnerClient {
+                client,
+                uri,
+                requests: AtomicU64::default(),
+                id_generator: self.id_generator,
+                request_timeout: self.request_timeout,
+                propagate_deadlines: self.propagate_deadlines,
+                limits: self.limits,
+            }),
+        })
+    }
+}
+
+impl Default for HttpClientBuilder {
+    fn default() -> Self {
+        HttpClientBuilder {
+            request_timeout: Duration::from_secs(3),
+            id_generator: IdGenerator::default(),
+            propagate_deadlines: false,
+            limits: Limits::default(),
+            pool_idle_timeout: Some(Duration::from_secs(90)),
+            pool_max_idle_per_host: usize::MAX,
+        }
+    }
+}
+
+/// Inner client data representation.
+struct InnerClient {
+    /// Pooled HTTP client.
+    client: Client<HttpConnector>,
+    /// Endpoint URI.
+    uri: Uri,
+    /// Atomic request counter for message ID.
+    requests: AtomicU64,
+    /// Request ID generation strategy.
+    id_generator: IdGenerator,
+    /// Default request timeout used on a [`request_opt`] call.
+    ///
+    /// [`request_opt`]: struct.HttpClient.html#method.request_opt
+    request_timeout: Duration,
+    /// Encode request timeout in the request metadata.
+    propagate_deadlines: bool,
+    /// Response body limits.
+    limits: Limits,
+}
+
+/// JSON-RPC over HTTP client.
+///
+/// Clones share the connection pool and the request counter.
+#[derive(Clone)]
+pub struct HttpClient {
+    inner: Arc<InnerClient>,
+}
+
+impl HttpClient {
+    /// Creates a client of the endpoint at `url` with default settings.
+    pub fn new(url: &str) -> std::io::Result<Self> {
+        HttpClientBuilder::default().build(url)
+    }
+
+    /// Creates a new client builder.
+    pub fn builder() -> HttpClientBuilder {
+        HttpClientBuilder::default()
+    }
+
+    /// Returns URI of the endpoint.
+    pub fn uri(&self) -> &Uri {
+        &self.inner.uri
+    }
+
+    /// Sends a method call request.
+    ///
+    /// Default timeout duration is used to await for the response.
+    pub async fn request_opt<V: Serialize, R: DeserializeOwned>(
+        &self,
+        method: &str,
+        params: Option<&V>,
+    ) -> Result<Option<R>> {
+        self.request_timeout(method, params, self.inner.request_timeout)
+            .await
+    }
+
+    /// Helper for sending requests with no parameters.
+    ///
+    /// Otherwise one would have to use confusing `None as Option<&()>`.
+    pub async fn request_empty_opt<R: DeserializeOwned>(&self, method: &str) -> Result<Option<R>> {
+        self.request_opt(method, None as Option<&()>).await
+    }
+
+    /// Sends a method call request.
+    ///
+    /// Timeouts with a default timeout set with [`with_call_timeout`].
+    ///
+    /// [`with_call_timeout`]: struct.HttpClientBuilder.html#method.with_call_timeout
+    pub async fn request_timeout<V: Serialize, R: DeserializeOwned>(
+        &self,
+        method: &str,
+        params: Option<&V>,
+        request_timeout: Duration,
+    ) -> Result<Option<R>> {
+        let message = self
+            .request_message(method, params, Metadata::new(), request_timeout)
+            .await?;
+        Ok(message.read_optional()?)
+    }
+
+    /// Sends a method call request with metadata.
+    ///
+    /// Returns response together with metadata of the response message.
+    /// Default timeout duration is used to await for the response.
+    pub async fn request_with_metadata<V: Serialize, R: DeserializeOwned>(
+        &self,
+        method: &str,
+        params: Option<&V>,
+        metadata: Metadata,
+    ) -> Result<(R, Metadata)> {
+        let message = self
+            .request_message(method, params, metadata, self.inner.request_timeout)
+            .await?;
+        match message.read_optional()? {
+            Some(res) => Ok((res, message.metadata().clone())),
+            None => Err(Error::new(ErrorKind::InvalidData, "empty response").into()),
+        }
+    }
+
+    /// Sends an event message.
+    ///
+    /// Default timeout duration is used to await for the server to accept it.
+    pub async fn send_notification<T: Serialize>(
+        &self,
+        method: &str,
+        params: Option<&T>,
+    ) -> Result<()> {
+        let message = builder::new_event::<Message, T>(method, params)?.build();
+        self.post(Batch::Single(message), self.inner.request_timeout)
+            .await?;
+        Ok(())
+    }
+
+    /// Creates a batch of calls sent in a single HTTP request.
+    pub fn batch(&self) -> HttpBatchRequest<'_> {
+        HttpBatchRequest {
+            client: self,
+            messages: Vec::new(),
+            calls: HashMap::new(),
+            request_timeout: self.inner.request_timeout,
+        }
+    }
+
+    /// Sends a method call request and receives a response message.
+    ///
+    /// Timeout is limited to the deadline of a request being handled, if any.
+    async fn request_message<V: Serialize>(
+        &self,
+        method: &str,
+        params: Option<&V>,
+        mut metadata: Metadata,
+        request_timeout: Duration,
+    ) -> Result<Message> {
+        let request_timeout = self.call_timeout(&mut metadata, request_timeout);
+        let id = self.next_id();
+        let message = builder::new_request::<Message, V>(id.clone(), method, params)?
+            .with_metadata(metadata)
+            .build();
+        match self.post(Batch::Single(message), request_timeout).await? {
+            Some(Batch::Single(response)) => into_response(&id, response),
+            _ => Err(Error::new(ErrorKind::InvalidData, "unexpected response").into()),
+        }
+    }
+
+    /// Returns timeout of a call limited to the deadline of a request being handled.
+    ///
+    /// Timeout is inserted into the request `metadata` if deadlines are propagated.
+    fn call_timeout(&self, metadata: &mut Metadata, request_timeout: Duration) -> Duration {
+        let request_timeout = deadline::remaining()
+            .map_or(request_timeout, |remaining| remaining.min(request_timeout));
+        if self.inner.propagate_deadlines {
+            metadata.insert(
+                TIMEOUT_META.to_owned(),
+                request_timeout.as_millis().to_string(),
+            );
+        }
+        request_timeout
+    }
+
+    /// Returns identifier of the next request.
+    fn next_id(&self) -> Id {
+        let msg_id = self.inner.requests.fetch_add(1, Ordering::SeqCst);
+        self.inner.id_generator.generate(msg_id)
+    }
+
+    /// Posts messages within the timeout.
+    ///
+    /// Returns `None` if the server accepted them without a response.
+    async fn post(
+        &self,
+        messages: Batch<Message>,
+        request_timeout: Duration,
+    ) -> Result<Option<Batch<Message>>> {
+        let body =
+            serde_json::to_vec(&messages).map_err(|err| Error::new(ErrorKind::InvalidData, err))?;
+        let request = Request::builder()
+            .method(Method::POST)
+            .uri(self.inner.uri.clone())
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body))
+            .map_err(|err| Error::new(ErrorKind::InvalidInput, err))?;
+        match timeout(request_timeout, self.exchange(request)).await {
+            Ok(response) => Ok(response?),
+            Err(_) => Err(CallError::from(ErrorKind::TimedOut)),
+        }
+    }
+
+    /// Sends a HTTP request and decodes the response body.
+    async fn exchange(&self, request: Request<Body>) -> std::io::Result<Option<Batch<Message>>> {
+        let response = self
+            .inner
+            .client
+            .request(request)
+            .await
+            .map_err(Error::other)?;
+        match response.status() {
+            StatusCode::NO_CONTENT => Ok(None),
+            status if status.is_success() => {
+                let body = read_body(response.into_body(), &self.inner.limits).await?;
+                decode(&body, &self.inner.limits).map(Some)
+            }
+            status => Err(Error::other(format!("unexpected HTTP status: {}", status))),
+        }
+    }
+}
+
+#[async_trait]
+impl Requester for HttpClient {
+    async fn request_opt<V, R>(&self, method: &str, params: Option<&V>) -> Result<Option<R>>
+    where
+        V: Serialize + Sync,
+        R: DeserializeOwned + Send,
+    {
+        HttpClient::request_opt(self, method, params).await
+    }
+}
+
+/// Batch of requests and notifications sent in a single HTTP request.
+///
+/// Created with [`HttpClient::batch`]. Nothing is sent until [`send`] is called.
+///
+/// [`HttpClient::batch`]: struct.HttpClient.html#method.batch
+/// [`send`]: #method.send
+pub struct HttpBatchRequest<'a> {
+    client: &'a HttpClient,
+    messages: Vec<Message>,
+    /// Response senders by normalized request identifiers.
+    calls: HashMap<Id, Sender<Response>>,
+    /// Timeout of the batch limited to the deadline of a request being handled.
+    request_timeout: Duration,
+}
+
+impl HttpBatchRequest<'_> {
+    /// Adds a method call request to the batch.
+    ///
+    /// Returns a call receiving the response once the batch is sent.
+    pub fn request<V: Serialize, R: DeserializeOwned>(
+        &mut self,
+        method: &str,
+        params: Option<&V>,
+    ) -> Result<HttpBatchCall<R>> {
+        let mut metadata = Metadata::new();
+        self.request_timeout = self
+            .client
+            .call_timeout(&mut metadata, self.client.inner.request_timeout);
+        let id = self.client.next_id();
+        let message = builder::new_request::<Message, V>(id.clone(), method, params)?
+            .with_metadata(metadata)
+            .build();
+        let (sender, receiver) = channel();
+        self.messages.push(message);
+        self.calls.insert(response_key(&id).into_owned(), sender);
+        Ok(HttpBatchCall {
+            id,
+            receiver,
+            _marker: PhantomData,
+        })
+    }
+
+    /// Adds an event message to the batch.
+    pub fn notification<V: Serialize>(
+        &mut self,
+        method: &str,
+        params: Option<&V>,
+    ) -> std::io::Result<()> {
+        let message = builder::new_event::<Message, V>(method, params)?.build();
+        self.messages.push(message);
+        Ok(())
+    }
+
+    /// Returns amount of messages in the batch.
+    pub fn len(&self) -> usize {
+        self.messages.len()
+    }
+
+    /// Returns true if the batch is empty.
+    pub fn is_empty(&self) -> bool {
+        self.messages.is_empty()
+    }
+
+    /// Sends the batch and delivers responses to the calls.
+    ///
+    /// Empty batch is not sent. Default timeout duration is used
+    /// to await for the responses.
+    pub async fn send(mut self) -> Result<()> {
+        if self.messages.is_empty() {
+            return Ok(());
+        }
+        let messages = std::mem::take(&mut self.messages);
+        let responses = match self
+            .client
+            .post(Batch::Many(messages), self.request_timeout)
+            .await?
+        {
+            Some(Batch::Many(responses)) => responses,
+            Some(Batch::Single(response)) => vec![response],
+            None => vec![],
+        };
+        for response in responses {
+            if response.id() == &Id::Null && response.error_kind().is_some() {
+                // Whole batch was rejected by the server.
+                let err = response.into_error().expect("error");
+                for (_, sender) in self.calls.drain() {
+                    let _ = sender.send(Err(CallError::Rpc(err.clone())));
+                }
+                break;
+            }
+            match self.calls.remove(&*response_key(response.id())) {
+                Some(sender) => {
+                    let response = match response.kind() {
+                        MessageKind::ErrorResponse => {
+                            Err(CallError::Rpc(response.into_error().expect("error")))
+                        }
+                        _ => Ok(response),
+                    };
+                    let _ = sender.send(response);
+                }
+                None => log::warn!("HTTP batch response handler not found"),
+            }
+        }
+        for (id, sender) in self.calls.drain() {
+            let err = Error::new(
+                ErrorKind::InvalidData,
+                format!("missing response for id {}", id),
+            );
+            let _ = sender.send(Err(err.into()));
+        }
+        Ok(())
+    }
+}
+
+/// Method call request of a batch awaiting a response.
+///
+/// Receiving the response fails if the batch is dropped without sending,
+/// if the server did not respond to the request it fails with `InvalidData`.
+pub struct HttpBatchCall<R> {
+    id: Id,
+    receiver: Receiver<Response>,
+    _marker: PhantomData<fn() -> R>,
+}
+
+impl<R: DeserializeOwned> HttpBatchCall<R> {
+    /// Returns identifier of the request.
+    pub fn id(&self) -> &Id {
+        &self.id
+    }
+
+    /// Receives the response to the request.
+    pub async fn response(self) -> Result<R> {
+        match self.response_opt().await? {
+            Some(res) => Ok(res),
+            None => Err(Error::new(ErrorKind::InvalidData, "empty response").into()),
+        }
+    }
+
+    /// Receives the response to the request.
+    pub async fn response_opt(self) -> Result<Option<R>> {
+        match self.receiver.await {
+            Ok(Ok(message)) => Ok(message.read_optional()?),
+            Ok(Err(err)) => Err(err),
+            Err(_) => Err(CallError::from(ErrorKind::ConnectionReset)),
+        }
+    }
+}
+
+/// Returns a response to the request of `id` or its error.
+///
+/// Errors without an identifier are responses to requests the server could not read.
+fn into_response(id: &Id, message: Message) -> Result<Message> {
+    if let Err(reason) = message.validate() {
+        return Err(CallError::Rpc(reason.into()));
+    }
+    let matches = response_key(message.id()) == response_key(id);
+    match message.kind() {
+        MessageKind::Response if matches => Ok(message),
+        MessageKind::ErrorResponse if matches || message.id() == &Id::Null => {
+            Err(CallError::Rpc(message.into_error().expect("error")))
+        }
+        _ => Err(Error::new(ErrorKind::InvalidData, "unexpected response").into()),
+    }
+}
@@ -0,0 +1,23 @@
+//! JSON-RPC over HTTP transport.
+//!
+//! [`HttpServer`] decodes `POST` request bodies as JSON-RPC messages or batches
+//! and dispatches them to the same [`Handler`] implementations the TCP server uses.
+//!
+//! [`HttpClient`] sends calls in `POST` requests, it implements the same [`Requester`]
+//! trait as a client [`Handle`] and can be used with clients of `#[rpc]` traits.
+//!
+//! [`HttpServer`]: server/struct.HttpServer.html
+//! [`HttpClient`]: client/struct.HttpClient.html
+//! [`Handler`]: ../net3_rpc_client/trait.Handler.html
+//! [`Requester`]: ../net3_rpc_client/trait.Requester.html
+//! [`Handle`]: ../net3_rpc_client/handle/struct.Handle.html
+
+pub(crate) mod body;
+pub mod client;
+pub mod server;
+
+pub use self::client::{HttpBatchCall, HttpBatchRequest, HttpClient, HttpClientBuilder};
+pub use self::server::{HttpServer, HttpServerBuilder};
+
+#[cfg(test)]
+mod tests;
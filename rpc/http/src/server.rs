@@ -0,0 +1,306 @@
+//! JSON-RPC over HTTP server.
+//!
+//! Every `POST` request body is a single message or a batch of messages.
+//! Responses are sent back in the response body, requests without responses,
+//! like notifications, are answered with `204 No Content`.
+//!
+//! Handlers are built once per HTTP connection and forked for every request,
+//! handlers which can not be forked fail all requests with an internal error.
+//! Handler errors are sent back as internal error responses to each request.
+//! Handles given to the handler builder are not connected to a peer,
+//! requests sent with them fail.
+
+use std::{
+    convert::Infallible,
+    io::{Error, ErrorKind},
+    net::SocketAddr,
+    sync::Arc,
+};
+
+use hyper::{
+    header,
+    server::conn::{AddrIncoming, AddrStream},
+    service::{make_service_fn, service_fn},
+    Body, Method, Request, Response, StatusCode,
+};
+use tokio::{
+    net::{lookup_host, ToSocketAddrs},
+    sync::Mutex,
+    task::JoinHandle,
+};
+
+use net3_msg::prelude::*;
+use net3_proto_jsonrpc::Message;
+use net3_rpc_client::{
+    deadline, Builder as ClientBuilder, Handler, HandlerBuilder, LimitError, Limits,
+};
+
+use crate::body::{decode, read_body};
+
+/// JSON lines codec of a detached client builder creating handles.
+type Codec = net3_codec_json_lines::Codec<Message>;
+
+/// JSON-RPC over HTTP [`HttpServer`] builder.
+///
+/// [`HttpServer`]: struct.HttpServer.html
+pub struct HttpServerBuilder<B> {
+    builder: B,
+    limits: Limits,
+}
+
+impl<B> HttpServerBuilder<B> {
+    /// Sets server handler by default using [`CloneBuilder`].
+    ///
+    /// [`CloneBuilder`]: ../../net3_rpc_client/common/struct.CloneBuilder.html
+    pub fn with_handler<H: Handler>(mut self, handler: H) -> Self
+    where
+        B: From<H>,
+    {
+        self.builder = handler.into();
+        self
+    }
+
+    /// Sets resource limits applied to every request body.
+    ///
+    /// Default limits are defined in [`Limits`].
+    ///
+    /// [`Limits`]: ../../net3_codec_limits/struct.Limits.html
+    pub fn with_limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Binds the server to the first resolved address.
+    ///
+    /// Returns [`HttpServer`] handle.
+    ///
+    /// [`HttpServer`]: struct.HttpServer.html
+    pub async fn bind<A: ToSocketAddrs>(self, addr: A) -> Result<HttpServer<B>, Error> {
+        let addr = lookup_host(addr)
+            .await?
+            .next()
+            .ok_or_else(|| Error::new(ErrorKind::InvalidInput, "no address to bind"))?;
+        let incoming = AddrIncoming::bind(&addr).map_err(Error::other)?;
+        Ok(HttpServer {
+            incoming,
+            builder: self.builder,
+            limits: self.limits,
+        })
+    }
+}
+
+impl<B: Default> Default for HttpServerBuilder<B> {
+    fn default() -> Self {
+        HttpServerBuilder {
+            builder: Default::default(),
+            limits: Limits::default(),
+        }
+    }
+}
+
+impl<B: HandlerBuilder> From<B> for HttpServerBuilder<B> {
+    fn from(builder: B) -> Self {
+        HttpServerBuilder {
+            builder,
+            limits: Limits::default(),
+        }
+    }
+}
+
+/// JSON-RPC over HTTP server structure.
+///
+/// Builds connection handlers using a [`HandlerBuilder`].
+///
+/// [`HandlerBuilder`]: ../../net3_rpc_client/trait.HandlerBuilder.html
+pub struct HttpServer<B> {
+    incoming: AddrIncoming,
+    builder: B,
+    limits: Limits,
+}
+
+impl<B> HttpServer<B> {
+    /// Returns local address the server is bound to.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.incoming.local_addr()
+    }
+}
+
+impl<B> HttpServer<B>
+where
+    B: HandlerBuilder + Send + Sync + 'static,
+    <B as HandlerBuilder>::Handler: Handler<Message = Message> + Send + Sync + 'static,
+{
+    /// Spawns server in background by launching [`start`] using [`tokio::spawn`].
+    ///
+    /// [`start`]: #method.start
+    /// [`tokio::spawn`]: https://docs.rs/tokio/0.2/tokio/fn.spawn.html
+    pub fn background(self) -> JoinHandle<std::io::Result<()>> {
+        tokio::spawn(async move { self.start().await })
+    }
+
+    /// Starts accepting connections and handling requests.
+    pub async fn start(self) -> std::io::Result<()> {
+        let builder = Arc::new(Mutex::new(self.builder));
+        let limits = self.limits;
+        let make_service = make_service_fn(move |stream: &AddrStream| {
+            log::trace!("HTTP connection accepted from {:?}", stream.remote_addr());
+            let builder = builder.clone();
+            async move {
+                let handler = build_handler(&builder).await;
+                Ok::<_, Infallible>(service_fn(move |request| {
+                    handle_request(handler.fork(), limits, request)
+                }))
+            }
+        });
+        hyper::Server::builder(self.incoming)
+            .serve(make_service)
+            .await
+            .map_err(Error::other)
+    }
+}
+
+/// Builds a connection handler with a detached client handle.
+async fn build_handler<B: HandlerBuilder>(builder: &Mutex<B>) -> <B as HandlerBuilder>::Handler {
+    let handle = ClientBuilder::<Codec, B>::new().handle();
+    builder.lock().await.build_handler(&handle).await
+}
+
+/// Handles a HTTP request with a forked connection handler.
+async fn handle_request<H>(
+    handler: Option<H>,
+    limits: Limits,
+    request: Request<Body>,
+) -> Result<Response<Body>, Infallible>
+where
+    H: Handler<Message = Message> + Send,
+{
+    if request.method() != Method::POST {
+        return Ok(Response::builder()
+            .status(StatusCode::METHOD_NOT_ALLOWED)
+            .header(header::ALLOW, "POST")
+            .body(Body::empty())
+            .unwrap());
+    }
+    let body = match read_body(request.into_body(), &limits).await {
+        Ok(body) => body,
+        Err(err) => {
+            log::debug!("HTTP request body error: {}", err);
+            let status = match LimitError::from_io(&err) {
+                Some(_) => StatusCode::PAYLOAD_TOO_LARGE,
+                None => StatusCode::BAD_REQUEST,
+            };
+            return Ok(Response::builder()
+                .status(status)
+                .body(Body::empty())
+                .unwrap());
+        }
+    };
+    let batch = match decode(&body, &limits) {
+        Ok(batch) => batch,
+        Err(err) => {
+            log::debug!("Invalid HTTP request body: {}", err);
+            let error = types::Error::new(types::ErrorKind::ParseError, Some(err.to_string()));
+            let response = builder::new_error_response(&Message::default(), error).build();
+            return Ok(json_response(&Batch::Single(response)));
+        }
+    };
+    let mut handler = match handler {
+        Some(handler) => handler,
+        None => {
+            log::error!("HTTP request handler can not be forked");
+            return Ok(Response::builder()
+                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                .body(Body::empty())
+                .unwrap());
+        }
+    };
+    let responses = match batch {
+        Batch::Many(messages) if messages.is_empty() => {
+            let error = types::Error::new(
+                types::ErrorKind::InvalidRequest,
+                Some("empty batch".to_owned()),
+            );
+            Batch::Single(builder::new_error_response(&Message::default(), error).build())
+        }
+        Batch::Single(message) => {
+            let mut responses = handle_message(&mut handler, message).await;
+            match responses.len() {
+                1 => Batch::Single(responses.remove(0)),
+                _ => Batch::Many(responses),
+            }
+        }
+        Batch::Many(messages) => {
+            let mut responses = Vec::new();
+            for message in messages {
+                responses.extend(handle_message(&mut handler, message).await);
+            }
+            Batch::Many(responses)
+        }
+    };
+    if responses.is_empty() {
+        Ok(Response::builder()
+            .status(StatusCode::NO_CONTENT)
+            .body(Body::empty())
+            .unwrap())
+    } else {
+        Ok(json_response(&responses))
+    }
+}
+
+/// Handles a single message, returns messages to send back.
+///
+/// Messages sent in response to notifications are dropped.
+/// Request handler errors are answered with an internal error response.
+async fn handle_message<H>(handler: &mut H, message: Message) -> Vec<Message>
+where
+    H: Handler<Message = Message> + Send,
+{
+    if let Err(reason) = message.validate() {
+        log::debug!("Invalid HTTP message: {}", reason);
+        return vec![builder::new_error_response(&message, reason.into()).build()];
+    }
+    match message.kind() {
+        MessageKind::Request => {
+            let timeout = message.timeout();
+            match deadline::handle(timeout, handler.handle_request(message.clone())).await {
+                Ok(responses) => responses,
+                Err(err) => {
+                    log::debug!("HTTP request handler error: {:?}", err);
+                    let error =
+                        types::Error::new(types::ErrorKind::InternalError, Some(err.to_string()));
+                    vec![builder::new_error_response(&message, error).build()]
+                }
+            }
+        }
+        MessageKind::Event => {
+            if let Err(err) = handler.handle_notification(message).await {
+                log::debug!("HTTP notification handler error: {:?}", err);
+            }
+            vec![]
+        }
+        _ => {
+            let error = types::Error::new(
+                types::ErrorKind::InvalidRequest,
+                Some("expected a request or a notification".to_owned()),
+            );
+            vec![builder::new_error_response(&message, error).build()]
+        }
+    }
+}
+
+/// Creates a JSON response.
+fn json_response(body: &Batch<Message>) -> Response<Body> {
+    match serde_json::to_vec(body) {
+        Ok(body) => Response::builder()
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body))
+            .unwrap(),
+        Err(err) => {
+            log::error!("HTTP response serialization error: {}", err);
+            Response::builder()
+                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                .body(Body::empty())
+                .unwrap()
+        }
+    }
+}
@@ -0,0 +1,248 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use hyper::{body::to_bytes, Body, Client, Method, Request, StatusCode};
+use serde_json::{json, Value};
+
+use net3_msg::prelude::*;
+use net3_proto_jsonrpc::Message;
+use net3_rpc_client::{common::CloneBuilder, Handler, LimitError, Limits, Requester};
+
+use crate::*;
+
+/// Handler responding to requests with their parameters.
+///
+/// Requests of `sleep` method are answered after a second,
+/// requests of `fail` method fail and `skip` requests are not answered.
+#[derive(Clone)]
+struct EchoHandler;
+
+#[async_trait]
+impl Handler for EchoHandler {
+    type Event = ();
+    type Message = Message;
+
+    async fn handle_request(&mut self, message: Message) -> std::io::Result<Vec<Message>> {
+        if message.method() == Some("sleep") {
+            tokio::time::delay_for(Duration::from_secs(1)).await;
+        }
+        if message.method() == Some("fail") {
+            return Err(std::io::Error::other("failed"));
+        }
+        if message.method() == Some("skip") {
+            return Ok(vec![]);
+        }
+        let params = message.read_optional::<Value>()?;
+        Ok(vec![builder::new_response(&message)
+            .with_data(&params)?
+            .build()])
+    }
+}
+
+async fn start_server(limits: Limits) -> String {
+    let server = HttpServerBuilder::from(CloneBuilder(EchoHandler))
+        .with_limits(limits)
+        .bind("127.0.0.1:0")
+        .await
+        .unwrap();
+    let url = format!("http://{}/", server.local_addr());
+    server.background();
+    url
+}
+
+async fn post(url: &str, body: &str) -> (StatusCode, Option<Value>) {
+    let request = Request::builder()
+        .method(Method::POST)
+        .uri(url)
+        .header("content-type", "application/json")
+        .body(Body::from(body.to_owned()))
+        .unwrap();
+    let response = Client::new().request(request).await.unwrap();
+    let status = response.status();
+    let body = to_bytes(response.into_body()).await.unwrap();
+    if body.is_empty() {
+        (status, None)
+    } else {
+        (status, Some(serde_json::from_slice(&body).unwrap()))
+    }
+}
+
+#[tokio::test]
+async fn http_request() {
+    let url = start_server(Limits::default()).await;
+    let (status, body) = post(
+        &url,
+        r#"{"jsonrpc":"2.0","id":1,"method":"echo","params":[1]}"#,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        body,
+        Some(json!({"jsonrpc": "2.0", "id": 1, "result": [1]}))
+    );
+
+    let (status, body) = post(&url, r#"{"jsonrpc":"2.0","method":"log","params":[1]}"#).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    assert_eq!(body, None);
+}
+
+#[tokio::test]
+async fn http_batch() {
+    let url = start_server(Limits::default()).await;
+    let batch = r#"[
+        {"jsonrpc":"2.0","id":1,"method":"echo","params":["a"]},
+        {"jsonrpc":"2.0","method":"log","params":[]},
+        {"jsonrpc":"2.0","id":2,"method":"echo","params":["b"]}
+    ]"#;
+    let (status, body) = post(&url, batch).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        body,
+        Some(json!([
+            {"jsonrpc": "2.0", "id": 1, "result": ["a"]},
+            {"jsonrpc": "2.0", "id": 2, "result": ["b"]},
+        ]))
+    );
+
+    let (status, _) = post(&url, r#"[{"jsonrpc":"2.0","method":"log"}]"#).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    let (status, body) = post(&url, "[]").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body.unwrap()["error"]["code"], json!(-32600));
+}
+
+#[tokio::test]
+async fn http_handler_errors() {
+    let url = start_server(Limits::default()).await;
+    let (status, body) = post(&url, r#"{"jsonrpc":"2.0","id":1,"method":"fail"}"#).await;
+    assert_eq!(status, StatusCode::OK);
+    let body = body.unwrap();
+    assert_eq!(body["id"], json!(1));
+    assert_eq!(body["error"]["code"], json!(-32603));
+
+    let batch = r#"[
+        {"jsonrpc":"2.0","id":1,"method":"fail"},
+        {"jsonrpc":"2.0","method":"fail"},
+        {"jsonrpc":"2.0","id":2,"method":"echo","params":["b"]}
+    ]"#;
+    let (status, body) = post(&url, batch).await;
+    assert_eq!(status, StatusCode::OK);
+    let body = body.unwrap();
+    assert_eq!(body[0]["id"], json!(1));
+    assert_eq!(body[0]["error"]["code"], json!(-32603));
+    assert_eq!(body[1], json!({"jsonrpc": "2.0", "id": 2, "result": ["b"]}));
+}
+
+#[tokio::test]
+async fn http_invalid_requests() {
+    let url = start_server(Limits::default().with_max_frame_length(64)).await;
+    let (status, body) = post(&url, "{").await;
+    assert_eq!(status, StatusCode::OK);
+    let body = body.unwrap();
+    assert_eq!(body["id"], Value::Null);
+    assert_eq!(body["error"]["code"], json!(-32700));
+
+    let (status, body) = post(&url, r#"{"jsonrpc":"2.0","id":1,"result":1}"#).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body.unwrap()["error"]["code"], json!(-32600));
+
+    let large = format!(
+        r#"{{"jsonrpc":"2.0","method":"log","params":["{}"]}}"#,
+        "a".repeat(64)
+    );
+    let (status, _) = post(&url, &large).await;
+    assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+
+    let request = Request::builder().uri(&url).body(Body::empty()).unwrap();
+    let response = Client::new().request(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+}
+
+/// Echo RPC trait answered by `EchoHandler`.
+#[net3_rpc_derive::rpc]
+trait Echo {
+    #[rpc]
+    async fn echo(&mut self, value: &u64) -> net3_rpc_error::Result<u64>;
+}
+
+#[tokio::test]
+async fn http_client_request() {
+    let url = start_server(Limits::default()).await;
+    let client = HttpClient::new(&url).unwrap();
+    let res: Vec<u64> = client.request("echo", Some(&vec![1, 2])).await.unwrap();
+    assert_eq!(res, vec![1, 2]);
+    let res: Option<Vec<u64>> = client.request_empty_opt("echo").await.unwrap();
+    assert_eq!(res, None);
+    client
+        .send_notification("log", Some(&vec![1]))
+        .await
+        .unwrap();
+
+    let mut rpc = client.clone();
+    assert_eq!(rpc.echo(&7).await.unwrap(), 7);
+
+    let client = HttpClient::builder()
+        .with_call_timeout(Duration::from_millis(50))
+        .build(&url)
+        .unwrap();
+    match client.request::<(), Value>("sleep", None).await {
+        Err(net3_rpc_error::Error::Io(err)) => {
+            assert_eq!(err.kind(), std::io::ErrorKind::TimedOut)
+        }
+        res => panic!("unexpected result: {:?}", res),
+    }
+}
+
+#[tokio::test]
+async fn http_client_batch() {
+    let url = start_server(Limits::default()).await;
+    let client = HttpClient::builder()
+        .with_id_generator(net3_rpc_client::IdGenerator::Numeric)
+        .build(&url)
+        .unwrap();
+    let mut batch = client.batch();
+    let first = batch.request::<_, String>("echo", Some(&"a")).unwrap();
+    batch.notification("log", Some(&"b")).unwrap();
+    let second = batch.request::<_, String>("echo", Some(&"c")).unwrap();
+    assert_eq!(batch.len(), 3);
+    batch.send().await.unwrap();
+    assert_eq!(second.response().await.unwrap(), "c");
+    assert_eq!(first.response().await.unwrap(), "a");
+
+    let mut batch = client.batch();
+    batch.notification("log", Some(&"d")).unwrap();
+    batch.send().await.unwrap();
+
+    let mut batch = client.batch();
+    let skipped = batch.request::<_, String>("skip", Some(&"e")).unwrap();
+    let answered = batch.request::<_, String>("echo", Some(&"f")).unwrap();
+    batch.send().await.unwrap();
+    assert_eq!(answered.response().await.unwrap(), "f");
+    match skipped.response().await {
+        Err(net3_rpc_error::Error::Io(err)) => {
+            assert_eq!(err.kind(), std::io::ErrorKind::InvalidData)
+        }
+        res => panic!("unexpected result: {:?}", res),
+    }
+}
+
+#[tokio::test]
+async fn http_client_errors() {
+    let url = start_server(Limits::default().with_max_frame_length(64)).await;
+    let client = HttpClient::new(&url).unwrap();
+    let large = "a".repeat(64);
+    match client.request::<_, String>("echo", Some(&large)).await {
+        Err(net3_rpc_error::Error::Io(_)) => {}
+        res => panic!("unexpected result: {:?}", res),
+    }
+
+    let client = HttpClient::builder()
+        .with_limits(Limits::default().with_max_frame_length(16))
+        .build(&url)
+        .unwrap();
+    match client.request::<_, String>("echo", Some(&"a")).await {
+        Err(net3_rpc_error::Error::Io(err)) => assert!(LimitError::from_io(&err).is_some()),
+        res => panic!("unexpected result: {:?}", res),
+    }
+}
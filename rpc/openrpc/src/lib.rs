@@ -0,0 +1,259 @@
+//! OpenRPC service description documents.
+//!
+//! Documents of `#[rpc(openrpc)]` traits are generated by the derive macro
+//! and returned by the [`DISCOVER_METHOD`] method.
+//! Parameter and result schemas are created from [`JsonSchema`] implementations.
+//!
+//! [`DISCOVER_METHOD`]: constant.DISCOVER_METHOD.html
+//! [`JsonSchema`]: https://docs.rs/schemars/0.8/schemars/trait.JsonSchema.html
+//!
+//! See [OpenRPC specification](https://spec.open-rpc.org/).
+
+#[macro_use]
+extern crate serde_derive;
+
+use std::collections::BTreeMap;
+
+use schemars::{
+    gen::{SchemaGenerator, SchemaSettings},
+    schema::{Schema, SingleOrVec},
+    JsonSchema,
+};
+
+use net3_proto_jsonrpc::code;
+
+pub use schemars;
+
+/// Version of the OpenRPC specification.
+pub const OPENRPC_VERSION: &str = "1.2.6";
+
+/// Name of the service discovery method.
+pub const DISCOVER_METHOD: &str = "rpc.discover";
+
+/// OpenRPC document.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OpenRpc {
+    /// Version of the OpenRPC specification.
+    pub openrpc: String,
+
+    /// Service metadata.
+    pub info: Info,
+
+    /// Service methods.
+    pub methods: Vec<Method>,
+
+    /// Reusable schemas.
+    #[serde(default, skip_serializing_if = "Components::is_empty")]
+    pub components: Components,
+}
+
+/// Service metadata.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Info {
+    /// Service title.
+    pub title: String,
+
+    /// Service version.
+    pub version: String,
+
+    /// Service description.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+/// Method description.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Method {
+    /// Method name.
+    pub name: String,
+
+    /// Method description.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    /// Method parameters.
+    pub params: Vec<ContentDescriptor>,
+
+    /// Method result.
+    pub result: ContentDescriptor,
+
+    /// Errors returned by the method.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<Error>,
+
+    /// Structure of the parameters.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub param_structure: Option<ParamStructure>,
+}
+
+/// Structure of method parameters.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ParamStructure {
+    /// Parameters are sent as an object.
+    ByName,
+
+    /// Parameters are sent as an array.
+    ByPosition,
+
+    /// Parameters are sent as an object or an array.
+    Either,
+}
+
+/// Parameter or result description.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContentDescriptor {
+    /// Name of the content.
+    pub name: String,
+
+    /// JSON schema of the content.
+    pub schema: Schema,
+
+    /// True if the content is required.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub required: bool,
+}
+
+/// Application defined error.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Error {
+    /// Error code.
+    pub code: i64,
+
+    /// Error message.
+    pub message: String,
+}
+
+impl Error {
+    /// Returns an error of invalid method parameters.
+    #[inline]
+    pub fn invalid_params() -> Self {
+        Error {
+            code: code::INVALID_PARAMS,
+            message: "Invalid params".to_owned(),
+        }
+    }
+}
+
+/// Reusable document components.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Components {
+    /// JSON schemas by name.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub schemas: BTreeMap<String, Schema>,
+}
+
+impl Components {
+    /// Returns true if there are no components.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.schemas.is_empty()
+    }
+}
+
+/// OpenRPC document generator.
+///
+/// Schemas of structures are referenced from document components.
+pub struct Generator {
+    document: OpenRpc,
+    schemas: SchemaGenerator,
+}
+
+impl Generator {
+    /// Creates a generator of a service document.
+    pub fn new(title: &str, version: &str) -> Self {
+        let settings = SchemaSettings::draft07().with(|settings| {
+            settings.definitions_path = "#/components/schemas/".to_owned();
+        });
+        Generator {
+            document: OpenRpc {
+                openrpc: OPENRPC_VERSION.to_owned(),
+                info: Info {
+                    title: title.to_owned(),
+                    version: version.to_owned(),
+                    description: None,
+                },
+                methods: Vec::new(),
+                components: Components::default(),
+            },
+            schemas: settings.into_generator(),
+        }
+    }
+
+    /// Sets service description.
+    pub fn set_description(&mut self, description: &str) {
+        self.document.info.description = Some(description.to_owned());
+    }
+
+    /// Creates a required content descriptor of a type.
+    pub fn content<T: JsonSchema>(&mut self, name: &str) -> ContentDescriptor {
+        ContentDescriptor {
+            name: name.to_owned(),
+            schema: self.schemas.subschema_for::<T>(),
+            required: true,
+        }
+    }
+
+    /// Creates descriptors of parameters sent as a single value of a type.
+    ///
+    /// Properties of an object are described as parameters by name
+    /// and items of a tuple as parameters by position.
+    /// Other values are described as a single parameter of given name.
+    pub fn value_params<T: JsonSchema>(
+        &mut self,
+        name: &str,
+    ) -> (Vec<ContentDescriptor>, Option<ParamStructure>) {
+        let schema = self.schemas.subschema_for::<T>();
+        let object = match self.schemas.dereference(&schema).unwrap_or(&schema) {
+            Schema::Object(object) => object,
+            Schema::Bool(_) => return (vec![required(name.to_owned(), schema.clone())], None),
+        };
+        if let Some(validation) = &object.object {
+            let params = validation
+                .properties
+                .iter()
+                .map(|(name, schema)| ContentDescriptor {
+                    name: name.clone(),
+                    schema: schema.clone(),
+                    required: validation.required.contains(name),
+                })
+                .collect();
+            return (params, Some(ParamStructure::ByName));
+        }
+        let items = object.array.as_ref().and_then(|array| array.items.as_ref());
+        if let Some(SingleOrVec::Vec(items)) = items {
+            let params = items
+                .iter()
+                .enumerate()
+                .map(|(index, schema)| required(format!("{}{}", name, index), schema.clone()))
+                .collect();
+            return (params, Some(ParamStructure::ByPosition));
+        }
+        (vec![required(name.to_owned(), schema.clone())], None)
+    }
+
+    /// Adds a method to the document.
+    pub fn add_method(&mut self, method: Method) {
+        self.document.methods.push(method);
+    }
+
+    /// Returns the document.
+    pub fn build(mut self) -> OpenRpc {
+        self.document.components.schemas = self.schemas.take_definitions().into_iter().collect();
+        self.document
+    }
+}
+
+/// Returns a required content descriptor.
+#[inline]
+fn required(name: String, schema: Schema) -> ContentDescriptor {
+    ContentDescriptor {
+        name,
+        schema,
+        required: true,
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,84 @@
+use serde_json::json;
+
+use crate::*;
+
+#[derive(JsonSchema)]
+#[allow(dead_code)]
+struct Job {
+    id: String,
+}
+
+#[test]
+fn generate_document() {
+    let mut generator = Generator::new("Pool", "1.0.0");
+    generator.set_description("Mining pool.");
+    let params = vec![generator.content::<String>("worker")];
+    let result = generator.content::<Job>("result");
+    generator.add_method(Method {
+        name: "job".to_owned(),
+        description: None,
+        params,
+        result,
+        errors: vec![Error {
+            code: 24,
+            message: "Unauthorized worker".to_owned(),
+        }],
+        param_structure: Some(ParamStructure::ByPosition),
+    });
+    let document = serde_json::to_value(generator.build()).unwrap();
+    assert_eq!(
+        document,
+        json!({
+            "openrpc": OPENRPC_VERSION,
+            "info": {"title": "Pool", "version": "1.0.0", "description": "Mining pool."},
+            "methods": [{
+                "name": "job",
+                "params": [{"name": "worker", "schema": {"type": "string"}, "required": true}],
+                "result": {
+                    "name": "result",
+                    "schema": {"$ref": "#/components/schemas/Job"},
+                    "required": true,
+                },
+                "errors": [{"code": 24, "message": "Unauthorized worker"}],
+                "paramStructure": "by-position",
+            }],
+            "components": {
+                "schemas": {
+                    "Job": {
+                        "type": "object",
+                        "required": ["id"],
+                        "properties": {"id": {"type": "string"}},
+                    },
+                },
+            },
+        })
+    );
+}
+
+#[derive(JsonSchema)]
+#[allow(dead_code)]
+struct Share {
+    job_id: String,
+    nonce: Option<u64>,
+}
+
+#[test]
+fn value_params() {
+    let mut generator = Generator::new("Pool", "1.0.0");
+    let (params, structure) = generator.value_params::<Share>("share");
+    assert_eq!(structure, Some(ParamStructure::ByName));
+    assert_eq!(
+        serde_json::to_value(&params[0]).unwrap(),
+        json!({"name": "job_id", "schema": {"type": "string"}, "required": true})
+    );
+    assert_eq!(params[1].name, "nonce");
+    assert!(!params[1].required);
+    let (params, structure) = generator.value_params::<(String, u32)>("job");
+    assert_eq!(structure, Some(ParamStructure::ByPosition));
+    assert_eq!(params.len(), 2);
+    assert_eq!(params[0].name, "job0");
+    let (params, structure) = generator.value_params::<String>("worker");
+    assert_eq!(structure, None);
+    assert_eq!(params, vec![generator.content::<String>("worker")]);
+    assert_eq!(Error::invalid_params().code, -32602);
+}
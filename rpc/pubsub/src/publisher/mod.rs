@@ -1,20 +1,24 @@
 //! Internal job channel server.
 
-use std::io::{Error, ErrorKind};
+use std::io::ErrorKind;
 
 use serde::ser::Serialize;
 
-use tokio::{stream::StreamExt, sync::mpsc};
+use tokio::stream::StreamExt;
 
 // use ice_msgpack_proto::{client::Handle, server, Message};
 // use ice_net_channel::router::{Builder as Router, InitFunc};
 use net3_msg::{builder, traits::Message};
-use net3_rpc_client::{common::InitFunc, Handle};
+use net3_rpc_client::{
+    common::InitFunc,
+    queue::{self, QueueConfig, QueueReceiver, QueueSender},
+    Handle,
+};
 
 /// Publisher structure.
 #[derive(Clone)]
 pub struct Publisher<M: Message> {
-    sender: mpsc::UnboundedSender<M>,
+    sender: QueueSender<M>,
 }
 
 impl<M> Publisher<M>
@@ -24,21 +28,33 @@ where
     M: builder::MessageBuilderExt<Builder = M>,
 {
     /// Publishes a job.
+    ///
+    /// Fails with `WouldBlock` if the publisher queue is full.
     pub fn publish<T: Serialize>(&self, channel: &str, data: Option<&T>) -> std::io::Result<()> {
-        self.sender
-            .send(builder::new_event::<M, T>(channel, data)?)
-            .map_err(|err| Error::new(ErrorKind::ConnectionReset, err))
+        Ok(self
+            .sender
+            .try_send(builder::new_event::<M, T>(channel, data)?)?)
+    }
+
+    /// Publishes a job, waiting for space in the publisher queue.
+    pub async fn publish_async<T: Serialize>(
+        &self,
+        channel: &str,
+        data: Option<&T>,
+    ) -> std::io::Result<()> {
+        let message = builder::new_event::<M, T>(channel, data)?;
+        Ok(self.sender.send(message).await?)
     }
 }
 
 /// Publisher builder.
 pub struct Builder<M: Message, U = ()> {
     // Communication with the server
-    hnd_sender: mpsc::UnboundedSender<Handle<M, U>>,
-    hnd_receiver: mpsc::UnboundedReceiver<Handle<M, U>>,
+    hnd_sender: QueueSender<Handle<M, U>>,
+    hnd_receiver: QueueReceiver<Handle<M, U>>,
     // Communication with publishers
-    msg_sender: mpsc::UnboundedSender<M>,
-    msg_receiver: mpsc::UnboundedReceiver<M>,
+    msg_sender: QueueSender<M>,
+    msg_receiver: QueueReceiver<M>,
 }
 
 impl<M: Message + 'static, U: 'static + Send> Builder<M, U> {
@@ -49,30 +65,59 @@ impl<M: Message + 'static, U: 'static + Send> Builder<M, U> {
         }
     }
 
+    /// Sets capacity and overflow policy of the queue of published messages.
+    ///
+    /// Queue is unbounded by default. It has to be set before creating publishers,
+    /// publishers created before are disconnected.
+    pub fn with_queue(mut self, config: QueueConfig) -> Self {
+        let (msg_sender, msg_receiver) = queue::channel(config);
+        self.msg_sender = msg_sender;
+        self.msg_receiver = msg_receiver;
+        self
+    }
+
+    /// Sets capacity and overflow policy of the queue of registered subscribers.
+    ///
+    /// Queue is unbounded by default. It has to be set before creating registration
+    /// senders and server initialization functions, senders created before are disconnected.
+    pub fn with_registration_queue(mut self, config: QueueConfig) -> Self {
+        let (hnd_sender, hnd_receiver) = queue::channel(config);
+        self.hnd_sender = hnd_sender;
+        self.hnd_receiver = hnd_receiver;
+        self
+    }
+
     /// Spawns publisher in the background.
     pub fn background(self) -> tokio::task::JoinHandle<std::io::Result<()>> {
         tokio::spawn(async move { self.start().await })
     }
 
     /// Binds a publisher to a TCP address and starts publisher loop.
+    ///
+    /// Messages are delivered without waiting, according to the message queue policy of
+    /// each subscriber. Subscribers with a full queue miss the message, disconnected
+    /// subscribers are removed.
     pub async fn start(mut self) -> std::io::Result<()> {
-        let mut handles = Vec::new();
+        let mut handles: Vec<Handle<M, U>> = Vec::new();
         loop {
             tokio::select! {
                 message = self.msg_receiver.next() => match message {
                     Some(message) => {
-                        handles = handles
-                            .into_iter()
-                            .filter(|handle: &Handle<M, U> | {
-                                handle.send(message.clone()).is_ok()
-                            })
-                            .collect();
+                        handles.retain(|handle| match handle.send(message.clone()) {
+                            Err(err) if err.kind() == ErrorKind::WouldBlock => {
+                                log::debug!("Subscriber queue is full, message dropped");
+                                true
+                            }
+                            result => result.is_ok(),
+                        });
                     },
-                    None => return Ok(()),
+                    // Builder holds a sender, queue ends only when disconnected by an overflow.
+                    None => log::debug!("Publisher queue overflow, queued messages dropped"),
                 },
                 handle = self.hnd_receiver.next() => match handle {
                     Some(handle) => handles.push(handle),
-                    None => return Ok(()),
+                    // Builder holds a sender, queue ends only when disconnected by an overflow.
+                    None => log::debug!("Registration queue overflow, queued subscribers dropped"),
                 }
             }
         }
@@ -80,7 +125,7 @@ impl<M: Message + 'static, U: 'static + Send> Builder<M, U> {
 
     /// Returns handle register channel.
     #[inline]
-    pub fn registration(&self) -> mpsc::UnboundedSender<Handle<M, U>> {
+    pub fn registration(&self) -> QueueSender<Handle<M, U>> {
         self.hnd_sender.clone()
     }
 
@@ -88,7 +133,9 @@ impl<M: Message + 'static, U: 'static + Send> Builder<M, U> {
     pub fn server_init_fn(&self) -> InitFunc<M, U> {
         let sender = self.registration();
         Box::new(move |handle| {
-            let _ = sender.send(handle.clone());
+            if let Err(err) = sender.try_send(handle.clone()) {
+                log::warn!("Subscriber registration failed: {}", err);
+            }
         })
     }
 
@@ -106,8 +153,8 @@ impl<M: Message + 'static, U: 'static + Send> Builder<M, U> {
 
 impl<M: Message, U> Default for Builder<M, U> {
     fn default() -> Self {
-        let (hnd_sender, hnd_receiver) = mpsc::unbounded_channel();
-        let (msg_sender, msg_receiver) = mpsc::unbounded_channel();
+        let (hnd_sender, hnd_receiver) = queue::channel(QueueConfig::default());
+        let (msg_sender, msg_receiver) = queue::channel(QueueConfig::default());
         Builder {
             hnd_sender,
             hnd_receiver,
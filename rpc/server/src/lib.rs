@@ -60,8 +60,10 @@ use tokio::{
 };
 use tokio_util::codec::{Decoder, Encoder};
 
-use net3_msg::traits::Message;
-pub use net3_rpc_client::{common, Handler, HandlerBuilder};
+use net3_msg::batch::Frame;
+pub use net3_rpc_client::{
+    common, Handler, HandlerBuilder, LimitError, LimitedCodec, Limits, Overflow, QueueConfig,
+};
 use net3_rpc_client::{Builder as ClientBuilder, ClientHandle};
 
 /// Network channel [`Server`] builder utility.
@@ -69,6 +71,12 @@ use net3_rpc_client::{Builder as ClientBuilder, ClientHandle};
 /// [`Server`]: struct.Server.html
 pub struct ServerBuilder<C, B> {
     builder: B,
+    limits: Limits,
+    cancel_method: Option<String>,
+    max_concurrent_requests: Option<usize>,
+    message_queue: QueueConfig,
+    event_queue: QueueConfig,
+    read_ahead_queue: Option<QueueConfig>,
     codec: PhantomData<C>,
 }
 
@@ -84,6 +92,66 @@ impl<C, B> ServerBuilder<C, B> {
         self
     }
 
+    /// Sets codec resource limits applied to every accepted connection.
+    ///
+    /// Default limits are defined in [`Limits`].
+    ///
+    /// [`Limits`]: ../net3_codec_limits/struct.Limits.html
+    pub fn with_limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Enables remote cancellation of requests using given notification method name.
+    ///
+    /// See [`ClientBuilder::with_cancel_method`].
+    ///
+    /// [`ClientBuilder::with_cancel_method`]: ../net3_rpc_client/builder/struct.Builder.html#method.with_cancel_method
+    pub fn with_cancel_method<T: ToString>(mut self, method: T) -> Self {
+        self.cancel_method = Some(method.to_string());
+        self
+    }
+
+    /// Handles up to `limit` requests of every connection concurrently.
+    ///
+    /// See [`ClientBuilder::with_concurrent_requests`].
+    ///
+    /// [`ClientBuilder::with_concurrent_requests`]: ../net3_rpc_client/builder/struct.Builder.html#method.with_concurrent_requests
+    pub fn with_concurrent_requests(mut self, limit: usize) -> Self {
+        self.max_concurrent_requests = Some(limit);
+        self
+    }
+
+    /// Sets capacity and overflow policy of the outbound message queue of every connection.
+    ///
+    /// See [`ClientBuilder::with_message_queue`].
+    ///
+    /// [`ClientBuilder::with_message_queue`]: ../net3_rpc_client/builder/struct.Builder.html#method.with_message_queue
+    pub fn with_message_queue(mut self, config: QueueConfig) -> Self {
+        self.message_queue = config;
+        self
+    }
+
+    /// Sets capacity and overflow policy of the internal event queue of every connection.
+    ///
+    /// See [`ClientBuilder::with_event_queue`].
+    ///
+    /// [`ClientBuilder::with_event_queue`]: ../net3_rpc_client/builder/struct.Builder.html#method.with_event_queue
+    pub fn with_event_queue(mut self, config: QueueConfig) -> Self {
+        self.event_queue = config;
+        self
+    }
+
+    /// Sets queue of frames read ahead by every connection while a request is handled.
+    ///
+    /// See [`ClientBuilder::with_read_ahead_queue`].
+    ///
+    /// [`ClientBuilder::with_read_ahead_queue`]: ../net3_rpc_client/builder/struct.Builder.html#method.with_read_ahead_queue
+    pub fn with_read_ahead_queue(mut self, config: QueueConfig) -> Self {
+        self.read_ahead_queue = Some(config);
+        self
+    }
+
     /// Binds an asynchronous [`TcpListener`] to a set of addresses.
     ///
     /// Returns [`Server`] handle.
@@ -95,6 +163,12 @@ impl<C, B> ServerBuilder<C, B> {
         Ok(Server {
             listener,
             builder: self.builder,
+            limits: self.limits,
+            cancel_method: self.cancel_method,
+            max_concurrent_requests: self.max_concurrent_requests,
+            message_queue: self.message_queue,
+            event_queue: self.event_queue,
+            read_ahead_queue: self.read_ahead_queue,
             codec: PhantomData,
         })
     }
@@ -104,6 +178,12 @@ impl<C, B: Default> Default for ServerBuilder<C, B> {
     fn default() -> Self {
         ServerBuilder {
             builder: Default::default(),
+            limits: Limits::default(),
+            cancel_method: None,
+            max_concurrent_requests: None,
+            message_queue: QueueConfig::default(),
+            event_queue: QueueConfig::default(),
+            read_ahead_queue: None,
             codec: PhantomData,
         }
     }
@@ -113,6 +193,12 @@ impl<C, B: HandlerBuilder> From<B> for ServerBuilder<C, B> {
     fn from(builder: B) -> Self {
         ServerBuilder {
             builder,
+            limits: Limits::default(),
+            cancel_method: None,
+            max_concurrent_requests: None,
+            message_queue: QueueConfig::default(),
+            event_queue: QueueConfig::default(),
+            read_ahead_queue: None,
             codec: PhantomData,
         }
     }
@@ -126,6 +212,12 @@ impl<C, B: HandlerBuilder> From<B> for ServerBuilder<C, B> {
 pub struct Server<C, B> {
     listener: TcpListener,
     builder: B,
+    limits: Limits,
+    cancel_method: Option<String>,
+    max_concurrent_requests: Option<usize>,
+    message_queue: QueueConfig,
+    event_queue: QueueConfig,
+    read_ahead_queue: Option<QueueConfig>,
     codec: PhantomData<C>,
 }
 
@@ -136,18 +228,28 @@ impl<C, B> Server<C, B> {
     pub fn builder(builder: B) -> ServerBuilder<C, B> {
         ServerBuilder {
             builder,
+            limits: Limits::default(),
+            cancel_method: None,
+            max_concurrent_requests: None,
+            message_queue: QueueConfig::default(),
+            event_queue: QueueConfig::default(),
+            read_ahead_queue: None,
             codec: PhantomData,
         }
     }
+
+    /// Returns local address the server is bound to.
+    pub fn local_addr(&self) -> std::io::Result<std::net::SocketAddr> {
+        self.listener.local_addr()
+    }
 }
 
 impl<C, B> Server<C, B>
 where
-    C: Default + Send + Sync + 'static,
+    C: Default + LimitedCodec + Send + Sync + 'static,
     B: HandlerBuilder + Send + Sync + 'static,
-    C: Decoder<Item = <<B as HandlerBuilder>::Handler as Handler>::Message, Error = std::io::Error>,
-    <C as Decoder>::Item: Message + Clone,
-    C: Encoder<<<B as HandlerBuilder>::Handler as Handler>::Message, Error = std::io::Error>,
+    C: Decoder<Error = std::io::Error> + Encoder<<C as Decoder>::Item, Error = std::io::Error>,
+    <C as Decoder>::Item: Frame<<<B as HandlerBuilder>::Handler as Handler>::Message> + Send,
     <B as HandlerBuilder>::Handler: Send + Sync + 'static,
     <<B as HandlerBuilder>::Handler as Handler>::Message: Clone,
 {
@@ -176,19 +278,37 @@ where
                 socket.peer_addr(),
                 connection + 1
             );
-            let builder = ClientBuilder::<C, RefBuilder<B>>::new()
+            let mut builder = ClientBuilder::<C, RefBuilder<B>>::new()
                 .with_id(connections)
+                .with_limits(self.limits)
+                .with_message_queue(self.message_queue)
+                .with_event_queue(self.event_queue)
                 .with_stream(socket)?
                 .with_handler_builder(builder.clone());
+            if let Some(method) = self.cancel_method.as_ref() {
+                builder = builder.with_cancel_method(method);
+            }
+            if let Some(limit) = self.max_concurrent_requests {
+                builder = builder.with_concurrent_requests(limit);
+            }
+            if let Some(config) = self.read_ahead_queue {
+                builder = builder.with_read_ahead_queue(config);
+            }
             connections += 1;
             tokio::spawn(async move {
-                if let Err(err) = builder.start().await {
-                    let connection = connected.fetch_sub(1, Ordering::SeqCst);
-                    log::debug!(
+                let result = builder.start().await;
+                let connection = connected.fetch_sub(1, Ordering::SeqCst);
+                match result {
+                    Ok(reason) => log::debug!(
+                        "Connection closed: {:?}. Total connected: {}",
+                        reason,
+                        connection - 1
+                    ),
+                    Err(err) => log::debug!(
                         "Connection error: {:?}. Total connected: {}",
                         err,
                         connection - 1
-                    );
+                    ),
                 }
             });
         }